@@ -0,0 +1,437 @@
+//! The age-layered population structure (ALPS) of Hornby.
+//!
+//! ALPS fights premature convergence by only letting individuals compete
+//! with others of a similar age, and by regularly injecting new random
+//! individuals.
+//!
+//! The population is split into layers, each of which has a limit on the
+//! [age](Aged) of the individuals in it (except the last layer, which has
+//! no limit). Each generation:
+//!   - every `injection_interval` generations the bottom layer is replaced with
+//!     new random individuals;
+//!   - each layer makes its children from its own individuals (and, optionally,
+//!     those in the layer below), keeping some elites;
+//!   - individuals that have become too old for their layer move up to the next
+//!     one if they're good enough to earn a place there.
+//!
+//! For ages to be meaningful, children have to inherit the birth generation
+//! of their parents, which the child maker does by wrapping the part of
+//! the pipeline that turns parents into a child in [`InheritBirth`].
+
+use anyhow::{ensure, Result};
+use itertools::Itertools;
+use rand::{prelude::Distribution, rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+use crate::{
+    generation::child_seeds,
+    individual::Aged,
+    operator::{Composable, Describe, Description, Operator, Prepare},
+};
+
+/// An operator that turns one or more parents into a child using another
+/// operator, and then sets the child's birth generation to that of its
+/// oldest parent.
+///
+/// This is typically used after selection, e.g.,
+/// `Select::new(selector).then(InheritBirth::new(GenomeExtractor.
+/// then(Mutate::new(mutator)).wrap::<GenomeScorer<_, _>>(scorer)))`.
+#[derive(Debug, Clone, Copy)]
+pub struct InheritBirth<C> {
+    make_child: C,
+}
+
+impl<C> InheritBirth<C> {
+    pub const fn new(make_child: C) -> Self {
+        Self { make_child }
+    }
+}
+
+impl<'a, I, C> Operator<&'a I> for InheritBirth<C>
+where
+    I: Aged,
+    C: Operator<&'a I>,
+    C::Output: Aged,
+{
+    type Output = C::Output;
+
+    fn apply<R: Rng + ?Sized>(&self, parent: &'a I, rng: &mut R) -> Result<Self::Output> {
+        let birth_generation = parent.birth_generation();
+        let mut child = self.make_child.apply(parent, rng)?;
+        child.set_birth_generation(birth_generation);
+        Ok(child)
+    }
+}
+
+impl<'a, I, C, const N: usize> Operator<[&'a I; N]> for InheritBirth<C>
+where
+    I: Aged,
+    C: Operator<[&'a I; N]>,
+    C::Output: Aged,
+{
+    type Output = C::Output;
+
+    fn apply<R: Rng + ?Sized>(&self, parents: [&'a I; N], rng: &mut R) -> Result<Self::Output> {
+        let birth_generation = parents
+            .iter()
+            .map(|parent| parent.birth_generation())
+            .min()
+            .unwrap_or_default();
+        let mut child = self.make_child.apply(parents, rng)?;
+        child.set_birth_generation(birth_generation);
+        Ok(child)
+    }
+}
+
+impl<C> Composable for InheritBirth<C> {}
+
+impl<C> Describe for InheritBirth<C>
+where
+    C: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("InheritBirth").with_child(self.make_child.describe())
+    }
+}
+
+/// Age limits that grow linearly: layer `i` holds individuals up to
+/// `age_gap * (i + 1)` generations old. There are `num_layers - 1` limits,
+/// since the last layer has no limit.
+///
+/// The age gap is usually the same as the injection interval, so the
+/// bottom layer doesn't age out before new individuals are injected.
+#[must_use]
+pub fn linear_age_limits(age_gap: usize, num_layers: usize) -> Vec<usize> {
+    (1..num_layers).map(|layer| age_gap * layer).collect()
+}
+
+/// An age-layered population, where `C` makes children from the
+/// individuals in a layer (as a `Vec`), and `G` generates new random
+/// individuals for the bottom layer.
+pub struct Alps<I, C, G> {
+    layers: Vec<Vec<I>>,
+    age_limits: Vec<usize>,
+    layer_size: usize,
+    child_maker: C,
+    generator: G,
+    injection_interval: usize,
+    num_elites: usize,
+    parents_from_below: bool,
+    generation_number: usize,
+}
+
+impl<I, C, G> Alps<I, C, G> {
+    /// Create an ALPS population with one layer per age limit plus a last
+    /// layer with no age limit, each of which holds (up to) `layer_size`
+    /// individuals.
+    ///
+    /// The layers start out empty; the bottom layer is filled with random
+    /// individuals at the start of the first generation. By default, new
+    /// individuals are injected every 10 generations, each layer keeps its
+    /// best individual, and parents can be drawn from the layer below.
+    pub fn new(child_maker: C, generator: G, layer_size: usize, age_limits: Vec<usize>) -> Self {
+        Self {
+            layers: std::iter::repeat_with(Vec::new)
+                .take(age_limits.len() + 1)
+                .collect(),
+            age_limits,
+            layer_size,
+            child_maker,
+            generator,
+            injection_interval: 10,
+            num_elites: 1,
+            parents_from_below: true,
+            generation_number: 0,
+        }
+    }
+
+    /// Replace the bottom layer with new random individuals every
+    /// `injection_interval` generations.
+    #[must_use]
+    pub const fn with_injection_interval(mut self, injection_interval: usize) -> Self {
+        self.injection_interval = injection_interval;
+        self
+    }
+
+    /// Carry over the `num_elites` best individuals in each layer into the
+    /// next generation.
+    #[must_use]
+    pub const fn with_num_elites(mut self, num_elites: usize) -> Self {
+        self.num_elites = num_elites;
+        self
+    }
+
+    /// Whether the parents for a layer are drawn from that layer and the
+    /// layer below (`true`), or from just that layer (`false`).
+    #[must_use]
+    pub const fn with_parents_from_below(mut self, parents_from_below: bool) -> Self {
+        self.parents_from_below = parents_from_below;
+        self
+    }
+
+    /// The individuals in each layer, starting with the bottom (youngest)
+    /// layer.
+    pub fn layers(&self) -> &[Vec<I>] {
+        &self.layers
+    }
+
+    pub fn age_limits(&self) -> &[usize] {
+        &self.age_limits
+    }
+
+    /// The number of generations that have been run.
+    pub const fn generation_number(&self) -> usize {
+        self.generation_number
+    }
+}
+
+impl<I: Ord, C, G> Alps<I, C, G> {
+    /// The best individual in any layer, or `None` if every layer is empty.
+    pub fn best(&self) -> Option<&I> {
+        self.layers.iter().flatten().max()
+    }
+}
+
+impl<I, C, G> Alps<I, C, G>
+where
+    I: Aged + Ord + Clone,
+    G: Distribution<I>,
+{
+    /// Replace the bottom layer with new random individuals if it's time
+    /// to, and return the populations each layer's parents are drawn from.
+    fn start_generation<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Result<Vec<Vec<I>>> {
+        ensure!(
+            self.injection_interval > 0,
+            "The injection interval must be positive"
+        );
+        if self
+            .generation_number
+            .is_multiple_of(self.injection_interval)
+        {
+            self.layers[0] = (0..self.layer_size)
+                .map(|_| {
+                    let mut individual = self.generator.sample(rng);
+                    individual.set_birth_generation(self.generation_number);
+                    individual
+                })
+                .collect();
+        }
+        Ok((0..self.layers.len())
+            .map(|layer| {
+                let mut parents = self.layers[layer].clone();
+                if self.parents_from_below && layer > 0 {
+                    parents.extend_from_slice(&self.layers[layer - 1]);
+                }
+                parents
+            })
+            .collect())
+    }
+
+    /// Replace each layer with its children and elites, and then move
+    /// individuals that are too old for their layer up to the next one.
+    fn finish_generation(&mut self, children: Vec<Vec<I>>) {
+        for (layer, mut children) in self.layers.iter_mut().zip(children) {
+            layer.sort_unstable_by(|a, b| b.cmp(a));
+            children.extend(layer.drain(..).take(self.num_elites));
+            *layer = best(children, self.layer_size);
+        }
+        self.generation_number += 1;
+
+        for (index, &age_limit) in self.age_limits.iter().enumerate() {
+            let (staying, too_old): (Vec<_>, Vec<_>) = std::mem::take(&mut self.layers[index])
+                .into_iter()
+                .partition(|individual| individual.age(self.generation_number) <= age_limit);
+            self.layers[index] = staying;
+            let next_layer = &mut self.layers[index + 1];
+            next_layer.extend(too_old);
+            *next_layer = best(std::mem::take(next_layer), self.layer_size);
+        }
+    }
+}
+
+/// The `size` best individuals, best first.
+fn best<I: Ord>(mut individuals: Vec<I>, size: usize) -> Vec<I> {
+    individuals.sort_unstable_by(|a, b| b.cmp(a));
+    individuals.truncate(size);
+    individuals
+}
+
+impl<I, C, G> Alps<I, C, G>
+where
+    I: Aged + Ord + Clone + Send + Sync,
+    C: Prepare<Vec<I>>,
+    C::Prepared: for<'a> Operator<&'a Vec<I>, Output = I> + Send + Sync,
+    G: Distribution<I>,
+{
+    /// Run one generation, making each layer's children using a Rayon
+    /// parallel iterator.
+    ///
+    /// As with [`Generation`](crate::generation::Generation), the `seed`
+    /// completely determines the random choices made, so this gives the
+    /// same result as `serial_next` given the same seed.
+    ///
+    /// # Errors
+    /// This returns an error if the injection interval is zero, or if
+    /// preparing the child maker or making the children fails.
+    pub fn par_next(&mut self, seed: u64) -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let parent_populations = self.start_generation(&mut rng)?;
+        let children = parent_populations
+            .iter()
+            .map(|parents| {
+                let seeds = child_seeds(&mut rng, self.layer_size);
+                if parents.is_empty() {
+                    return Ok(Vec::new());
+                }
+                let child_maker = self.child_maker.prepare(parents)?;
+                seeds
+                    .into_par_iter()
+                    .map(|child_seed| {
+                        let mut rng = StdRng::seed_from_u64(child_seed);
+                        child_maker.apply(parents, &mut rng)
+                    })
+                    .collect()
+            })
+            .collect::<Result<_>>()?;
+        self.finish_generation(children);
+        Ok(())
+    }
+}
+
+impl<I, C, G> Alps<I, C, G>
+where
+    I: Aged + Ord + Clone,
+    C: Prepare<Vec<I>>,
+    C::Prepared: for<'a> Operator<&'a Vec<I>, Output = I>,
+    G: Distribution<I>,
+{
+    /// Run one generation, making the children serially.
+    ///
+    /// # Errors
+    /// This returns an error if the injection interval is zero, or if
+    /// preparing the child maker or making the children fails.
+    pub fn serial_next(&mut self, seed: u64) -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let parent_populations = self.start_generation(&mut rng)?;
+        let children = parent_populations
+            .iter()
+            .map(|parents| {
+                let seeds = child_seeds(&mut rng, self.layer_size);
+                if parents.is_empty() {
+                    return Ok(Vec::new());
+                }
+                let child_maker = self.child_maker.prepare(parents)?;
+                seeds
+                    .into_iter()
+                    .map(|child_seed| {
+                        let mut rng = StdRng::seed_from_u64(child_seed);
+                        child_maker.apply(parents, &mut rng)
+                    })
+                    .try_collect()
+            })
+            .collect::<Result<_>>()?;
+        self.finish_generation(children);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::distributions::Uniform;
+
+    use super::*;
+    use crate::{
+        individual::{
+            ec::{EcIndividual, IndividualGenerator},
+            scorer::FnScorer,
+            Individual,
+        },
+        operator::{
+            genome_extractor::GenomeExtractor,
+            genome_scorer::GenomeScorer,
+            selector::{random::Random, Select},
+        },
+        test_results::Score,
+    };
+
+    type Scored = EcIndividual<i32, Score<i32>>;
+
+    #[derive(Clone, Copy)]
+    struct AddOne;
+
+    impl Operator<i32> for AddOne {
+        type Output = i32;
+
+        fn apply<R: Rng + ?Sized>(&self, genome: i32, _: &mut R) -> Result<i32> {
+            Ok(genome + 1)
+        }
+    }
+    impl Composable for AddOne {}
+
+    #[derive(Clone, Copy)]
+    struct AddGenomes;
+
+    impl<'a> Operator<[&'a Scored; 2]> for AddGenomes {
+        type Output = Scored;
+
+        fn apply<R: Rng + ?Sized>(&self, [a, b]: [&'a Scored; 2], _: &mut R) -> Result<Scored> {
+            Ok(Scored::new(a.genome + b.genome, Score::from(0)))
+        }
+    }
+    impl Composable for AddGenomes {}
+
+    #[test]
+    fn linear_limits() {
+        assert_eq!(vec![5, 10, 15], linear_age_limits(5, 4));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn children_inherit_the_oldest_birth_generation() {
+        let make_child = InheritBirth::new(
+            GenomeExtractor
+                .then(AddOne)
+                .wrap::<GenomeScorer<_, _>>(FnScorer(|&genome: &i32| Score::from(genome))),
+        );
+        let mut rng = rand::thread_rng();
+
+        let parent = Scored::new(3, Score::from(3)).with_birth_generation(7);
+        let child = make_child.apply(&parent, &mut rng).unwrap();
+        assert_eq!((4, 7), (child.genome, child.birth_generation));
+
+        let make_child = InheritBirth::new(AddGenomes);
+        let other = Scored::new(1, Score::from(1)).with_birth_generation(2);
+        let child = make_child.apply([&parent, &other], &mut rng).unwrap();
+        assert_eq!((4, 2), (child.genome, child.birth_generation));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn old_individuals_move_up() {
+        let scorer = FnScorer(|&genome: &i32| Score::from(genome));
+        let generator = IndividualGenerator::new(Uniform::new(0, 100).unwrap(), scorer);
+        let make_child = Select::new(Random).then(InheritBirth::new(
+            GenomeExtractor
+                .then(AddOne)
+                .wrap::<GenomeScorer<_, _>>(scorer),
+        ));
+        let mut alps = Alps::new(make_child, generator, 10, linear_age_limits(3, 3))
+            .with_injection_interval(3);
+        let mut rng = rand::thread_rng();
+        for _ in 0..12 {
+            alps.serial_next(rng.gen()).unwrap();
+            let generation = alps.generation_number();
+            for (layer, &age_limit) in alps.layers().iter().zip(alps.age_limits()) {
+                assert!(layer.len() <= 10);
+                assert!(layer
+                    .iter()
+                    .all(|individual| individual.age(generation) <= age_limit));
+            }
+        }
+        assert!(alps.layers().iter().all(|layer| !layer.is_empty()));
+        assert_eq!(
+            alps.best().map(Individual::genome),
+            alps.layers().iter().flatten().map(Individual::genome).max()
+        );
+    }
+}
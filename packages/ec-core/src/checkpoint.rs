@@ -0,0 +1,288 @@
+//! Saving the state of a [`Run`] to a file so it can be resumed later,
+//! e.g., after the machine it was running on was rebooted.
+//!
+//! A checkpoint holds the population, the state of the random number
+//! generator that seeds each generation, the run's stop conditions, and
+//! how far the run had progressed. The child maker and survival strategy
+//! are code rather than data, so they aren't saved; they have to be
+//! provided again when resuming.
+//!
+//! Resuming a run from a checkpoint produces exactly the same results as
+//! if the run had never been interrupted, as long as the random number
+//! generator can be serialized. The generators in `rand::rngs` can't be, so
+//! this module re-exports [`ChaCha8Rng`], which can; seed it with
+//! `SeedableRng::seed_from_u64` and pass it to the run as usual.
+
+use std::{
+    fs::{self, File},
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+use rand::Rng;
+/// A fast, seedable random number generator that can be saved in a
+/// [`Checkpoint`].
+pub use rand_chacha::ChaCha8Rng;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    generation::Generation,
+    operator::{Operator, Prepare},
+    population::Population,
+    run::{Run, RunParameters, RunProgress, RunSummary},
+    survival::Survival,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint<P, I, R> {
+    pub population: P,
+    pub rng: R,
+    pub parameters: RunParameters,
+    pub progress: RunProgress<I>,
+}
+
+impl<P, I, R> Checkpoint<P, I, R>
+where
+    P: Serialize,
+    I: Serialize,
+    R: Serialize,
+{
+    /// Write this checkpoint to `path` as JSON.
+    ///
+    /// The checkpoint is first written to a temporary file that is then
+    /// renamed to `path`, so an interrupted save never clobbers the
+    /// previous checkpoint.
+    ///
+    /// # Errors
+    /// This returns an error if the checkpoint can't be serialized or
+    /// written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let temp_path = path.with_extension("tmp");
+        let file = File::create(&temp_path)
+            .with_context(|| format!("Failed to create {}", temp_path.display()))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, self).context("Failed to serialize the checkpoint")?;
+        writer.flush()?;
+        fs::rename(&temp_path, path)
+            .with_context(|| format!("Failed to move the checkpoint to {}", path.display()))
+    }
+}
+
+impl<P, I, R> Checkpoint<P, I, R>
+where
+    P: DeserializeOwned,
+    I: DeserializeOwned,
+    R: DeserializeOwned,
+{
+    /// Read a checkpoint that was written by [`Checkpoint::save`].
+    ///
+    /// # Errors
+    /// This returns an error if the file can't be read, or doesn't
+    /// contain a checkpoint of the expected type.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("Failed to read a checkpoint from {}", path.display()))
+    }
+}
+
+impl<P, C, S> Run<'_, P, C, S>
+where
+    P: Population,
+{
+    /// Resume a run from `checkpoint`, returning the run along with the
+    /// random number generator that should be passed to it.
+    ///
+    /// `make_generation` rebuilds the `Generation` (with its child maker
+    /// and survival strategy) around the saved population. Any target or
+    /// observers need to be added to the returned run again.
+    pub fn from_checkpoint<R>(
+        checkpoint: Checkpoint<P, P::Individual, R>,
+        make_generation: impl FnOnce(P) -> Generation<P, C, S>,
+    ) -> (Self, R) {
+        let run = Self::new(make_generation(checkpoint.population))
+            .with_parameters(checkpoint.parameters)
+            .resume_from(checkpoint.progress);
+        (run, checkpoint.rng)
+    }
+}
+
+fn save_checkpoint<P, I, R>(
+    path: &Path,
+    interval: usize,
+    population: &P,
+    parameters: &RunParameters,
+    progress: &RunProgress<I>,
+    rng: &R,
+) -> Result<()>
+where
+    P: Serialize,
+    I: Serialize,
+    R: Serialize,
+{
+    if interval == 0 || !progress.generation_number.is_multiple_of(interval) {
+        return Ok(());
+    }
+    Checkpoint {
+        population,
+        rng,
+        parameters: *parameters,
+        progress: RunProgress {
+            generation_number: progress.generation_number,
+            num_evaluations: progress.num_evaluations,
+            num_score_errors: progress.num_score_errors,
+            best_ever: &progress.best_ever,
+            generations_since_improvement: progress.generations_since_improvement,
+            elapsed: progress.elapsed,
+        },
+    }
+    .save(path)
+}
+
+impl<P, C, S> Run<'_, P, C, S>
+where
+    P: Population + Serialize,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Ord + Clone + Serialize,
+    C: Prepare<P>,
+    C::Prepared: for<'pop> Operator<&'pop P, Output = P::Individual>,
+    S: Survival<P>,
+{
+    /// Run the evolutionary process serially, like `serial_run`, saving a
+    /// checkpoint to `path` every `interval` generations.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if `serial_run` would, or if saving a
+    /// checkpoint fails.
+    pub fn serial_run_with_checkpoints<R: Rng + Serialize>(
+        &mut self,
+        rng: &mut R,
+        path: impl AsRef<Path>,
+        interval: usize,
+    ) -> Result<RunSummary<P::Individual>> {
+        let path = path.as_ref();
+        self.run_with(
+            rng,
+            Generation::serial_next,
+            |generation, parameters, progress, rng| {
+                save_checkpoint(
+                    path,
+                    interval,
+                    generation.population(),
+                    parameters,
+                    progress,
+                    rng,
+                )
+            },
+        )
+    }
+}
+
+impl<P, C, S> Run<'_, P, C, S>
+where
+    P: Population + Serialize + Send + Sync,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Ord + Clone + Serialize + Send,
+    C: Prepare<P>,
+    C::Prepared: for<'pop> Operator<&'pop P, Output = P::Individual> + Send + Sync,
+    S: Survival<P>,
+{
+    /// Run the evolutionary process in parallel, like `par_run`, saving a
+    /// checkpoint to `path` every `interval` generations.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if `par_run` would, or if saving a
+    /// checkpoint fails.
+    pub fn par_run_with_checkpoints<R: Rng + Serialize>(
+        &mut self,
+        rng: &mut R,
+        path: impl AsRef<Path>,
+        interval: usize,
+    ) -> Result<RunSummary<P::Individual>> {
+        let path = path.as_ref();
+        self.run_with(
+            rng,
+            Generation::par_next,
+            |generation, parameters, progress, rng| {
+                save_checkpoint(
+                    path,
+                    interval,
+                    generation.population(),
+                    parameters,
+                    progress,
+                    rng,
+                )
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::operator::{
+        selector::{random::Random, Select},
+        Composable,
+    };
+
+    #[derive(Clone, Copy)]
+    struct AddNoise;
+
+    impl Operator<&i32> for AddNoise {
+        type Output = i32;
+
+        fn apply<R: Rng + ?Sized>(&self, input: &i32, rng: &mut R) -> Result<Self::Output> {
+            Ok(input + rng.gen_range(-10..10))
+        }
+    }
+    impl Composable for AddNoise {}
+
+    fn make_generation(
+        population: Vec<i32>,
+    ) -> Generation<
+        Vec<i32>,
+        impl Prepare<Vec<i32>, Prepared: for<'a> Operator<&'a Vec<i32>, Output = i32> + Send + Sync>,
+    > {
+        Generation::new(Select::new(Random).then(AddNoise), population)
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn resumed_run_matches_uninterrupted_run() {
+        let mut uninterrupted =
+            Run::new(make_generation((0..20).collect())).with_max_generations(6);
+        let expected = uninterrupted
+            .serial_run(&mut ChaCha8Rng::seed_from_u64(7))
+            .unwrap();
+
+        let path =
+            std::env::temp_dir().join(format!("ec-core-checkpoint-{}.json", std::process::id()));
+        let mut interrupted = Run::new(make_generation((0..20).collect())).with_max_generations(3);
+        interrupted
+            .serial_run_with_checkpoints(&mut ChaCha8Rng::seed_from_u64(7), &path, 3)
+            .unwrap();
+
+        let checkpoint: Checkpoint<Vec<i32>, i32, ChaCha8Rng> = Checkpoint::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(3, checkpoint.progress.generation_number);
+        let (resumed, mut rng) = Run::from_checkpoint(checkpoint, make_generation);
+        let mut resumed = resumed.with_max_generations(6);
+        let summary = resumed.serial_run(&mut rng).unwrap();
+
+        assert_eq!(
+            uninterrupted.generation().population(),
+            resumed.generation().population()
+        );
+        assert_eq!(expected.best, summary.best);
+        assert_eq!(expected.num_generations, summary.num_generations);
+        assert_eq!(expected.num_evaluations, summary.num_evaluations);
+    }
+}
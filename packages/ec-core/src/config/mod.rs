@@ -0,0 +1,669 @@
+//! Describing runs in TOML or JSON config files, so that parameter sweeps
+//! don't require recompiling.
+//!
+//! A [`RunConfig`] names the components of a run (the problem, selector,
+//! mutator, and recombinator) along with their parameters, e.g.,
+//!
+//! ```toml
+//! population_size = 1000
+//! num_generations = 100
+//! seed = 42
+//!
+//! [problem]
+//! name = "hiff"
+//! bit_length = 128
+//!
+//! [selector]
+//! name = "weighted"
+//! selectors = [
+//!     { name = "fast_lexicase", num_test_cases = 255, weight = 5 },
+//!     { name = "tournament", size = 2, weight = 995 },
+//! ]
+//!
+//! [mutator]
+//! name = "with_one_over_length"
+//!
+//! [recombinator]
+//! name = "two_point_xo"
+//! ```
+//!
+//! Each named component is built by looking its name up in a [`Registry`]
+//! of constructors. This crate provides the [selectors](selectors::selectors);
+//! crates with genome types provide the mutators and recombinators for
+//! them, and the problem is up to the application.
+//!
+//! Misspelled fields are rejected, both in the run config and in the
+//! components' parameters (see [`ComponentConfig::check_parameters`]).
+
+use std::{fs, path::Path};
+
+use anyhow::{bail, ensure, Context, Result};
+use rand::Rng;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+pub use self::{
+    registry::Registry,
+    shared::{SharedMutator, SharedRecombinator, SharedSelector},
+};
+use crate::{
+    generation::Generation,
+    individual::{ec::EcIndividual, scorer::Scorer},
+    operator::{
+        composable,
+        genome_extractor::GenomeExtractor,
+        genome_scorer::GenomeScorer,
+        mutator::{Mutate, Mutator},
+        recombinator::{Recombinator, Recombine},
+        select_parents::SelectParents,
+        Composable, Describe, Description, Operator, Prepare,
+    },
+    population::Population,
+    survival::Elitism,
+};
+
+mod registry;
+pub mod selectors;
+mod shared;
+
+/// A named component and its parameters.
+///
+/// The parameters are stored alongside the name, so in TOML a selector
+/// is written as
+///
+/// ```toml
+/// [selector]
+/// name = "tournament"
+/// size = 2
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentConfig {
+    pub name: String,
+    #[serde(flatten)]
+    pub parameters: Map<String, Value>,
+}
+
+impl ComponentConfig {
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            parameters: Map::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_parameter(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.parameters.insert(name.into(), value.into());
+        self
+    }
+
+    /// The value of the parameter `name`.
+    ///
+    /// # Errors
+    /// This returns an error if there's no such parameter, or its value
+    /// isn't a `T`.
+    pub fn parameter<T: DeserializeOwned>(&self, name: &str) -> Result<T> {
+        self.optional_parameter(name)?
+            .with_context(|| format!("`{}` is missing the required parameter `{name}`", self.name))
+    }
+
+    /// The value of the parameter `name`, or `None` if there's no such
+    /// parameter.
+    ///
+    /// # Errors
+    /// This returns an error if the parameter's value isn't a `T`.
+    pub fn optional_parameter<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>> {
+        self.parameters
+            .get(name)
+            .map(|value| {
+                T::deserialize(value).with_context(|| {
+                    format!(
+                        "The parameter `{name}` of `{}` has the wrong type: {value}",
+                        self.name
+                    )
+                })
+            })
+            .transpose()
+    }
+
+    /// The value of the parameter `name`, or `default` if there's no such
+    /// parameter.
+    ///
+    /// # Errors
+    /// This returns an error if the parameter's value isn't a `T`.
+    pub fn parameter_or<T: DeserializeOwned>(&self, name: &str, default: T) -> Result<T> {
+        Ok(self.optional_parameter(name)?.unwrap_or(default))
+    }
+
+    /// Check that every parameter is one of `names`, so that a misspelled
+    /// or unsupported parameter is reported rather than silently ignored.
+    ///
+    /// Constructors should call this with the names of all the parameters
+    /// they read.
+    ///
+    /// # Errors
+    /// This returns an error naming the first parameter that isn't in
+    /// `names`.
+    pub fn check_parameters(&self, names: &[&str]) -> Result<()> {
+        let Some(unknown) = self
+            .parameters
+            .keys()
+            .find(|parameter| !names.contains(&parameter.as_str()))
+        else {
+            return Ok(());
+        };
+        if names.is_empty() {
+            bail!(
+                "`{}` doesn't take any parameters, but was given `{unknown}`",
+                self.name
+            )
+        }
+        bail!(
+            "`{}` doesn't take the parameter `{unknown}`; its parameters are `{}`",
+            self.name,
+            names.join("`, `")
+        )
+    }
+}
+
+/// The registries used to build the components of a run on populations of
+/// type `P` with genomes of type `G`.
+pub struct Components<P, G> {
+    pub selectors: Registry<SharedSelector<P>>,
+    pub mutators: Registry<SharedMutator<G>>,
+    pub recombinators: Registry<SharedRecombinator<G>>,
+}
+
+/// A child maker built from a [`RunConfig`], which a [`Generation`] can use
+/// (serially or in parallel), and which can be [described](Describe).
+pub trait ConfiguredChildMaker<P: Population>:
+    Prepare<P, Prepared: for<'a> Operator<&'a P, Output = P::Individual> + Send + Sync> + Describe
+{
+}
+
+impl<P, C> ConfiguredChildMaker<P> for C
+where
+    P: Population,
+    C: Prepare<P, Prepared: for<'a> Operator<&'a P, Output = P::Individual> + Send + Sync>
+        + Describe,
+{
+}
+
+/// A declarative description of a run.
+///
+/// Each child is made either by recombining two selected parents and
+/// then mutating the result, or by mutating a single selected parent,
+/// chosen with probability proportional to `crossover_weight` and
+/// `mutation_weight`.
+///
+/// The parents of all of a generation's children are
+/// [selected together](SelectParents), so batch selectors like stochastic
+/// universal sampling work as intended. If there's any crossover, two
+/// parents are selected for every child, and a child made by mutation alone
+/// uses the first of them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RunConfig {
+    pub population_size: usize,
+    pub num_generations: usize,
+    /// The seed for the run's random number generator; if this is `None` a
+    /// random seed is used.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    pub problem: ComponentConfig,
+    pub selector: ComponentConfig,
+    /// If this is `None`, children made by recombination aren't mutated.
+    #[serde(default)]
+    pub mutator: Option<ComponentConfig>,
+    /// If this is `None`, every child is made by mutation.
+    #[serde(default)]
+    pub recombinator: Option<ComponentConfig>,
+    /// Defaults to 1 if there's a recombinator.
+    #[serde(default)]
+    pub crossover_weight: Option<usize>,
+    /// Defaults to 0 if there's a recombinator, and 1 otherwise.
+    #[serde(default)]
+    pub mutation_weight: Option<usize>,
+    /// The number of the best individuals carried over unchanged into the
+    /// next generation.
+    #[serde(default)]
+    pub num_elites: usize,
+}
+
+impl RunConfig {
+    /// Parse a config written in JSON.
+    ///
+    /// # Errors
+    /// This returns an error if `json` isn't a valid config.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Failed to parse the JSON run config")
+    }
+
+    /// Parse a config written in TOML.
+    ///
+    /// # Errors
+    /// This returns an error if `toml` isn't a valid config.
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        toml::from_str(toml).context("Failed to parse the TOML run config")
+    }
+
+    /// Read a config from `path`, which is parsed as JSON if it ends in
+    /// `.json` and as TOML otherwise.
+    ///
+    /// # Errors
+    /// This returns an error if the file can't be read or isn't a valid
+    /// config.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the run config {}", path.display()))?;
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            Self::from_json(&contents)
+        } else {
+            Self::from_toml(&contents)
+        }
+        .with_context(|| format!("Invalid run config {}", path.display()))
+    }
+
+    /// The weights used to choose between crossover and mutation.
+    fn weights(&self) -> Result<(usize, usize)> {
+        let (crossover_weight, mutation_weight) = if self.recombinator.is_some() {
+            (
+                self.crossover_weight.unwrap_or(1),
+                self.mutation_weight.unwrap_or(0),
+            )
+        } else {
+            ensure!(
+                self.crossover_weight.unwrap_or(0) == 0,
+                "The crossover weight was {:?}, but there's no recombinator",
+                self.crossover_weight
+            );
+            (0, self.mutation_weight.unwrap_or(1))
+        };
+        ensure!(
+            crossover_weight + mutation_weight > 0,
+            "The crossover and mutation weights can't both be zero"
+        );
+        Ok((crossover_weight, mutation_weight))
+    }
+
+    /// Build the configured child maker, using `scorer` to score the new
+    /// genomes.
+    ///
+    /// # Errors
+    /// This returns an error if any of the components can't be built, e.g.,
+    /// because they aren't in the registries or are missing parameters.
+    pub fn child_maker<P, G, S>(
+        &self,
+        components: &Components<P, G>,
+        scorer: S,
+    ) -> Result<impl ConfiguredChildMaker<P>>
+    where
+        P: Population<Individual = EcIndividual<G, S::Score>> + AsRef<[P::Individual]>,
+        G: Clone + 'static,
+        S: Scorer<G> + Clone + Send + Sync,
+    {
+        let (crossover_weight, mutation_weight) = self.weights()?;
+        let selector = components
+            .selectors
+            .build(&self.selector)
+            .context("Invalid selector")?;
+        let mutator = self
+            .mutator
+            .as_ref()
+            .map(|config| components.mutators.build(config))
+            .transpose()
+            .context("Invalid mutator")?
+            .unwrap_or_else(|| SharedMutator::new(Unchanged));
+        let recombinator = self
+            .recombinator
+            .as_ref()
+            .map(|config| components.recombinators.build(config))
+            .transpose()
+            .context("Invalid recombinator")?
+            .unwrap_or_else(|| SharedRecombinator::new(NoRecombinator));
+
+        let mutation = FirstParent
+            .then(GenomeExtractor)
+            .then(Mutate::new(mutator.clone()));
+        if crossover_weight == 0 {
+            return Ok(ParentsPerChild::One(SelectParents::<_, _, 1>::new(
+                selector,
+                mutation.wrap::<GenomeScorer<_, _>>(scorer),
+            )));
+        }
+        let crossover = composable::Map::new(GenomeExtractor)
+            .then(Recombine::new(recombinator))
+            .then(Mutate::new(mutator));
+        Ok(ParentsPerChild::Two(SelectParents::<_, _, 2>::new(
+            selector,
+            crossover
+                .one_of_ops(crossover_weight, mutation, mutation_weight)
+                .wrap::<GenomeScorer<_, _>>(scorer),
+        )))
+    }
+
+    /// Build a [`Generation`] that starts from `population` and uses the
+    /// configured child maker and number of elites.
+    ///
+    /// # Errors
+    /// This returns an error if the child maker can't be built (see
+    /// [`RunConfig::child_maker`]).
+    pub fn generation<P, G, S>(
+        &self,
+        components: &Components<P, G>,
+        scorer: S,
+        population: P,
+    ) -> Result<Generation<P, impl ConfiguredChildMaker<P>, Elitism>>
+    where
+        P: Population<Individual = EcIndividual<G, S::Score>> + AsRef<[P::Individual]>,
+        G: Clone + 'static,
+        S: Scorer<G> + Clone + Send + Sync,
+    {
+        ensure!(
+            population.size() == self.population_size,
+            "The population has size {}, but the config asks for {}",
+            population.size(),
+            self.population_size
+        );
+        Ok(
+            Generation::new(self.child_maker(components, scorer)?, population)
+                .with_survival(Elitism::new(self.num_elites)),
+        )
+    }
+}
+
+/// The configured child maker, which selects one parent per child if
+/// every child is made by mutation, and two otherwise.
+enum ParentsPerChild<One, Two> {
+    One(One),
+    Two(Two),
+}
+
+impl<P, One, Two> Prepare<P> for ParentsPerChild<One, Two>
+where
+    One: Prepare<P>,
+    Two: Prepare<P>,
+{
+    type Prepared = ParentsPerChild<One::Prepared, Two::Prepared>;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        Ok(match self {
+            Self::One(one) => ParentsPerChild::One(one.prepare(population)?),
+            Self::Two(two) => ParentsPerChild::Two(two.prepare(population)?),
+        })
+    }
+
+    fn prepare_children<R: Rng + ?Sized>(
+        &self,
+        population: &P,
+        num_children: usize,
+        rng: &mut R,
+    ) -> Result<Option<Vec<Self::Prepared>>> {
+        Ok(match self {
+            Self::One(one) => one
+                .prepare_children(population, num_children, rng)?
+                .map(|prepared| prepared.into_iter().map(ParentsPerChild::One).collect()),
+            Self::Two(two) => two
+                .prepare_children(population, num_children, rng)?
+                .map(|prepared| prepared.into_iter().map(ParentsPerChild::Two).collect()),
+        })
+    }
+}
+
+impl<Input, One, Two> Operator<Input> for ParentsPerChild<One, Two>
+where
+    One: Operator<Input>,
+    Two: Operator<Input, Output = One::Output>,
+{
+    type Output = One::Output;
+
+    fn apply<R: Rng + ?Sized>(&self, input: Input, rng: &mut R) -> Result<Self::Output> {
+        match self {
+            Self::One(one) => one.apply(input, rng),
+            Self::Two(two) => two.apply(input, rng),
+        }
+    }
+}
+impl<One, Two> Composable for ParentsPerChild<One, Two> {}
+
+impl<One: Describe, Two: Describe> Describe for ParentsPerChild<One, Two> {
+    fn describe(&self) -> Description {
+        match self {
+            Self::One(one) => one.describe(),
+            Self::Two(two) => two.describe(),
+        }
+    }
+}
+
+/// Passes on the first of the selected parents, for the children that are
+/// made by mutation alone.
+struct FirstParent;
+
+impl<T, const N: usize> Operator<[T; N]> for FirstParent {
+    type Output = T;
+
+    fn apply<R: Rng + ?Sized>(&self, parents: [T; N], _: &mut R) -> Result<T> {
+        parents
+            .into_iter()
+            .next()
+            .context("A child needs at least one parent")
+    }
+}
+impl Composable for FirstParent {}
+
+impl Describe for FirstParent {
+    fn describe(&self) -> Description {
+        Description::new("FirstParent")
+    }
+}
+
+/// The mutator used when none is configured, which leaves genomes as they
+/// are.
+struct Unchanged;
+
+impl<G> Mutator<G> for Unchanged {
+    fn mutate<R: Rng + ?Sized>(&self, genome: G, _: &mut R) -> Result<G> {
+        Ok(genome)
+    }
+}
+
+impl Describe for Unchanged {
+    fn describe(&self) -> Description {
+        Description::new("Unchanged")
+    }
+}
+
+/// The recombinator used when none is configured. Its weight is always
+/// zero, so it's never actually used.
+struct NoRecombinator;
+
+impl<G> Recombinator<[G; 2]> for NoRecombinator {
+    type Output = G;
+
+    fn recombine<R: Rng + ?Sized>(&self, _: [G; 2], _: &mut R) -> Result<G> {
+        bail!("There's no recombinator configured")
+    }
+}
+
+impl Describe for NoRecombinator {
+    fn describe(&self) -> Description {
+        Description::new("NoRecombinator")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        individual::scorer::FnScorer,
+        test_results::{Score, TestResults},
+    };
+
+    type Scored = EcIndividual<i64, TestResults<Score<i64>>>;
+
+    const TOML: &str = "
+        population_size = 4
+        num_generations = 10
+        seed = 7
+        num_elites = 1
+
+        [problem]
+        name = 'count'
+
+        [selector]
+        name = 'tournament'
+        size = 2
+
+        [mutator]
+        name = 'add'
+        amount = 3
+    ";
+
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn score(genome: &i64) -> TestResults<Score<i64>> {
+        [Score::from(*genome)].into()
+    }
+
+    fn components() -> Components<Vec<Scored>, i64> {
+        Components {
+            selectors: selectors::selectors(),
+            mutators: Registry::new().with("add", |config, _| {
+                config.check_parameters(&["amount"])?;
+                let amount: i64 = config.parameter("amount")?;
+                Ok(SharedMutator::new(Add(amount)))
+            }),
+            recombinators: Registry::new(),
+        }
+    }
+
+    struct Add(i64);
+
+    impl Mutator<i64> for Add {
+        fn mutate<R: Rng + ?Sized>(&self, genome: i64, _: &mut R) -> Result<i64> {
+            Ok(genome + self.0)
+        }
+    }
+
+    impl Describe for Add {
+        fn describe(&self) -> Description {
+            Description::new("Add").with_parameter("amount", self.0)
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn toml_and_json_configs_are_equivalent() {
+        let from_toml = RunConfig::from_toml(TOML).unwrap();
+        let from_json = RunConfig::from_json(&serde_json::to_string(&from_toml).unwrap()).unwrap();
+        assert_eq!(from_toml, from_json);
+        assert_eq!(from_toml.seed, Some(7));
+        assert_eq!(from_toml.selector.parameter::<usize>("size").unwrap(), 2);
+        assert!(from_toml.recombinator.is_none());
+        assert_eq!(from_toml.weights().unwrap(), (0, 1));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn configs_build_working_generations() {
+        let config = RunConfig::from_toml(TOML).unwrap();
+        let population = (0..4)
+            .map(|genome| EcIndividual::new(genome, score(&genome)))
+            .collect::<Vec<_>>();
+        let mut generation = config
+            .generation(&components(), FnScorer(score), population)
+            .unwrap();
+        for seed in 0..3 {
+            generation.serial_next(seed).unwrap();
+        }
+        // Every child is a parent plus 3, so after three generations
+        // everyone (including the elite) is at least 3, and the best has
+        // been mutated more than once.
+        let genomes = generation
+            .population()
+            .iter()
+            .map(|individual| individual.genome)
+            .collect::<Vec<_>>();
+        assert!(genomes.iter().all(|&genome| genome >= 3), "{genomes:?}");
+        assert!(genomes.iter().any(|&genome| genome > 6), "{genomes:?}");
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn configured_parents_are_selected_together() {
+        let config = RunConfig::from_toml(
+            "
+            population_size = 4
+            num_generations = 1
+
+            [problem]
+            name = 'count'
+
+            [selector]
+            name = 'stochastic_universal'
+            weighting = { name = 'roulette' }
+
+            [mutator]
+            name = 'add'
+            amount = 0
+            ",
+        )
+        .unwrap();
+        // The scores add up to the population size, so with stochastic
+        // universal sampling the number of copies of each individual is
+        // exactly its score.
+        let population = [0, 1, 3, 0]
+            .into_iter()
+            .map(|genome| EcIndividual::new(genome, score(&genome)))
+            .collect::<Vec<_>>();
+        for seed in 0..10 {
+            let mut generation = config
+                .generation(&components(), FnScorer(score), population.clone())
+                .unwrap();
+            generation.serial_next(seed).unwrap();
+            let mut genomes = generation
+                .population()
+                .iter()
+                .map(|individual| individual.genome)
+                .collect::<Vec<_>>();
+            genomes.sort_unstable();
+            assert_eq!([1, 3, 3, 3], *genomes);
+        }
+    }
+
+    #[test]
+    fn invalid_configs_are_rejected() {
+        let mut config = RunConfig::from_toml(TOML).unwrap_or_else(|error| panic!("{error}"));
+        config.crossover_weight = Some(1);
+        assert!(config.weights().is_err());
+
+        config.crossover_weight = None;
+        config.mutator = Some(ComponentConfig::new("add"));
+        let Err(error) = config.child_maker(&components(), FnScorer(score)) else {
+            panic!("Built a mutator without its `amount`");
+        };
+        assert!(format!("{error:#}").contains("`amount`"));
+
+        config.mutator = Some(
+            ComponentConfig::new("add")
+                .with_parameter("amount", 3)
+                .with_parameter("amuont", 3),
+        );
+        let Err(error) = config.child_maker(&components(), FnScorer(score)) else {
+            panic!("Built a mutator with a misspelled parameter");
+        };
+        assert!(format!("{error:#}").contains("`amuont`"));
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        let Err(error) = RunConfig::from_toml(&format!("num_elite = 2\n{TOML}")) else {
+            panic!("Parsed a config with a misspelled field");
+        };
+        assert!(format!("{error:#}").contains("num_elite"));
+    }
+}
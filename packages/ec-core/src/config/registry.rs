@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+use itertools::Itertools;
+
+use super::ComponentConfig;
+
+type Constructor<T> = Box<dyn Fn(&ComponentConfig, &Registry<T>) -> Result<T>>;
+
+/// A set of named constructors that turn [`ComponentConfig`]s into
+/// components of type `T`, e.g., selectors.
+///
+/// Each constructor is also given the registry, so components that are
+/// built from other components (like a `weighted` selector) can build
+/// them from their own configs.
+pub struct Registry<T> {
+    constructors: BTreeMap<String, Constructor<T>>,
+}
+
+impl<T> Default for Registry<T> {
+    fn default() -> Self {
+        Self {
+            constructors: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> Registry<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `constructor` under `name`, replacing any constructor that
+    /// was already registered under that name.
+    #[must_use]
+    pub fn with(
+        mut self,
+        name: impl Into<String>,
+        constructor: impl Fn(&ComponentConfig, &Self) -> Result<T> + 'static,
+    ) -> Self {
+        self.register(name, constructor);
+        self
+    }
+
+    /// Register `constructor` under `name`, replacing any constructor that
+    /// was already registered under that name.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        constructor: impl Fn(&ComponentConfig, &Self) -> Result<T> + 'static,
+    ) {
+        self.constructors.insert(name.into(), Box::new(constructor));
+    }
+
+    /// Add all the constructors from `other`, replacing any constructors
+    /// with the same names.
+    #[must_use]
+    pub fn merge(mut self, other: Self) -> Self {
+        self.constructors.extend(other.constructors);
+        self
+    }
+
+    /// The names of the registered components, in alphabetical order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.constructors.keys().map(String::as_str)
+    }
+
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        self.constructors.contains_key(name)
+    }
+
+    /// Build the component described by `config`.
+    ///
+    /// # Errors
+    /// This returns an error if no component is registered under the
+    /// config's name, or if the constructor fails (e.g., because a
+    /// parameter is missing).
+    pub fn build(&self, config: &ComponentConfig) -> Result<T> {
+        let constructor = self.constructors.get(&config.name).with_context(|| {
+            format!(
+                "There's no component called `{}`; the known components are: {}",
+                config.name,
+                self.names().join(", ")
+            )
+        })?;
+        constructor(config, self).with_context(|| format!("Couldn't build `{}`", config.name))
+    }
+}
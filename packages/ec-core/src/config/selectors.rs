@@ -0,0 +1,390 @@
+//! The selectors that can be named in a run config.
+
+use anyhow::{bail, ensure, Context, Result};
+use num_traits::ToPrimitive;
+
+use super::{ComponentConfig, Registry, SharedSelector};
+use crate::{
+    genome::GenomeSize,
+    individual::{comparator::LexicographicParsimony, Individual},
+    operator::selector::{
+        best::Best,
+        crowded_tournament::CrowdedTournament,
+        double_tournament::DoubleTournament,
+        epsilon_lexicase::{Epsilon, EpsilonLexicase, Variant},
+        fast_lexicase::FastLexicase,
+        lexicase::Lexicase,
+        random::Random,
+        rank::{Rank, Ranking},
+        roulette::Roulette,
+        stochastic_universal::StochasticUniversal,
+        tarpeian::Tarpeian,
+        tournament::Tournament,
+        weighted::Weighted,
+    },
+    population::Population,
+    test_results::{Fitness, TestResults},
+};
+
+/// A registry of all the built-in selectors (other than novelty
+/// selection, which needs an archive and a behavior descriptor):
+///
+/// | Name                   | Parameters                                              |
+/// |------------------------|---------------------------------------------------------|
+/// | `best`                 |                                                         |
+/// | `random`               |                                                         |
+/// | `tournament`           | `size`                                                  |
+/// | `crowded_tournament`   | `size`                                                  |
+/// | `lexicase`             | `num_test_cases`                                        |
+/// | `fast_lexicase`        | `num_test_cases`                                        |
+/// | `epsilon_lexicase`     | `num_test_cases`, optional `epsilon` and `variant`      |
+/// | `rank`                 | either `selection_pressure` (linear) or `base` (exponential) |
+/// | `roulette`             |                                                         |
+/// | `stochastic_universal` | `weighting` (a `roulette` or `rank` selector)           |
+/// | `weighted`             | `selectors` (a list of selectors, each with a `weight`) |
+///
+/// If `epsilon` is omitted, epsilon-lexicase uses the median absolute
+/// deviation of each case; `variant` is `"semi_dynamic"` (the default) or
+/// `"dynamic"`.
+#[must_use]
+pub fn selectors<P, I, T>() -> Registry<SharedSelector<P>>
+where
+    P: Population<Individual = I> + AsRef<[I]> + Send + Sync + 'static,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop I>,
+    I: Individual<TestResults = TestResults<T>> + Ord,
+    T: Ord + ToPrimitive + Fitness,
+{
+    Registry::new()
+        .with("best", |config, _| {
+            config.check_parameters(&[])?;
+            Ok(SharedSelector::new(Best))
+        })
+        .with("random", |config, _| {
+            config.check_parameters(&[])?;
+            Ok(SharedSelector::new(Random))
+        })
+        .with("tournament", |config, _| {
+            config.check_parameters(&["size"])?;
+            Ok(SharedSelector::new(Tournament::new(
+                config.parameter("size")?,
+            )))
+        })
+        .with("crowded_tournament", |config, _| {
+            config.check_parameters(&["size"])?;
+            Ok(SharedSelector::new(CrowdedTournament::new(
+                config.parameter("size")?,
+            )))
+        })
+        .with("lexicase", |config, _| {
+            config.check_parameters(&["num_test_cases"])?;
+            Ok(SharedSelector::new(Lexicase::new(
+                config.parameter("num_test_cases")?,
+            )))
+        })
+        .with("fast_lexicase", |config, _| {
+            config.check_parameters(&["num_test_cases"])?;
+            Ok(SharedSelector::new(FastLexicase::new(
+                config.parameter("num_test_cases")?,
+            )))
+        })
+        .with("epsilon_lexicase", |config, _| {
+            epsilon_lexicase(config).map(SharedSelector::new)
+        })
+        .with("rank", |config, _| rank(config).map(SharedSelector::new))
+        .with("roulette", |config, _| {
+            config.check_parameters(&[])?;
+            Ok(SharedSelector::new(Roulette))
+        })
+        .with("stochastic_universal", |config, _| {
+            config.check_parameters(&["weighting"])?;
+            let weighting: ComponentConfig = config.parameter("weighting")?;
+            Ok(match weighting.name.as_str() {
+                "roulette" => {
+                    weighting.check_parameters(&[])?;
+                    SharedSelector::new(StochasticUniversal::new(Roulette))
+                }
+                "rank" => SharedSelector::new(StochasticUniversal::new(rank(&weighting)?)),
+                name => bail!(
+                    "Stochastic universal sampling can use `roulette` or `rank` weights, not \
+                     `{name}`"
+                ),
+            })
+        })
+        .with("weighted", weighted)
+}
+
+/// A registry of the selectors that control bloat by taking the size of
+/// the individuals' genomes into account:
+///
+/// | Name                   | Parameters                           |
+/// |------------------------|--------------------------------------|
+/// | `parsimony_best`       |                                      |
+/// | `parsimony_tournament` | `size`                               |
+/// | `double_tournament`    | `fitness_size`, `parsimony_pressure` |
+/// | `tarpeian`             | `size`, `kill_probability`           |
+///
+/// The first two break ties using [`LexicographicParsimony`]. This can be
+/// [merged](Registry::merge) with [`selectors`] for genomes with a size.
+#[must_use]
+pub fn parsimony_selectors<P, I>() -> Registry<SharedSelector<P>>
+where
+    P: Population<Individual = I> + AsRef<[I]> + Send + Sync + 'static,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop I>,
+    I: Individual + Ord,
+    I::Genome: GenomeSize,
+{
+    Registry::new()
+        .with("parsimony_best", |config, _| {
+            config.check_parameters(&[])?;
+            Ok(SharedSelector::new(Best::by(LexicographicParsimony)))
+        })
+        .with("parsimony_tournament", |config, _| {
+            config.check_parameters(&["size"])?;
+            Ok(SharedSelector::new(
+                Tournament::new(config.parameter("size")?).with_comparator(LexicographicParsimony),
+            ))
+        })
+        .with("double_tournament", |config, _| {
+            config.check_parameters(&["fitness_size", "parsimony_pressure"])?;
+            Ok(SharedSelector::new(DoubleTournament::new(
+                config.parameter("fitness_size")?,
+                config.parameter("parsimony_pressure")?,
+            )))
+        })
+        .with("tarpeian", |config, _| {
+            config.check_parameters(&["size", "kill_probability"])?;
+            Ok(SharedSelector::new(Tarpeian::new(
+                config.parameter("size")?,
+                config.parameter("kill_probability")?,
+            )))
+        })
+}
+
+fn epsilon_lexicase(config: &ComponentConfig) -> Result<EpsilonLexicase> {
+    config.check_parameters(&["num_test_cases", "epsilon", "variant"])?;
+    let mut selector = EpsilonLexicase::new(config.parameter("num_test_cases")?);
+    if let Some(epsilon) = config.optional_parameter("epsilon")? {
+        selector = selector.with_epsilon(Epsilon::Fixed(epsilon));
+    }
+    if let Some(variant) = config.optional_parameter::<String>("variant")? {
+        selector = selector.with_variant(match variant.as_str() {
+            "semi_dynamic" => Variant::SemiDynamic,
+            "dynamic" => Variant::Dynamic,
+            _ => bail!(
+                "The epsilon-lexicase variant must be `semi_dynamic` or `dynamic`, not `{variant}`"
+            ),
+        });
+    }
+    Ok(selector)
+}
+
+fn rank(config: &ComponentConfig) -> Result<Rank> {
+    config.check_parameters(&["selection_pressure", "base"])?;
+    let ranking = match (
+        config.optional_parameter("selection_pressure")?,
+        config.optional_parameter("base")?,
+    ) {
+        (Some(selection_pressure), None) => Ranking::Linear { selection_pressure },
+        (None, Some(base)) => Ranking::Exponential { base },
+        _ => bail!("Rank selection needs exactly one of `selection_pressure` and `base`"),
+    };
+    Ok(Rank::new(ranking))
+}
+
+// Builds the selectors from the last to the first, so the first selector
+// is paired with a selector that chooses between the rest, weighted by
+// their total weight. Each selector's `weight` is removed from its config
+// before it's built, since it's a parameter of `weighted`, not of the
+// selector.
+fn weighted<P>(
+    config: &ComponentConfig,
+    registry: &Registry<SharedSelector<P>>,
+) -> Result<SharedSelector<P>>
+where
+    P: Population + Send + Sync + 'static,
+{
+    config.check_parameters(&["selectors"])?;
+    let configs: Vec<ComponentConfig> = config.parameter("selectors")?;
+    let mut selectors = configs
+        .into_iter()
+        .enumerate()
+        .map(|(index, mut config)| {
+            let weight: usize = config.parameter("weight")?;
+            config.parameters.remove("weight");
+            let selector = registry
+                .build(&config)
+                .with_context(|| format!("Invalid weighted selector {index}"))?;
+            Ok((selector, weight))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let (mut rest, mut rest_weight) = selectors
+        .pop()
+        .context("A `weighted` selector needs at least one selector")?;
+    if selectors.is_empty() {
+        return Ok(SharedSelector::new(Weighted::new(rest, rest_weight)));
+    }
+    for (selector, weight) in selectors.into_iter().rev() {
+        rest =
+            SharedSelector::new(Weighted::new(selector, weight).with_selector(rest, rest_weight));
+        rest_weight += weight;
+    }
+    ensure!(
+        rest_weight > 0,
+        "The total weight of the weighted selectors was zero"
+    );
+    Ok(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        individual::ec::EcIndividual,
+        operator::{selector::Selector, Describe, Prepare},
+        test_results::Score,
+    };
+
+    type Scored = EcIndividual<usize, TestResults<Score<i64>>>;
+    type SizedScored = EcIndividual<Vec<u8>, TestResults<Score<i64>>>;
+
+    fn population() -> Vec<Scored> {
+        (0..10)
+            .map(|genome| {
+                let score = i64::try_from(genome).unwrap_or_default();
+                EcIndividual::new(genome, [Score::from(score), Score::from(-score)].into())
+            })
+            .collect()
+    }
+
+    fn config(toml: &str) -> ComponentConfig {
+        #[allow(clippy::unwrap_used)]
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn every_selector_can_be_built_and_used() {
+        let registry = selectors::<Vec<Scored>, _, _>();
+        let population = population();
+        let mut rng = StdRng::seed_from_u64(0);
+        for toml in [
+            "name = 'best'",
+            "name = 'random'",
+            "name = 'tournament'\nsize = 2",
+            "name = 'crowded_tournament'\nsize = 2",
+            "name = 'lexicase'\nnum_test_cases = 2",
+            "name = 'fast_lexicase'\nnum_test_cases = 2",
+            "name = 'epsilon_lexicase'\nnum_test_cases = 2\nepsilon = 0.5\nvariant = 'dynamic'",
+            "name = 'rank'\nselection_pressure = 1.5",
+            "name = 'rank'\nbase = 0.9",
+            "name = 'roulette'",
+            "name = 'stochastic_universal'\nweighting = { name = 'rank', base = 0.5 }",
+        ] {
+            let selector = registry.build(&config(toml)).unwrap();
+            selector
+                .prepare(&population)
+                .unwrap()
+                .select(&population, &mut rng)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn weighted_selectors_respect_their_weights() {
+        let selector = selectors::<Vec<Scored>, _, _>()
+            .build(&config(
+                "name = 'weighted'
+                 selectors = [
+                     { name = 'best', weight = 0 },
+                     { name = 'random', weight = 3 },
+                     { name = 'tournament', size = 10, weight = 0 },
+                 ]",
+            ))
+            .unwrap();
+        assert_eq!(
+            selector.describe().to_string(),
+            "OneOf\n  Weighted(weight = 0)\n    Best\n  Weighted(weight = 3)\n    OneOf\n      \
+             Weighted(weight = 3)\n        Random\n      Weighted(weight = 0)\n        \
+             Tournament(size = 10)\n          Natural"
+        );
+
+        // Neither `best` nor `tournament` (which sees the whole population)
+        // can ever be chosen, so the best individual is only chosen by
+        // `random`.
+        let population = population();
+        let mut rng = StdRng::seed_from_u64(0);
+        let num_best = (0..1000)
+            .filter(|_| selector.select(&population, &mut rng).unwrap().genome == 9)
+            .count();
+        assert!((50..150).contains(&num_best), "{num_best}");
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn parsimony_selectors_can_be_built_and_used() {
+        let registry = selectors::<Vec<SizedScored>, _, _>()
+            .merge(parsimony_selectors::<Vec<SizedScored>, _>());
+        let population: Vec<SizedScored> = (1..=10)
+            .map(|size| EcIndividual::new(vec![0; size], [Score::from(0)].into()))
+            .collect();
+        let mut rng = StdRng::seed_from_u64(0);
+        for toml in [
+            "name = 'parsimony_best'",
+            "name = 'parsimony_tournament'\nsize = 10",
+            "name = 'double_tournament'\nfitness_size = 3\nparsimony_pressure = 1.4",
+            "name = 'tarpeian'\nsize = 2\nkill_probability = 0.5",
+        ] {
+            let selector = registry.build(&config(toml)).unwrap();
+            selector
+                .prepare(&population)
+                .unwrap()
+                .select(&population, &mut rng)
+                .unwrap();
+        }
+        // Everyone has the same score, so the smallest individual wins.
+        let best = registry
+            .build(&config("name = 'parsimony_tournament'\nsize = 10"))
+            .unwrap()
+            .select(&population, &mut rng)
+            .unwrap();
+        assert_eq!(best.genome.len(), 1);
+    }
+
+    #[test]
+    fn unknown_selectors_and_missing_parameters_are_reported() {
+        let registry = selectors::<Vec<Scored>, _, _>();
+        let Err(error) = registry.build(&config("name = 'tournamnet'")) else {
+            panic!("Built a selector that doesn't exist");
+        };
+        assert!(format!("{error:#}").contains("fast_lexicase"));
+        let Err(error) = registry.build(&config("name = 'tournament'")) else {
+            panic!("Built a tournament without a size");
+        };
+        assert!(format!("{error:#}").contains("`size`"));
+    }
+
+    #[test]
+    fn unused_parameters_are_reported() {
+        let registry = selectors::<Vec<Scored>, _, _>();
+        for (toml, unused) in [
+            ("name = 'best'\nsize = 2", "`size`"),
+            ("name = 'tournament'\nsize = 2\nsise = 3", "`sise`"),
+            (
+                "name = 'stochastic_universal'\nweighting = { name = 'roulette', base = 0.5 }",
+                "`base`",
+            ),
+            (
+                "name = 'weighted'\nselectors = [{ name = 'random', weight = 1, size = 2 }]",
+                "`size`",
+            ),
+        ] {
+            let Err(error) = registry.build(&config(toml)) else {
+                panic!("Built a selector with an unused parameter: {toml}");
+            };
+            assert!(format!("{error:#}").contains(unused), "{error:#}");
+        }
+    }
+}
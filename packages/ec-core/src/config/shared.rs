@@ -0,0 +1,221 @@
+//! Type-erased selectors, mutators, and recombinators.
+//!
+//! Since `Selector::select` (and friends) are generic over the random
+//! number generator, they can't be used as trait objects directly. These
+//! wrappers pass the generator along as a `&mut dyn RngCore` instead, so
+//! components chosen at runtime (from a config) all have the same type.
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use rand::{Rng, RngCore};
+
+use crate::{
+    operator::{
+        mutator::Mutator,
+        recombinator::Recombinator,
+        selector::{PreparedSelector, Selector},
+        Describe, Description, Prepare,
+    },
+    population::Population,
+};
+
+trait ErasedSelector<P: Population>: Send + Sync {
+    fn select<'pop>(
+        &self,
+        population: &'pop P,
+        rng: &mut dyn RngCore,
+    ) -> Result<&'pop P::Individual>;
+
+    fn select_many<'pop>(
+        &self,
+        population: &'pop P,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<&'pop P::Individual>>;
+
+    fn prepare_selector(&self, population: &P) -> Result<Option<PreparedSelector<P>>>;
+
+    fn describe(&self) -> Description;
+}
+
+impl<P, S> ErasedSelector<P> for S
+where
+    P: Population,
+    S: Selector<P> + Describe + Send + Sync,
+{
+    fn select<'pop>(
+        &self,
+        population: &'pop P,
+        rng: &mut dyn RngCore,
+    ) -> Result<&'pop P::Individual> {
+        Selector::select(self, population, rng)
+    }
+
+    fn select_many<'pop>(
+        &self,
+        population: &'pop P,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<&'pop P::Individual>> {
+        Selector::select_many(self, population, count, rng)
+    }
+
+    fn prepare_selector(&self, population: &P) -> Result<Option<PreparedSelector<P>>> {
+        Selector::prepare_selector(self, population)
+    }
+
+    fn describe(&self) -> Description {
+        Describe::describe(self)
+    }
+}
+
+/// A selector of any type that can be [described](Describe).
+pub struct SharedSelector<P>(Arc<dyn ErasedSelector<P>>);
+
+impl<P: Population> SharedSelector<P> {
+    pub fn new(selector: impl Selector<P> + Describe + Send + Sync + 'static) -> Self {
+        Self(Arc::new(selector))
+    }
+}
+
+impl<P> Clone for SharedSelector<P> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<P: Population> Selector<P> for SharedSelector<P> {
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        mut rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        self.0.select(population, &mut rng)
+    }
+
+    fn select_many<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        count: usize,
+        mut rng: &mut R,
+    ) -> Result<Vec<&'pop P::Individual>> {
+        self.0.select_many(population, count, &mut rng)
+    }
+
+    fn prepare_selector(&self, population: &P) -> Result<Option<PreparedSelector<P>>> {
+        self.0.prepare_selector(population)
+    }
+}
+
+impl<P: Population + 'static> Prepare<P> for SharedSelector<P> {
+    type Prepared = PreparedSelector<P>;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        Ok(Selector::prepare_selector(self, population)?
+            .unwrap_or_else(|| PreparedSelector::new(self.clone())))
+    }
+}
+
+impl<P: Population> Describe for SharedSelector<P> {
+    fn describe(&self) -> Description {
+        self.0.describe()
+    }
+}
+
+trait ErasedMutator<G>: Send + Sync {
+    fn mutate(&self, genome: G, rng: &mut dyn RngCore) -> Result<G>;
+
+    fn describe(&self) -> Description;
+}
+
+impl<G, M> ErasedMutator<G> for M
+where
+    M: Mutator<G> + Describe + Send + Sync,
+{
+    fn mutate(&self, genome: G, rng: &mut dyn RngCore) -> Result<G> {
+        Mutator::mutate(self, genome, rng)
+    }
+
+    fn describe(&self) -> Description {
+        Describe::describe(self)
+    }
+}
+
+/// A mutator of any type that can be [described](Describe).
+pub struct SharedMutator<G>(Arc<dyn ErasedMutator<G>>);
+
+impl<G> SharedMutator<G> {
+    pub fn new(mutator: impl Mutator<G> + Describe + Send + Sync + 'static) -> Self {
+        Self(Arc::new(mutator))
+    }
+}
+
+impl<G> Clone for SharedMutator<G> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<G> Mutator<G> for SharedMutator<G> {
+    fn mutate<R: Rng + ?Sized>(&self, genome: G, mut rng: &mut R) -> Result<G> {
+        self.0.mutate(genome, &mut rng)
+    }
+}
+
+impl<G> Describe for SharedMutator<G> {
+    fn describe(&self) -> Description {
+        self.0.describe()
+    }
+}
+
+trait ErasedRecombinator<G>: Send + Sync {
+    fn recombine(&self, parents: [G; 2], rng: &mut dyn RngCore) -> Result<G>;
+
+    fn describe(&self) -> Description;
+}
+
+impl<G, C> ErasedRecombinator<G> for C
+where
+    C: Recombinator<[G; 2], Output = G> + Describe + Send + Sync,
+{
+    fn recombine(&self, parents: [G; 2], rng: &mut dyn RngCore) -> Result<G> {
+        Recombinator::recombine(self, parents, rng)
+    }
+
+    fn describe(&self) -> Description {
+        Describe::describe(self)
+    }
+}
+
+/// A recombinator of any type that makes one child from two parents, and
+/// can be [described](Describe).
+pub struct SharedRecombinator<G>(Arc<dyn ErasedRecombinator<G>>);
+
+impl<G> SharedRecombinator<G> {
+    pub fn new(
+        recombinator: impl Recombinator<[G; 2], Output = G> + Describe + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(recombinator))
+    }
+}
+
+impl<G> Clone for SharedRecombinator<G> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<G> Recombinator<[G; 2]> for SharedRecombinator<G> {
+    type Output = G;
+
+    fn recombine<R: Rng + ?Sized>(&self, parents: [G; 2], mut rng: &mut R) -> Result<G> {
+        self.0.recombine(parents, &mut rng)
+    }
+}
+
+impl<G> Describe for SharedRecombinator<G> {
+    fn describe(&self) -> Description {
+        self.0.describe()
+    }
+}
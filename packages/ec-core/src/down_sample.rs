@@ -0,0 +1,572 @@
+//! Down-sampling of the training cases.
+//!
+//! Each generation only a subset of the cases is used to evaluate the new
+//! children, and so also to select from them in the following generation.
+//!
+//! The pieces cooperate through a shared [`CaseSample`]:
+//!   - a [`DownSampledScorer`] only evaluates genomes on the cases in the
+//!     current sample, so the skipped cases are never evaluated;
+//!   - selectors like `Lexicase` are created with [`CaseSample::sample_size`]
+//!     test cases, since that's how many results each individual has;
+//!   - the [`DownSample`] survival strategy draws a new sample (using a
+//!     [`DownSampler`]) once the survivors have been chosen, so the sample is
+//!     in place before the next generation's children are made.
+//!
+//! Each child is therefore scored exactly once, on the sample that's
+//! current when it's made. The only other evaluations are of the parents
+//! that survive into the next population (e.g., the elites kept by
+//! `Elitism`), which `DownSample` re-scores on the children's sample so
+//! that all the individuals in a population have results for the same
+//! cases.
+
+use std::sync::RwLock;
+
+use anyhow::{ensure, Context, Result};
+use num_traits::ToPrimitive;
+use rand::{prelude::IndexedRandom, seq::index, Rng};
+
+use crate::{
+    individual::{scorer::Scorer, Individual, Rescorable},
+    population::Population,
+    survival::Survival,
+    test_results::TestResults,
+};
+
+/// The indices of the training cases that are currently being used for
+/// evaluation and selection.
+///
+/// This is shared (by reference) between the scorer, which reads it for
+/// every evaluation, and the survival strategy, which replaces it once
+/// per generation.
+#[derive(Debug)]
+pub struct CaseSample {
+    num_cases: usize,
+    indices: RwLock<Vec<usize>>,
+}
+
+impl CaseSample {
+    /// Create a sample of `num_cases` training cases that initially
+    /// contains the given `indices`.
+    ///
+    /// # Errors
+    /// This returns an error if any index is out of range.
+    pub fn new(num_cases: usize, indices: Vec<usize>) -> Result<Self> {
+        ensure!(
+            indices.iter().all(|&index| index < num_cases),
+            "All the sampled case indices must be less than the number of cases ({num_cases})"
+        );
+        Ok(Self {
+            num_cases,
+            indices: RwLock::new(indices),
+        })
+    }
+
+    /// Create a sample of `sample_size` training cases chosen uniformly at
+    /// random from `num_cases` cases.
+    ///
+    /// # Errors
+    /// This returns an error if `sample_size` is larger than `num_cases`.
+    pub fn random<R: Rng + ?Sized>(
+        num_cases: usize,
+        sample_size: usize,
+        rng: &mut R,
+    ) -> Result<Self> {
+        Self::new(num_cases, random_indices(num_cases, sample_size, rng)?)
+    }
+
+    /// The total number of training cases (sampled or not).
+    #[must_use]
+    pub const fn num_cases(&self) -> usize {
+        self.num_cases
+    }
+
+    /// The number of cases in the sample, which is the number of results
+    /// each newly evaluated individual will have.
+    #[must_use]
+    pub fn sample_size(&self) -> usize {
+        self.with_indices(<[usize]>::len)
+    }
+
+    /// Call `f` with the indices of the cases in the current sample.
+    pub fn with_indices<T>(&self, f: impl FnOnce(&[usize]) -> T) -> T {
+        // Only a panic while holding the write lock can poison it, and all
+        // we do then is replace the indices, so poisoning can be ignored.
+        let indices = self
+            .indices
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        f(&indices)
+    }
+
+    fn set(&self, indices: Vec<usize>) {
+        *self
+            .indices
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = indices;
+    }
+}
+
+fn random_indices<R: Rng + ?Sized>(
+    num_cases: usize,
+    sample_size: usize,
+    rng: &mut R,
+) -> Result<Vec<usize>> {
+    ensure!(
+        sample_size <= num_cases,
+        "Can't sample {sample_size} cases from only {num_cases} cases"
+    );
+    let mut indices = index::sample(rng, num_cases, sample_size).into_vec();
+    indices.sort_unstable();
+    Ok(indices)
+}
+
+/// A scorer that only evaluates genomes on the cases in the current
+/// [`CaseSample`].
+///
+/// `score` is called with a genome and the indices of the sampled cases,
+/// and should return the results for just those cases, in that order.
+#[derive(Clone, Copy)]
+pub struct DownSampledScorer<'a, F> {
+    sample: &'a CaseSample,
+    score: F,
+}
+
+impl<'a, F> DownSampledScorer<'a, F> {
+    pub const fn new(sample: &'a CaseSample, score: F) -> Self {
+        Self { sample, score }
+    }
+}
+
+impl<G, F, T> Scorer<G> for DownSampledScorer<'_, F>
+where
+    F: Fn(&G, &[usize]) -> T,
+{
+    type Score = T;
+
+    fn score(&self, genome: &G) -> Self::Score {
+        self.sample
+            .with_indices(|indices| (self.score)(genome, indices))
+    }
+}
+
+/// A strategy for choosing which cases are in the next generation's
+/// sample.
+pub trait DownSampler<P>
+where
+    P: Population,
+{
+    /// Choose `sample_size` of the `num_cases` cases, based (perhaps) on
+    /// the new `population`.
+    ///
+    /// # Errors
+    /// This returns an error if a sample can't be chosen, e.g., if
+    /// `sample_size` is larger than `num_cases`.
+    fn down_sample<R: Rng + ?Sized>(
+        &self,
+        population: &P,
+        num_cases: usize,
+        sample_size: usize,
+        rng: &mut R,
+    ) -> Result<Vec<usize>>;
+}
+
+/// Choose the sample uniformly at random each generation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RandomDownSampler;
+
+impl<P: Population> DownSampler<P> for RandomDownSampler {
+    fn down_sample<R: Rng + ?Sized>(
+        &self,
+        _: &P,
+        num_cases: usize,
+        sample_size: usize,
+        rng: &mut R,
+    ) -> Result<Vec<usize>> {
+        random_indices(num_cases, sample_size, rng)
+    }
+}
+
+/// Informed down-sampling (Boldi et al.), which tries to choose cases
+/// that are as different from each other as possible.
+///
+/// Each generation a random fraction of the population is evaluated on
+/// _all_ the cases using `full_scorer`. Two cases are then as far apart as
+/// the number of those individuals that solve one of the cases but not the
+/// other, and the sample is built by farthest-first traversal: starting
+/// from a random case, we repeatedly add the case that is farthest from
+/// all the cases chosen so far.
+pub struct InformedDownSampler<S, F> {
+    parent_fraction: f64,
+    full_scorer: S,
+    is_solved: F,
+}
+
+impl<S, F> InformedDownSampler<S, F> {
+    /// Estimate the distances between cases by evaluating
+    /// `parent_fraction` of the population on all the cases with
+    /// `full_scorer`, where `is_solved` says whether a result solves
+    /// its case.
+    pub const fn new(parent_fraction: f64, full_scorer: S, is_solved: F) -> Self {
+        Self {
+            parent_fraction,
+            full_scorer,
+            is_solved,
+        }
+    }
+}
+
+impl<P, S, F, G, T> DownSampler<P> for InformedDownSampler<S, F>
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual<Genome = G>,
+    S: Scorer<G, Score = TestResults<T>>,
+    F: Fn(&T) -> bool,
+{
+    fn down_sample<R: Rng + ?Sized>(
+        &self,
+        population: &P,
+        num_cases: usize,
+        sample_size: usize,
+        rng: &mut R,
+    ) -> Result<Vec<usize>> {
+        ensure!(
+            sample_size <= num_cases,
+            "Can't sample {sample_size} cases from only {num_cases} cases"
+        );
+        if sample_size == 0 {
+            return Ok(Vec::new());
+        }
+        let individuals: Vec<_> = population.into_iter().collect();
+        let num_parents = (self.parent_fraction * individuals.len().to_f64().unwrap_or_default())
+            .ceil()
+            .to_usize()
+            .unwrap_or_default()
+            .clamp(1, individuals.len().max(1));
+        let solved = individuals
+            .choose_multiple(rng, num_parents)
+            .map(|parent| {
+                let results = self.full_scorer.score(parent.genome()).results;
+                ensure!(
+                    results.len() == num_cases,
+                    "The full scorer returned {} results, but there are {num_cases} cases",
+                    results.len()
+                );
+                Ok(results.iter().map(&self.is_solved).collect::<Vec<_>>())
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let distance = |i: usize, j: usize| {
+            solved
+                .iter()
+                .filter(|solved| solved[i] != solved[j])
+                .count()
+        };
+
+        let first = rng.gen_range(0..num_cases);
+        let mut chosen = vec![first];
+        let mut is_chosen = vec![false; num_cases];
+        is_chosen[first] = true;
+        let mut min_distances: Vec<usize> =
+            (0..num_cases).map(|case| distance(first, case)).collect();
+        let mut farthest = Vec::with_capacity(num_cases);
+        while chosen.len() < sample_size {
+            let max_distance = (0..num_cases)
+                .filter(|&case| !is_chosen[case])
+                .map(|case| min_distances[case])
+                .max()
+                .context("Ran out of cases to choose from")?;
+            farthest.clear();
+            farthest.extend(
+                (0..num_cases)
+                    .filter(|&case| !is_chosen[case] && min_distances[case] == max_distance),
+            );
+            let next = *farthest
+                .choose(rng)
+                .context("Ran out of cases to choose from")?;
+            chosen.push(next);
+            is_chosen[next] = true;
+            for (case, min_distance) in min_distances.iter_mut().enumerate() {
+                *min_distance = (*min_distance).min(distance(next, case));
+            }
+        }
+        chosen.sort_unstable();
+        Ok(chosen)
+    }
+}
+
+/// A survival strategy that wraps another one and then draws a new
+/// [`CaseSample`] using a [`DownSampler`].
+///
+/// Any parents that survive are first re-scored with `scorer` (typically
+/// the same [`DownSampledScorer`] used to score the children) on the sample
+/// the children were scored on. The children aren't re-scored, and since a
+/// [`Run`](crate::run::Run) only counts the children as evaluations, those
+/// re-scorings aren't included in its count.
+///
+/// Strategies that rank the parents and children together, like
+/// `MuPlusLambda`, compare results from different samples, since the
+/// parents were scored on the previous sample.
+///
+/// The size of the sample stays the same from one generation to the next.
+pub struct DownSample<'a, S, D, Sc> {
+    survival: S,
+    sample: &'a CaseSample,
+    down_sampler: D,
+    scorer: Sc,
+}
+
+impl<'a, S, D, Sc> DownSample<'a, S, D, Sc> {
+    pub const fn new(survival: S, sample: &'a CaseSample, down_sampler: D, scorer: Sc) -> Self {
+        Self {
+            survival,
+            sample,
+            down_sampler,
+            scorer,
+        }
+    }
+}
+
+impl<P, S, D, Sc> Survival<P> for DownSample<'_, S, D, Sc>
+where
+    P: Population + IntoIterator<Item = P::Individual> + FromIterator<P::Individual>,
+    P::Individual: Rescorable + Clone + PartialEq,
+    S: Survival<P>,
+    D: DownSampler<P>,
+    Sc: Scorer<
+        <P::Individual as Individual>::Genome,
+        Score = <P::Individual as Individual>::TestResults,
+    >,
+{
+    fn num_children(&self, population: &P) -> usize {
+        self.survival.num_children(population)
+    }
+
+    fn survivors<R: Rng + ?Sized>(
+        &self,
+        parents: &P,
+        children: Vec<P::Individual>,
+        rng: &mut R,
+    ) -> Result<P> {
+        // Any survivor that isn't one of the children is a parent that was
+        // carried over, so its results are for the previous sample. (A parent
+        // that's identical to one of the children already has the right
+        // results.)
+        let scored_children = children.clone();
+        let survivors = self
+            .survival
+            .survivors(parents, children, rng)?
+            .into_iter()
+            .map(|mut survivor| {
+                if !scored_children.contains(&survivor) {
+                    survivor.set_test_results(self.scorer.score(survivor.genome()));
+                }
+                survivor
+            })
+            .collect();
+        let indices = self.down_sampler.down_sample(
+            &survivors,
+            self.sample.num_cases(),
+            self.sample.sample_size(),
+            rng,
+        )?;
+        self.sample.set(indices);
+        Ok(survivors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        generation::Generation,
+        individual::{ec::EcIndividual, scorer::FnScorer},
+        operator::{
+            genome_extractor::GenomeExtractor,
+            genome_scorer::GenomeScorer,
+            selector::{random::Random, Select},
+            Composable, Operator,
+        },
+        survival::{Elitism, Generational},
+        test_results::Error,
+    };
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn scorer_only_evaluates_sampled_cases() {
+        let sample = CaseSample::new(10, vec![2, 5, 7]).unwrap();
+        let num_evaluations = AtomicUsize::new(0);
+        let scorer = DownSampledScorer::new(&sample, |genome: &i32, indices: &[usize]| {
+            indices
+                .iter()
+                .map(|&index| {
+                    num_evaluations.fetch_add(1, Ordering::Relaxed);
+                    genome * i32::try_from(index).unwrap()
+                })
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(vec![4, 10, 14], scorer.score(&2));
+        assert_eq!(3, num_evaluations.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn survival_draws_a_new_sample() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sample = CaseSample::random(100, 10, &mut rng).unwrap();
+        let original = sample.with_indices(<[usize]>::to_vec);
+        let scorer = DownSampledScorer::new(&sample, |_: &i32, indices: &[usize]| indices.to_vec());
+        let survival = DownSample::new(Generational, &sample, RandomDownSampler, scorer);
+        let next = survival
+            .survivors(
+                &vec![EcIndividual::new(1, Vec::new())],
+                vec![EcIndividual::new(3, Vec::new())],
+                &mut rng,
+            )
+            .unwrap();
+        assert_eq!(3, next[0].genome);
+        let resampled = sample.with_indices(<[usize]>::to_vec);
+        assert_eq!(10, resampled.len());
+        assert!(resampled.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_ne!(original, resampled);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn only_surviving_parents_are_rescored() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sample = CaseSample::random(100, 10, &mut rng).unwrap();
+        let original = sample.with_indices(<[usize]>::to_vec);
+        // The results are just the indices of the sampled cases, plus the
+        // genome, so we can tell which sample each individual was scored on.
+        let scorer = DownSampledScorer::new(&sample, |genome: &usize, indices: &[usize]| {
+            indices
+                .iter()
+                .map(|index| index + genome)
+                .collect::<Vec<_>>()
+        });
+        let scored_on = |genome: usize, indices: &[usize]| -> Vec<usize> {
+            indices.iter().map(|index| index + genome).collect()
+        };
+        // The parents were scored on some earlier sample.
+        let parents: Vec<_> = (0..5)
+            .map(|genome| EcIndividual::new(genome, vec![genome; 10]))
+            .collect();
+        let children: Vec<_> = (5..9)
+            .map(|genome| EcIndividual::new(genome, scorer.score(&genome)))
+            .collect();
+        // The best parent survives alongside the children.
+        let survival = DownSample::new(Elitism::new(1), &sample, RandomDownSampler, scorer);
+        let next = survival.survivors(&parents, children, &mut rng).unwrap();
+
+        assert_eq!(5, next.len());
+        assert!(next.iter().any(|individual| individual.genome < 5));
+        assert!(next
+            .iter()
+            .all(|individual| individual.test_results == scored_on(individual.genome, &original)));
+        assert_ne!(original, sample.with_indices(<[usize]>::to_vec));
+    }
+
+    #[derive(Clone, Copy)]
+    struct Increment;
+
+    impl Operator<usize> for Increment {
+        type Output = usize;
+
+        fn apply<R: Rng + ?Sized>(&self, genome: usize, _: &mut R) -> Result<usize> {
+            Ok(genome + 1)
+        }
+    }
+    impl Composable for Increment {}
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn each_child_is_scored_once_per_generation() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let sample = CaseSample::random(100, 10, &mut rng).unwrap();
+        let elitist_sample = CaseSample::random(100, 10, &mut rng).unwrap();
+        let num_calls = AtomicUsize::new(0);
+        let num_elitist_calls = AtomicUsize::new(0);
+        let scorer = DownSampledScorer::new(&sample, |genome: &usize, indices: &[usize]| {
+            num_calls.fetch_add(1, Ordering::Relaxed);
+            indices
+                .iter()
+                .map(|index| index + genome)
+                .collect::<Vec<_>>()
+        });
+        let elitist_scorer =
+            DownSampledScorer::new(&elitist_sample, |genome: &usize, indices: &[usize]| {
+                num_elitist_calls.fetch_add(1, Ordering::Relaxed);
+                indices
+                    .iter()
+                    .map(|index| index + genome)
+                    .collect::<Vec<_>>()
+            });
+        let population: Vec<_> = (1..=10)
+            .map(|genome| EcIndividual::new(genome, vec![genome; 10]))
+            .collect();
+
+        let mut generational = Generation::new(
+            Select::new(Random)
+                .then(GenomeExtractor)
+                .then(Increment)
+                .wrap::<GenomeScorer<_, _>>(scorer),
+            population.clone(),
+        )
+        .with_survival(DownSample::new(
+            Generational,
+            &sample,
+            RandomDownSampler,
+            scorer,
+        ));
+        let mut elitist = Generation::new(
+            Select::new(Random)
+                .then(GenomeExtractor)
+                .then(Increment)
+                .wrap::<GenomeScorer<_, _>>(elitist_scorer),
+            population,
+        )
+        .with_survival(DownSample::new(
+            Elitism::new(2),
+            &elitist_sample,
+            RandomDownSampler,
+            elitist_scorer,
+        ));
+        for seed in 0..5 {
+            num_calls.store(0, Ordering::Relaxed);
+            generational.serial_next(seed).unwrap();
+            assert_eq!(10, num_calls.load(Ordering::Relaxed));
+
+            // The 8 children, and the 2 elites that survive.
+            num_elitist_calls.store(0, Ordering::Relaxed);
+            elitist.par_next(seed).unwrap();
+            assert_eq!(8 + 2, num_elitist_calls.load(Ordering::Relaxed));
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn informed_down_sampling_chooses_distinct_cases() {
+        // Cases 0, 1, and 2 are solved by exactly the same individuals,
+        // while case 3 is solved by different ones.
+        let population: Vec<EcIndividual<Vec<i32>, ()>> = vec![
+            EcIndividual::new(vec![0, 0, 0, 1], ()),
+            EcIndividual::new(vec![0, 0, 0, 1], ()),
+            EcIndividual::new(vec![1, 1, 1, 0], ()),
+        ];
+        let full_scorer = FnScorer(|genome: &Vec<i32>| -> TestResults<Error<i32>> {
+            genome.iter().copied().collect()
+        });
+        let sampler =
+            InformedDownSampler::new(1.0, full_scorer, |error: &Error<i32>| error.error == 0);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..10 {
+            let indices = sampler.down_sample(&population, 4, 2, &mut rng).unwrap();
+            assert_eq!(2, indices.len());
+            assert!(indices.contains(&3));
+        }
+    }
+}
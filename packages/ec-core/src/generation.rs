@@ -1,75 +1,419 @@
-use anyhow::Result;
+use anyhow::{ensure, Context, Result};
 use itertools::Itertools;
-use rayon::prelude::{FromParallelIterator, IntoParallelIterator, ParallelIterator};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::{IndexedParallelIterator, IntoParallelIterator, ParallelIterator};
 
-use crate::{operator::Operator, population::Population};
+use crate::{
+    operator::{Operator, Prepare},
+    population::Population,
+    survival::{Generational, Survival},
+};
 
-pub struct Generation<P, C> {
+pub struct Generation<P, C, S = Generational> {
     population: P,
     child_maker: C,
+    survival: S,
 }
 
-impl<P, C> Generation<P, C> {
+impl<P, C, S> Generation<P, C, S> {
     pub const fn population(&self) -> &P {
         &self.population
     }
+
+    pub const fn population_mut(&mut self) -> &mut P {
+        &mut self.population
+    }
+
+    pub const fn child_maker(&self) -> &C {
+        &self.child_maker
+    }
 }
 
 impl<P, C> Generation<P, C> {
+    /// Create a new `Generation` that replaces the whole population with
+    /// newly made children every generation.
     pub const fn new(child_maker: C, population: P) -> Self {
         Self {
             population,
             child_maker,
+            survival: Generational,
         }
     }
 }
 
-impl<P, C> Generation<P, C>
+impl<P, C, S> Generation<P, C, S> {
+    /// Use the given survivor selection strategy when building the next
+    /// population from the parents and their children, e.g., to carry
+    /// over some number of elites.
+    pub fn with_survival<T>(self, survival: T) -> Generation<P, C, T> {
+        Generation {
+            population: self.population,
+            child_maker: self.child_maker,
+            survival,
+        }
+    }
+}
+
+impl<P, C, S> Generation<P, C, S>
 where
-    P: Population + FromParallelIterator<P::Individual> + Send + Sync,
+    P: Population,
+    S: Survival<P>,
+{
+    /// The number of children that will be made (and evaluated) in the
+    /// next generation.
+    pub fn num_children(&self) -> usize {
+        self.survival.num_children(&self.population)
+    }
+}
+
+/// Draw one seed per child from `rng`.
+///
+/// Each child gets its own random number generator seeded from
+/// these values, which means that the children we make depend only
+/// on the state of `rng` and the child's position in the batch,
+/// and not on how the work is spread across threads. As a result the
+/// `serial_next` and `par_next` methods of `Generation`, `Alps`, and
+/// `MapElites` produce identical results when given the same seed.
+pub(crate) fn child_seeds<R: Rng + ?Sized>(rng: &mut R, num_children: usize) -> Vec<u64> {
+    (0..num_children).map(|_| rng.gen()).collect()
+}
+
+/// The child makers prepared for one generation: either one shared by
+/// all the children, or one for each child (see
+/// [`Prepare::prepare_children`]).
+enum PreparedChildMakers<C> {
+    Shared(C),
+    PerChild(Vec<C>),
+}
+
+impl<C> PreparedChildMakers<C> {
+    fn prepare<P, M, R>(
+        child_maker: &M,
+        population: &P,
+        num_children: usize,
+        rng: &mut R,
+    ) -> Result<Self>
+    where
+        M: Prepare<P, Prepared = C>,
+        R: Rng + ?Sized,
+    {
+        if let Some(child_makers) = child_maker.prepare_children(population, num_children, rng)? {
+            ensure!(
+                child_makers.len() == num_children,
+                "Prepared {} child makers for {num_children} children",
+                child_makers.len()
+            );
+            Ok(Self::PerChild(child_makers))
+        } else {
+            Ok(Self::Shared(child_maker.prepare(population)?))
+        }
+    }
+
+    fn get(&self, child: usize) -> Result<&C> {
+        match self {
+            Self::Shared(child_maker) => Ok(child_maker),
+            Self::PerChild(child_makers) => child_makers
+                .get(child)
+                .with_context(|| format!("There's no child maker for child {child}")),
+        }
+    }
+}
+
+impl<P, C, S> Generation<P, C, S>
+where
+    P: Population + Send + Sync,
     P::Individual: Send,
-    C: for<'a> Operator<&'a P, Output = P::Individual> + Send + Sync,
+    C: Prepare<P>,
+    C::Prepared: for<'a> Operator<&'a P, Output = P::Individual> + Send + Sync,
+    S: Survival<P>,
 {
     /// Make the next generation using a Rayon parallel iterator.
+    ///
+    /// The `seed` completely determines the random choices made
+    /// when creating the next generation, so two calls with the
+    /// same seed on the same population will generate the same
+    /// new population.
+    ///
+    /// The child maker is [prepared](Prepare) once for the current
+    /// population, and that prepared version makes all the children. If
+    /// it [prepares each child separately](Prepare::prepare_children),
+    /// e.g., to select all the children's parents together, each child is
+    /// made by its own prepared version instead.
+    ///
     /// # Errors
     ///
     /// This can return errors if any aspect of creating the next generation
-    /// fail. That can include constructing or scoring the genomes.
-    pub fn par_next(&mut self) -> anyhow::Result<()> {
-        let pop_size = self.population.size();
-        let population = (0..pop_size)
+    /// fail. That can include preparing the child maker or constructing or
+    /// scoring the genomes.
+    pub fn par_next(&mut self, seed: u64) -> anyhow::Result<()> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let num_children = self.num_children();
+        let child_makers = PreparedChildMakers::prepare(
+            &self.child_maker,
+            &self.population,
+            num_children,
+            &mut rng,
+        )?;
+        let children = child_seeds(&mut rng, num_children)
             .into_par_iter()
-            .map_init(rand::thread_rng, |rng, _| {
-                self.child_maker.apply(&self.population, rng)
+            .enumerate()
+            .map(|(child, child_seed)| {
+                let mut rng = StdRng::seed_from_u64(child_seed);
+                child_makers.get(child)?.apply(&self.population, &mut rng)
             })
             .collect::<Result<_>>()?;
         // TODO: We can reduce allocations by pre-allocating the memory for "old" and
         // "new"   population in `::new()` and then re-using those vectors here.
-        self.population = population;
+        self.population = self
+            .survival
+            .survivors(&self.population, children, &mut rng)?;
         Ok(())
     }
 }
 
-impl<P, C> Generation<P, C>
+impl<P, C, S> Generation<P, C, S>
 where
-    P: Population + FromIterator<P::Individual>,
-    C: for<'a> Operator<&'a P, Output = P::Individual>,
+    P: Population,
+    C: Prepare<P>,
+    C::Prepared: for<'a> Operator<&'a P, Output = P::Individual>,
+    S: Survival<P>,
 {
     /// Make the next generation serially.
+    ///
+    /// The `seed` completely determines the random choices made
+    /// when creating the next generation, so two calls with the
+    /// same seed on the same population will generate the same
+    /// new population.
+    ///
+    /// The child maker is [prepared](Prepare) once for the current
+    /// population, and that prepared version makes all the children. If
+    /// it [prepares each child separately](Prepare::prepare_children),
+    /// e.g., to select all the children's parents together, each child is
+    /// made by its own prepared version instead.
+    ///
     /// # Errors
     ///
     /// This can return errors if any aspect of creating the next generation
-    /// fail. That can include constructing or scoring the genomes.
-    pub fn serial_next(&mut self) -> anyhow::Result<()> {
-        let pop_size = self.population.size();
-        let mut rng = rand::thread_rng();
-        // Switch to `repeat_with` and `take`
-        let new_population = (0..pop_size)
-            .map(|_| self.child_maker.apply(&self.population, &mut rng))
+    /// fail. That can include preparing the child maker or constructing or
+    /// scoring the genomes.
+    pub fn serial_next(&mut self, seed: u64) -> anyhow::Result<()> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let num_children = self.num_children();
+        let child_makers = PreparedChildMakers::prepare(
+            &self.child_maker,
+            &self.population,
+            num_children,
+            &mut rng,
+        )?;
+        let children = child_seeds(&mut rng, num_children)
+            .into_iter()
+            .enumerate()
+            .map(|(child, child_seed)| {
+                let mut rng = StdRng::seed_from_u64(child_seed);
+                child_makers.get(child)?.apply(&self.population, &mut rng)
+            })
             .try_collect()?;
         // TODO: We can reduce allocations by pre-allocating the memory for "old" and
         // "new"   population in `::new()` and then re-using those vectors here.
-        self.population = new_population;
+        self.population = self
+            .survival
+            .survivors(&self.population, children, &mut rng)?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{
+        operator::{
+            select_parents::SelectParents,
+            selector::{random::Random, PreparedSelector, Select, Selector},
+            Composable,
+        },
+        survival::Elitism,
+    };
+
+    #[derive(Clone, Copy)]
+    struct AddNoise;
+
+    impl Operator<&i32> for AddNoise {
+        type Output = i32;
+
+        fn apply<R: Rng + ?Sized>(&self, input: &i32, rng: &mut R) -> Result<Self::Output> {
+            Ok(input + rng.gen_range(-100..100))
+        }
+    }
+    impl Composable for AddNoise {}
+
+    fn make_generation() -> Generation<
+        Vec<i32>,
+        impl Prepare<Vec<i32>, Prepared: for<'a> Operator<&'a Vec<i32>, Output = i32> + Send + Sync>,
+    > {
+        Generation::new(Select::new(Random).then(AddNoise), (0..50).collect())
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn same_seed_same_serial_population() {
+        let mut first = make_generation();
+        let mut second = make_generation();
+        for seed in [3, 17, 42] {
+            first.serial_next(seed).unwrap();
+            second.serial_next(seed).unwrap();
+        }
+        assert_eq!(first.population(), second.population());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn same_seed_same_parallel_population() {
+        let mut first = make_generation();
+        let mut second = make_generation();
+        for seed in [3, 17, 42] {
+            first.par_next(seed).unwrap();
+            second.par_next(seed).unwrap();
+        }
+        assert_eq!(first.population(), second.population());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn serial_and_parallel_agree() {
+        let mut serial = make_generation();
+        let mut parallel = make_generation();
+        for seed in [3, 17, 42] {
+            serial.serial_next(seed).unwrap();
+            parallel.par_next(seed).unwrap();
+        }
+        assert_eq!(serial.population(), parallel.population());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn elitism_keeps_best_in_serial_and_parallel() {
+        let mut serial = make_generation().with_survival(Elitism::new(1));
+        let mut parallel = make_generation().with_survival(Elitism::new(1));
+        assert_eq!(49, serial.num_children());
+        for seed in [3, 17, 42] {
+            let serial_best = *serial.population().iter().max().unwrap();
+            serial.serial_next(seed).unwrap();
+            parallel.par_next(seed).unwrap();
+            assert!(serial.population().contains(&serial_best));
+        }
+        assert_eq!(serial.population(), parallel.population());
+    }
+
+    /// Selects randomly, counting how many times it's been prepared.
+    struct CountPreparations<'a>(&'a AtomicUsize);
+
+    impl Selector<Vec<i32>> for CountPreparations<'_> {
+        fn select<'pop, R: Rng + ?Sized>(
+            &self,
+            population: &'pop Vec<i32>,
+            rng: &mut R,
+        ) -> Result<&'pop i32> {
+            Random.select(population, rng)
+        }
+
+        fn prepare_selector(&self, _: &Vec<i32>) -> Result<Option<PreparedSelector<Vec<i32>>>> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Ok(Some(PreparedSelector::new(Random)))
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn child_maker_is_prepared_once_per_generation() {
+        let num_preparations = AtomicUsize::new(0);
+        let mut generation = Generation::new(
+            Select::new(CountPreparations(&num_preparations)).then(AddNoise),
+            (0..50).collect::<Vec<_>>(),
+        );
+        for seed in [3, 17, 42] {
+            generation.serial_next(seed).unwrap();
+            generation.par_next(seed).unwrap();
+        }
+        assert_eq!(6, num_preparations.load(Ordering::Relaxed));
+    }
+
+    /// A selector that only implements `Selector`, with nothing to prepare.
+    struct Smallest;
+
+    impl Selector<Vec<i32>> for Smallest {
+        fn select<'pop, R: Rng + ?Sized>(
+            &self,
+            population: &'pop Vec<i32>,
+            _: &mut R,
+        ) -> Result<&'pop i32> {
+            population
+                .iter()
+                .min()
+                .ok_or_else(|| anyhow::anyhow!("The population was empty"))
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn selectors_do_not_have_to_be_prepared() {
+        let mut generation =
+            Generation::new(Select::new(Smallest).then(AddNoise), (0..50).collect());
+        generation.serial_next(3).unwrap();
+        generation.par_next(17).unwrap();
+        assert_eq!(50, generation.population().len());
+    }
+
+    /// Selects randomly, counting the calls to `select_many`.
+    struct CountBatches<'a>(&'a AtomicUsize);
+
+    impl Selector<Vec<i32>> for CountBatches<'_> {
+        fn select<'pop, R: Rng + ?Sized>(
+            &self,
+            population: &'pop Vec<i32>,
+            rng: &mut R,
+        ) -> Result<&'pop i32> {
+            Random.select(population, rng)
+        }
+
+        fn select_many<'pop, R: Rng + ?Sized>(
+            &self,
+            population: &'pop Vec<i32>,
+            count: usize,
+            rng: &mut R,
+        ) -> Result<Vec<&'pop i32>> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            (0..count).map(|_| Random.select(population, rng)).collect()
+        }
+    }
+
+    impl Operator<[&i32; 1]> for AddNoise {
+        type Output = i32;
+
+        fn apply<R: Rng + ?Sized>(&self, [parent]: [&i32; 1], rng: &mut R) -> Result<i32> {
+            self.apply(parent, rng)
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn parents_are_selected_in_one_batch_per_generation() {
+        let num_batches = AtomicUsize::new(0);
+        let make_generation = || {
+            Generation::new(
+                SelectParents::<_, _, 1>::new(CountBatches(&num_batches), AddNoise),
+                (0..50).collect::<Vec<_>>(),
+            )
+            .with_survival(Elitism::new(1))
+        };
+        let mut serial = make_generation();
+        let mut parallel = make_generation();
+        for seed in [3, 17, 42] {
+            serial.serial_next(seed).unwrap();
+            parallel.par_next(seed).unwrap();
+        }
+        assert_eq!(6, num_batches.load(Ordering::Relaxed));
+        assert_eq!(serial.population(), parallel.population());
+    }
+}
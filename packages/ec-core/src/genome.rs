@@ -1,3 +1,81 @@
 pub trait Genome {
     type Gene;
 }
+
+/// Genomes that can be compared to see how different they are.
+///
+/// This is used, e.g., to measure the genotypic diversity of a population
+/// (see [`Statistics::with_genome_distance`](crate::statistics::Statistics::with_genome_distance)).
+pub trait GenomeDistance {
+    /// The distance between two genomes, which should be zero for identical
+    /// genomes and never negative.
+    fn distance(&self, other: &Self) -> f64;
+}
+
+/// Genomes that have a size, e.g., the number of genes in a linear genome.
+///
+/// This is used for bloat control, e.g., by breaking ties between
+/// individuals with the same test results in favor of the smaller one (see
+/// [`LexicographicParsimony`](crate::individual::comparator::LexicographicParsimony)).
+pub trait GenomeSize {
+    fn genome_size(&self) -> usize;
+}
+
+impl<T> GenomeSize for Vec<T> {
+    fn genome_size(&self) -> usize {
+        self.len()
+    }
+}
+
+/// The number of positions where the genes differ, with every gene past
+/// the end of the shorter sequence counting as a difference.
+#[must_use]
+pub fn hamming_distance<T: PartialEq>(first: &[T], second: &[T]) -> usize {
+    let num_different = first
+        .iter()
+        .zip(second)
+        .filter(|(first, second)| first != second)
+        .count();
+    num_different + first.len().abs_diff(second.len())
+}
+
+/// The smallest number of genes that have to be inserted, deleted, or
+/// replaced to turn one sequence into the other.
+#[must_use]
+pub fn levenshtein_distance<T: PartialEq>(first: &[T], second: &[T]) -> usize {
+    // `distances[j]` is the distance between the part of `first` seen so far
+    // and the first `j` genes of `second`.
+    let mut distances: Vec<usize> = (0..=second.len()).collect();
+    for (i, first_gene) in first.iter().enumerate() {
+        let mut previous_diagonal = distances[0];
+        distances[0] = i + 1;
+        for (j, second_gene) in second.iter().enumerate() {
+            let replace = previous_diagonal + usize::from(first_gene != second_gene);
+            previous_diagonal = distances[j + 1];
+            distances[j + 1] = replace.min(distances[j] + 1).min(previous_diagonal + 1);
+        }
+    }
+    distances[second.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hamming_counts_differences_and_extra_genes() {
+        assert_eq!(hamming_distance(&[1, 2, 3], &[1, 2, 3]), 0);
+        assert_eq!(hamming_distance(&[1, 2, 3], &[1, 5, 3]), 1);
+        assert_eq!(hamming_distance(&[1, 2, 3], &[0, 2, 3, 4, 5]), 3);
+    }
+
+    #[test]
+    fn levenshtein_allows_insertions_and_deletions() {
+        let kitten: Vec<_> = "kitten".chars().collect();
+        let sitting: Vec<_> = "sitting".chars().collect();
+        assert_eq!(levenshtein_distance(&kitten, &sitting), 3);
+        assert_eq!(levenshtein_distance(&kitten, &kitten), 0);
+        assert_eq!(levenshtein_distance(&kitten, &[]), 6);
+        assert_eq!(levenshtein_distance(&[1, 2, 3, 4], &[2, 3, 4]), 1);
+    }
+}
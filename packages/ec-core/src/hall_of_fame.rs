@@ -0,0 +1,334 @@
+//! A hall of fame: the best individuals seen at any point in a run.
+//!
+//! A [`Generation`](crate::generation::Generation) only holds the current
+//! population, so a great individual that's lost to drift is gone for good.
+//! A [`HallOfFame`] keeps copies of the best distinct individuals ever seen,
+//! where individuals are distinct if their genomes are. It's shared (by
+//! reference) with:
+//!   - a [`Run`](crate::run::Run), through
+//!     [`with_hall_of_fame`](crate::run::Run::with_hall_of_fame), which updates
+//!     it with each new population;
+//!   - optionally, the [`FromHallOfFame`] survival strategy, which feeds the
+//!     best members back into the population so they can be selected as parents
+//!     again.
+//!
+//! It can then be queried at the end of the run.
+
+use std::{
+    cmp::Ordering,
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use anyhow::{ensure, Result};
+use rand::Rng;
+
+use crate::{individual::Individual, population::Population, survival::Survival};
+
+/// The best (at most) `capacity` distinct individuals ever seen, ordered by
+/// their `Ord` implementation or by a custom comparator.
+///
+/// Two individuals are the same if their genomes are equal; only the
+/// better of the two is kept.
+#[derive(Debug)]
+pub struct HallOfFame<I, C = fn(&I, &I) -> Ordering> {
+    capacity: usize,
+    compare: C,
+    // The best member is at the front.
+    members: RwLock<Vec<I>>,
+}
+
+impl<I: Ord> HallOfFame<I> {
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        let compare: fn(&I, &I) -> Ordering = I::cmp;
+        Self::new_with_comparator(capacity, compare)
+    }
+}
+
+impl<I, C> HallOfFame<I, C> {
+    /// Order individuals with `compare`, where individuals that compare as
+    /// `Greater` are better.
+    #[must_use]
+    pub const fn new_with_comparator(capacity: usize, compare: C) -> Self {
+        Self {
+            capacity,
+            compare,
+            members: RwLock::new(Vec::new()),
+        }
+    }
+
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.read().len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Call `f` with the members, best first.
+    pub fn with_members<T>(&self, f: impl FnOnce(&[I]) -> T) -> T {
+        f(&self.read())
+    }
+
+    /// A copy of the members, best first.
+    #[must_use]
+    pub fn members(&self) -> Vec<I>
+    where
+        I: Clone,
+    {
+        self.read().clone()
+    }
+
+    /// A copy of the best member, or `None` if the hall of fame is empty.
+    #[must_use]
+    pub fn best(&self) -> Option<I>
+    where
+        I: Clone,
+    {
+        self.read().first().cloned()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Vec<I>> {
+        // Only a panic while holding the write lock can poison it, and the
+        // members are always left sorted, so poisoning can be ignored.
+        self.members.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Vec<I>> {
+        self.members.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<I, C> HallOfFame<I, C>
+where
+    I: Individual + Clone,
+    I::Genome: PartialEq,
+    C: Fn(&I, &I) -> Ordering,
+{
+    /// Add copies of any of `individuals` that are good enough to be in the
+    /// hall of fame, removing the worst members if it's full. Returns the
+    /// number of individuals with new genomes that were added.
+    pub fn update<'a>(&self, individuals: impl IntoIterator<Item = &'a I>) -> usize
+    where
+        I: 'a,
+    {
+        let mut members = self.write();
+        let mut num_added = 0;
+        for individual in individuals {
+            let is_full = members.len() >= self.capacity;
+            if is_full
+                && members
+                    .last()
+                    .is_none_or(|worst| (self.compare)(individual, worst) != Ordering::Greater)
+            {
+                continue;
+            }
+            match members
+                .iter()
+                .position(|member| member.genome() == individual.genome())
+            {
+                Some(index) if (self.compare)(individual, &members[index]) != Ordering::Greater => {
+                    continue;
+                }
+                Some(index) => {
+                    members.remove(index);
+                }
+                None => num_added += 1,
+            }
+            // Individuals that tie with existing members go after them.
+            let index = members
+                .partition_point(|member| (self.compare)(member, individual) != Ordering::Less);
+            members.insert(index, individual.clone());
+            members.truncate(self.capacity);
+        }
+        num_added
+    }
+}
+
+/// Carry (copies of) the best `num_elites` members of a [`HallOfFame`]
+/// into the next population, and fill the rest of it with children.
+///
+/// This is like [`Elitism`](crate::survival::Elitism), except that the
+/// elites can come from any earlier generation, not just the current one.
+/// If the hall of fame has fewer than `num_elites` members, all of them are
+/// used.
+///
+/// The hall of fame isn't updated here, so it has to be updated (typically
+/// by [`Run::with_hall_of_fame`](crate::run::Run::with_hall_of_fame)) for
+/// there to be any elites.
+#[derive(Debug)]
+pub struct FromHallOfFame<'a, I, C> {
+    hall_of_fame: &'a HallOfFame<I, C>,
+    num_elites: usize,
+}
+
+impl<'a, I, C> FromHallOfFame<'a, I, C> {
+    #[must_use]
+    pub const fn new(hall_of_fame: &'a HallOfFame<I, C>, num_elites: usize) -> Self {
+        Self {
+            hall_of_fame,
+            num_elites,
+        }
+    }
+
+    fn num_elites(&self) -> usize {
+        self.num_elites.min(self.hall_of_fame.len())
+    }
+}
+
+impl<P, I, C> Survival<P> for FromHallOfFame<'_, I, C>
+where
+    P: Population<Individual = I> + FromIterator<I>,
+    I: Clone,
+{
+    fn num_children(&self, population: &P) -> usize {
+        population.size().saturating_sub(self.num_elites())
+    }
+
+    fn survivors<R: Rng + ?Sized>(&self, parents: &P, children: Vec<I>, _: &mut R) -> Result<P> {
+        let num_elites = self.num_elites();
+        ensure!(
+            parents.size() >= num_elites,
+            "The population had size {} and we wanted to add {} elites from the hall of fame",
+            parents.size(),
+            num_elites
+        );
+        Ok(self.hall_of_fame.with_members(|members| {
+            members[..num_elites]
+                .iter()
+                .cloned()
+                .chain(children)
+                .collect()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, thread_rng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        generation::Generation,
+        individual::ec::EcIndividual,
+        operator::{
+            selector::{best::Best, Select},
+            Composable, Operator,
+        },
+        run::Run,
+    };
+
+    type Scored = EcIndividual<char, i32>;
+
+    fn individuals(genomes_and_scores: &[(char, i32)]) -> Vec<Scored> {
+        genomes_and_scores
+            .iter()
+            .map(|&(genome, score)| EcIndividual::new(genome, score))
+            .collect()
+    }
+
+    fn genomes<C>(hall_of_fame: &HallOfFame<Scored, C>) -> String {
+        hall_of_fame.with_members(|members| members.iter().map(|member| member.genome).collect())
+    }
+
+    #[test]
+    fn keeps_the_best_distinct_individuals() {
+        let hall_of_fame = HallOfFame::new(3);
+
+        let num_added = hall_of_fame.update(&individuals(&[('a', 5), ('b', 1), ('c', 3)]));
+        assert_eq!(num_added, 3);
+        assert_eq!(genomes(&hall_of_fame), "acb");
+
+        // A better copy of `c` replaces the old one, and `d` pushes `b` out.
+        let num_added = hall_of_fame.update(&individuals(&[('c', 9), ('d', 2), ('e', 0)]));
+        assert_eq!(num_added, 1);
+        assert_eq!(genomes(&hall_of_fame), "cad");
+        assert_eq!(hall_of_fame.best().map(|best| best.test_results), Some(9));
+
+        // Worse copies of members don't change anything.
+        let num_added = hall_of_fame.update(&individuals(&[('a', 4), ('d', 1)]));
+        assert_eq!(num_added, 0);
+        assert_eq!(genomes(&hall_of_fame), "cad");
+    }
+
+    #[test]
+    fn custom_comparators_are_used() {
+        // Lower scores are better.
+        let hall_of_fame = HallOfFame::new_with_comparator(2, |x: &Scored, y: &Scored| {
+            y.test_results.cmp(&x.test_results)
+        });
+        hall_of_fame.update(&individuals(&[('a', 5), ('b', 1), ('c', 3)]));
+        assert_eq!(genomes(&hall_of_fame), "bc");
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn elites_are_fed_back_into_the_population() {
+        let hall_of_fame = HallOfFame::new(5);
+        hall_of_fame.update(&individuals(&[('a', 5), ('b', 1), ('c', 3)]));
+        let survival = FromHallOfFame::new(&hall_of_fame, 2);
+
+        let parents = individuals(&[('x', 0), ('y', 0), ('z', 0), ('w', 0)]);
+        assert_eq!(survival.num_children(&parents), 2);
+        let children = individuals(&[('d', 0), ('e', 0)]);
+        let survivors = survival
+            .survivors(&parents, children, &mut thread_rng())
+            .unwrap();
+        let genomes: String = survivors.iter().map(|survivor| survivor.genome).collect();
+        assert_eq!(genomes, "acde");
+
+        // There are only three members, so only they can be elites.
+        let survival = FromHallOfFame::new(&hall_of_fame, 10);
+        assert_eq!(survival.num_children(&parents), 1);
+    }
+
+    // Makes a child whose genome and score are one less than its parent's.
+    #[derive(Clone, Copy)]
+    struct Decrement;
+
+    impl Operator<&EcIndividual<i32, i32>> for Decrement {
+        type Output = EcIndividual<i32, i32>;
+
+        fn apply<R: Rng + ?Sized>(
+            &self,
+            parent: &EcIndividual<i32, i32>,
+            _: &mut R,
+        ) -> Result<Self::Output> {
+            Ok(EcIndividual::new(
+                parent.genome - 1,
+                parent.test_results - 1,
+            ))
+        }
+    }
+    impl Composable for Decrement {}
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn runs_update_the_hall_of_fame() {
+        let hall_of_fame = HallOfFame::new(3);
+        let population = vec![EcIndividual::new(10, 10), EcIndividual::new(20, 20)];
+        let generation = Generation::new(Select::new(Best).then(Decrement), population);
+        Run::new(generation)
+            .with_max_generations(3)
+            .with_hall_of_fame(&hall_of_fame)
+            .serial_run(&mut StdRng::seed_from_u64(0))
+            .unwrap();
+
+        // The children 19 and 18 push out the initial 10, but the population
+        // gets worse every generation, so 17 isn't good enough to join them.
+        let genomes = hall_of_fame.with_members(|members| {
+            members
+                .iter()
+                .map(|member| member.genome)
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(genomes, [20, 19, 18]);
+    }
+}
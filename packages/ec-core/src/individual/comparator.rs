@@ -0,0 +1,132 @@
+//! Ways of comparing individuals, used by selectors like
+//! [`Tournament`](crate::operator::selector::tournament::Tournament) to
+//! decide which individual is better.
+
+use std::cmp::Ordering;
+
+use super::Individual;
+use crate::{
+    genome::GenomeSize,
+    operator::{Describe, Description},
+};
+
+/// Compares two individuals, where the better individual is `Greater`.
+pub trait Comparator<I> {
+    fn compare(&self, x: &I, y: &I) -> Ordering;
+}
+
+impl<I, F> Comparator<I> for F
+where
+    F: Fn(&I, &I) -> Ordering,
+{
+    fn compare(&self, x: &I, y: &I) -> Ordering {
+        self(x, y)
+    }
+}
+
+/// Compare individuals using their `Ord` implementation, which for
+/// [`EcIndividual`](super::ec::EcIndividual)s compares their test results.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Natural;
+
+impl<I: Ord> Comparator<I> for Natural {
+    fn compare(&self, x: &I, y: &I) -> Ordering {
+        x.cmp(y)
+    }
+}
+
+impl Describe for Natural {
+    fn describe(&self) -> Description {
+        Description::new("Natural")
+    }
+}
+
+/// A comparator function with a name, so that selectors using it can be
+/// [described](Describe). Plain closures can be used as comparators, but
+/// can't describe themselves.
+#[derive(Debug, Clone)]
+pub struct NamedComparator<F> {
+    name: String,
+    compare: F,
+}
+
+impl<F> NamedComparator<F> {
+    pub fn new(name: impl Into<String>, compare: F) -> Self {
+        Self {
+            name: name.into(),
+            compare,
+        }
+    }
+}
+
+impl<I, F> Comparator<I> for NamedComparator<F>
+where
+    F: Fn(&I, &I) -> Ordering,
+{
+    fn compare(&self, x: &I, y: &I) -> Ordering {
+        (self.compare)(x, y)
+    }
+}
+
+impl<F> Describe for NamedComparator<F> {
+    fn describe(&self) -> Description {
+        Description::new(self.name.clone())
+    }
+}
+
+/// Lexicographic parsimony pressure (Luke & Panait): individuals are
+/// compared using their `Ord` implementation, and ties are broken in favor
+/// of the individual with the smaller genome.
+///
+/// This only makes a difference when there are ties, so it works best when
+/// there are few possible test results, e.g., with integer scores.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LexicographicParsimony;
+
+impl<I> Comparator<I> for LexicographicParsimony
+where
+    I: Individual + Ord,
+    I::Genome: GenomeSize,
+{
+    fn compare(&self, x: &I, y: &I) -> Ordering {
+        x.cmp(y)
+            .then_with(|| y.genome().genome_size().cmp(&x.genome().genome_size()))
+    }
+}
+
+impl Describe for LexicographicParsimony {
+    fn describe(&self) -> Description {
+        Description::new("LexicographicParsimony")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::individual::ec::EcIndividual;
+
+    #[test]
+    fn ties_are_broken_by_size() {
+        let short = EcIndividual::new(vec![1], 5);
+        let long = EcIndividual::new(vec![1, 2, 3], 5);
+        let better = EcIndividual::new(vec![1, 2, 3, 4], 6);
+        assert_eq!(Natural.compare(&short, &long), Ordering::Equal);
+        assert_eq!(
+            LexicographicParsimony.compare(&short, &long),
+            Ordering::Greater
+        );
+        assert_eq!(
+            LexicographicParsimony.compare(&short, &better),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn named_comparators_can_be_described() {
+        let longest = NamedComparator::new("Longest", |x: &Vec<i32>, y: &Vec<i32>| {
+            x.len().cmp(&y.len())
+        });
+        assert_eq!(longest.compare(&vec![1, 2], &vec![3]), Ordering::Greater);
+        assert_eq!(longest.describe().to_string(), "Longest");
+    }
+}
@@ -11,17 +11,31 @@ use rand::prelude::Distribution;
 
 use super::{
     scorer::{FnScorer, Scorer},
-    Individual,
+    Aged, Identified, Individual, IndividualId, Rescorable,
 };
 
 /// `EcIndividual` is a struct that represents an individual in an evolutionary
 /// computation system. It contains a genome and the results of scoring the
 /// genome.
-#[derive(Debug, Eq, PartialEq, Clone)]
+///
+/// Two individuals are equal if they have the same genome and test results;
+/// the bookkeeping fields (`birth_generation` and `id`) are ignored, so
+/// copies of an individual are equal however old they are or wherever they
+/// are in a lineage.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[allow(clippy::module_name_repetitions)]
 pub struct EcIndividual<G, R> {
     pub genome: G,
     pub test_results: R,
+    /// The generation in which the oldest genetic material in this
+    /// individual was created; see [`Aged`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub birth_generation: usize,
+    /// This individual's ID in a lineage log, if it's been recorded in one;
+    /// see [`Identified`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub id: Option<IndividualId>,
 }
 
 impl<G, R> Individual for EcIndividual<G, R> {
@@ -39,16 +53,60 @@ impl<G, R> Individual for EcIndividual<G, R> {
     }
 }
 
+impl<G, R> Aged for EcIndividual<G, R> {
+    fn birth_generation(&self) -> usize {
+        self.birth_generation
+    }
+
+    fn set_birth_generation(&mut self, birth_generation: usize) {
+        self.birth_generation = birth_generation;
+    }
+}
+
+impl<G, R> Rescorable for EcIndividual<G, R> {
+    fn set_test_results(&mut self, test_results: R) {
+        self.test_results = test_results;
+    }
+}
+
+impl<G, R> Identified for EcIndividual<G, R> {
+    fn id(&self) -> Option<IndividualId> {
+        self.id
+    }
+
+    fn set_id(&mut self, id: IndividualId) {
+        self.id = Some(id);
+    }
+}
+
 impl<G, R> EcIndividual<G, R> {
-    /// Create a new `EcIndividual` with the given genome and test results.
+    /// Create a new `EcIndividual` with the given genome and test results,
+    /// born in generation 0 and without an ID.
     pub const fn new(genome: G, test_results: R) -> Self {
         Self {
             genome,
             test_results,
+            birth_generation: 0,
+            id: None,
         }
     }
+
+    /// Set the generation this individual was born in.
+    #[must_use]
+    pub const fn with_birth_generation(mut self, birth_generation: usize) -> Self {
+        self.birth_generation = birth_generation;
+        self
+    }
 }
 
+impl<G: PartialEq, R: PartialEq> PartialEq for EcIndividual<G, R> {
+    fn eq(&self, other: &Self) -> bool {
+        self.genome == other.genome && self.test_results == other.test_results
+    }
+}
+
+impl<G: Eq, R: Eq> Eq for EcIndividual<G, R> {}
+
 impl<G: Eq, R: Ord> Ord for EcIndividual<G, R> {
     /// Compare two individuals based on their test results.
     fn cmp(&self, other: &Self) -> Ordering {
@@ -141,3 +199,18 @@ where
         EcIndividual::new(genome, test_results)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equality_ignores_age_and_lineage() {
+        let individual = EcIndividual::new(vec![1, 2], 5);
+        let mut copy = individual.clone().with_birth_generation(3);
+        copy.set_id(IndividualId(7));
+        assert_eq!(individual, copy);
+        assert_ne!(individual, EcIndividual::new(vec![1, 3], 5));
+        assert_ne!(individual, EcIndividual::new(vec![1, 2], 6));
+    }
+}
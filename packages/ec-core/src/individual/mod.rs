@@ -1,3 +1,4 @@
+pub mod comparator;
 pub mod ec;
 pub mod scorer;
 
@@ -8,3 +9,48 @@ pub trait Individual {
     fn genome(&self) -> &Self::Genome;
     fn test_results(&self) -> &Self::TestResults;
 }
+
+/// Individuals whose test results can be replaced, e.g., when they're
+/// re-scored on a new [sample](crate::down_sample) of the training cases.
+pub trait Rescorable: Individual {
+    fn set_test_results(&mut self, test_results: Self::TestResults);
+}
+
+/// Individuals that know how old their genetic material is, as used by
+/// age-layered populations ([ALPS](crate::alps)).
+///
+/// Randomly generated individuals are born in the generation they're
+/// created, while children inherit the birth generation of their oldest
+/// parent, so an individual's age is the number of generations since
+/// its oldest ancestor was created.
+pub trait Aged {
+    fn birth_generation(&self) -> usize;
+    fn set_birth_generation(&mut self, birth_generation: usize);
+
+    /// The age of this individual in the given generation.
+    fn age(&self, generation: usize) -> usize {
+        generation.saturating_sub(self.birth_generation())
+    }
+}
+
+/// A unique identifier for an individual, assigned by a
+/// [`LineageLog`](crate::lineage::LineageLog).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndividualId(pub u64);
+
+impl std::fmt::Display for IndividualId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Individuals that can be given an [`IndividualId`] so their
+/// [lineage](crate::lineage) can be tracked.
+///
+/// Individuals don't have an ID until one is assigned, e.g., when they're
+/// recorded in a lineage log.
+pub trait Identified {
+    fn id(&self) -> Option<IndividualId>;
+    fn set_id(&mut self, id: IndividualId);
+}
@@ -1,3 +1,13 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+    hash::{BuildHasher, Hash, RandomState},
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use anyhow::Result;
+use num_traits::ToPrimitive;
+
 pub trait Scorer<G> {
     type Score;
 
@@ -29,3 +39,327 @@ where
         (**self).score(genome)
     }
 }
+
+/// A scorer that can fail, e.g., because a program couldn't be run.
+///
+/// Use [`GenomeScorer::fallible`] to make children with a `TryScorer`; how
+/// failures are handled is then up to the `GenomeScorer`'s failure policy.
+/// Any [`Scorer`] can be used as a `TryScorer` that never fails by wrapping
+/// it in an [`InfallibleScorer`], which [`GenomeScorer::new`] does.
+///
+/// [`GenomeScorer::fallible`]: crate::operator::genome_scorer::GenomeScorer::fallible
+/// [`GenomeScorer::new`]: crate::operator::genome_scorer::GenomeScorer::new
+pub trait TryScorer<G> {
+    type Score;
+
+    /// Take a reference to a genome and try to score it.
+    ///
+    /// # Errors
+    /// This returns an error if the genome couldn't be scored.
+    fn try_score(&self, genome: &G) -> Result<Self::Score>;
+}
+
+impl<G, T> TryScorer<G> for &T
+where
+    T: TryScorer<G>,
+{
+    type Score = T::Score;
+
+    fn try_score(&self, genome: &G) -> Result<Self::Score> {
+        (**self).try_score(genome)
+    }
+}
+
+/// A [`TryScorer`] that never fails, made from any [`Scorer`].
+///
+/// (`TryScorer` can't simply be implemented for every `Scorer`, since that
+/// would conflict with the implementations for fallible scorers like
+/// [`FnTryScorer`].)
+#[derive(Debug, Clone, Copy)]
+pub struct InfallibleScorer<S>(pub S);
+
+impl<G, S> TryScorer<G> for InfallibleScorer<S>
+where
+    S: Scorer<G>,
+{
+    type Score = S::Score;
+
+    fn try_score(&self, genome: &G) -> Result<Self::Score> {
+        Ok(self.0.score(genome))
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct FnTryScorer<T>(pub T);
+
+impl<G, R, T> TryScorer<G> for FnTryScorer<T>
+where
+    T: Fn(&G) -> Result<R>,
+{
+    type Score = R;
+
+    fn try_score(&self, genome: &G) -> Result<Self::Score> {
+        self.0(genome)
+    }
+}
+
+/// Hit/miss statistics for a [`CachingScorer`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl CacheStats {
+    /// The fraction of lookups that were found in the cache, or `None` if
+    /// there haven't been any lookups.
+    #[must_use]
+    pub fn hit_rate(&self) -> Option<f64> {
+        let lookups = (self.hits + self.misses).to_f64()?;
+        (lookups > 0.0).then(|| self.hits.to_f64().unwrap_or(f64::NAN) / lookups)
+    }
+}
+
+impl Display for CacheStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} hits, {} misses", self.hits, self.misses)?;
+        if let Some(hit_rate) = self.hit_rate() {
+            write!(f, " ({:.1}% hit rate)", hit_rate * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+struct Entry<G, R> {
+    genome: G,
+    score: R,
+    last_used: u64,
+}
+
+struct Cache<G, R> {
+    // The entries are keyed on the hash of their genome. Different genomes
+    // can have the same hash, so each hash has a (usually one element) list
+    // of entries.
+    entries: HashMap<u64, Vec<Entry<G, R>>>,
+    len: usize,
+    // The hashes of the genomes in the cache, ordered from least to most
+    // recently used. Storing the hashes instead of the genomes means the
+    // cache only holds one copy of each genome.
+    recency: BTreeMap<u64, u64>,
+    hasher: RandomState,
+    clock: u64,
+    stats: CacheStats,
+}
+
+impl<G, R> Cache<G, R>
+where
+    G: Hash + Eq + Clone,
+    R: Clone,
+{
+    fn get(&mut self, genome: &G) -> Option<R> {
+        self.clock += 1;
+        let hash = self.hasher.hash_one(genome);
+        let Some(entry) = self
+            .entries
+            .get_mut(&hash)
+            .and_then(|bucket| bucket.iter_mut().find(|entry| entry.genome == *genome))
+        else {
+            self.stats.misses += 1;
+            return None;
+        };
+        self.recency.remove(&entry.last_used);
+        self.recency.insert(self.clock, hash);
+        entry.last_used = self.clock;
+        self.stats.hits += 1;
+        Some(entry.score.clone())
+    }
+
+    fn insert(&mut self, genome: &G, score: R, capacity: usize) {
+        if capacity == 0 {
+            return;
+        }
+        self.clock += 1;
+        let hash = self.hasher.hash_one(genome);
+        let bucket = self.entries.entry(hash).or_default();
+        if let Some(entry) = bucket.iter_mut().find(|entry| entry.genome == *genome) {
+            self.recency.remove(&entry.last_used);
+            entry.score = score;
+            entry.last_used = self.clock;
+        } else {
+            bucket.push(Entry {
+                genome: genome.clone(),
+                score,
+                last_used: self.clock,
+            });
+            self.len += 1;
+        }
+        self.recency.insert(self.clock, hash);
+        while self.len > capacity {
+            let Some((last_used, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.remove(oldest, last_used);
+        }
+    }
+
+    fn remove(&mut self, hash: u64, last_used: u64) {
+        let Some(bucket) = self.entries.get_mut(&hash) else {
+            return;
+        };
+        let num_entries = bucket.len();
+        bucket.retain(|entry| entry.last_used != last_used);
+        self.len -= num_entries - bucket.len();
+        if bucket.is_empty() {
+            self.entries.remove(&hash);
+        }
+    }
+}
+
+/// A scorer that caches the scores of the most recently scored genomes.
+///
+/// Scoring a genome that's already been seen (e.g., a child that's an exact
+/// copy of its parent) then doesn't require running the wrapped scorer
+/// again.
+///
+/// The cache holds at most `capacity` genomes, evicting the least recently
+/// used one when it's full. It can be shared between threads, so the same
+/// cache can be used (by reference) to score both the initial population
+/// and the children in `par_next`.
+pub struct CachingScorer<S, G, R> {
+    scorer: S,
+    capacity: usize,
+    cache: Mutex<Cache<G, R>>,
+}
+
+impl<S, G, R> CachingScorer<S, G, R> {
+    pub fn new(scorer: S, capacity: usize) -> Self {
+        Self {
+            scorer,
+            capacity,
+            cache: Mutex::new(Cache {
+                entries: HashMap::new(),
+                len: 0,
+                recency: BTreeMap::new(),
+                hasher: RandomState::new(),
+                clock: 0,
+                stats: CacheStats::default(),
+            }),
+        }
+    }
+
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The number of genomes currently in the cache.
+    pub fn len(&self) -> usize {
+        self.lock().len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The number of lookups so far that were (and weren't) found in the
+    /// cache.
+    pub fn stats(&self) -> CacheStats {
+        self.lock().stats
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Cache<G, R>> {
+        // A panic while holding the lock can't leave the cache inconsistent
+        // enough to matter, so we ignore poisoning.
+        self.cache.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<S, G> Scorer<G> for CachingScorer<S, G, S::Score>
+where
+    S: Scorer<G>,
+    S::Score: Clone,
+    G: Hash + Eq + Clone,
+{
+    type Score = S::Score;
+
+    fn score(&self, genome: &G) -> Self::Score {
+        let cached = self.lock().get(genome);
+        if let Some(score) = cached {
+            return score;
+        }
+
+        // We don't hold the lock while scoring so other threads can use the
+        // cache in the meantime. This means two threads can both score the
+        // same new genome, which is wasteful but harmless.
+        let score = self.scorer.score(genome);
+        self.lock().insert(genome, score.clone(), self.capacity);
+        score
+    }
+}
+
+// Failures aren't cached, so genomes that couldn't be scored are tried again
+// the next time they're seen.
+impl<S, G> TryScorer<G> for CachingScorer<S, G, S::Score>
+where
+    S: TryScorer<G>,
+    S::Score: Clone,
+    G: Hash + Eq + Clone,
+{
+    type Score = S::Score;
+
+    fn try_score(&self, genome: &G) -> Result<Self::Score> {
+        let cached = self.lock().get(genome);
+        if let Some(score) = cached {
+            return Ok(score);
+        }
+
+        let score = self.scorer.try_score(genome)?;
+        self.lock().insert(genome, score.clone(), self.capacity);
+        Ok(score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn repeated_genomes_are_only_scored_once() {
+        let num_calls = AtomicUsize::new(0);
+        let scorer = CachingScorer::new(
+            FnScorer(|genome: &Vec<u8>| {
+                num_calls.fetch_add(1, Ordering::Relaxed);
+                genome.len()
+            }),
+            10,
+        );
+
+        assert_eq!(scorer.score(&vec![1, 2, 3]), 3);
+        assert_eq!(scorer.score(&vec![4]), 1);
+        assert_eq!(scorer.score(&vec![1, 2, 3]), 3);
+
+        assert_eq!(num_calls.load(Ordering::Relaxed), 2);
+        assert_eq!(scorer.len(), 2);
+        assert_eq!(scorer.stats(), CacheStats { hits: 1, misses: 2 });
+        assert!((scorer.stats().hit_rate().unwrap() - 1.0 / 3.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn least_recently_used_genome_is_evicted() {
+        let scorer = CachingScorer::new(FnScorer(|genome: &u8| *genome), 2);
+
+        scorer.score(&1);
+        scorer.score(&2);
+        // Using 1 again makes 2 the least recently used.
+        scorer.score(&1);
+        scorer.score(&3);
+        assert_eq!(scorer.len(), 2);
+
+        scorer.score(&1);
+        assert_eq!(scorer.stats(), CacheStats { hits: 2, misses: 3 });
+        scorer.score(&2);
+        assert_eq!(scorer.stats(), CacheStats { hits: 2, misses: 4 });
+    }
+}
@@ -0,0 +1,398 @@
+//! An island model: several sub-populations ("islands"), each evolved
+//! by its own [`Generation`], with individuals occasionally migrating
+//! between them.
+
+use anyhow::{Context, Result};
+use rand::{prelude::SliceRandom, rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator,
+};
+
+use crate::{
+    generation::Generation,
+    operator::{selector::Selector, Operator, Prepare},
+    population::Population,
+    survival::Survival,
+};
+
+/// Which islands send their emigrants where.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// Island `i` sends its emigrants to island `i + 1`, with the last
+    /// island sending to the first.
+    Ring,
+    /// Every island sends its emigrants to every other island.
+    FullyConnected,
+    /// Every island sends its emigrants to a randomly chosen other island,
+    /// chosen anew at each migration.
+    Random,
+}
+
+/// Which individuals on the receiving island are replaced by immigrants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Replacement {
+    /// Immigrants replace the worst individuals on the receiving island.
+    Worst,
+    /// Immigrants replace randomly chosen individuals on the receiving
+    /// island.
+    Random,
+}
+
+/// A collection of islands, each with its own population, child maker,
+/// and survival strategy.
+///
+/// The islands evolve independently, except that every
+/// `migration_interval` generations copies of selected individuals
+/// migrate between them.
+pub struct Islands<P, C, S, E> {
+    generations: Vec<Generation<P, C, S>>,
+    emigrant_selector: E,
+    topology: Topology,
+    replacement: Replacement,
+    migration_interval: usize,
+    num_migrants: usize,
+    generation_number: usize,
+}
+
+impl<P, C, S, E> Islands<P, C, S, E> {
+    /// Create a new island model from the given islands, using
+    /// `emigrant_selector` to choose which individuals leave an island
+    /// when migration happens.
+    ///
+    /// By default one individual migrates along a ring every 10
+    /// generations, replacing the worst individual on the receiving
+    /// island.
+    pub const fn new(islands: Vec<Generation<P, C, S>>, emigrant_selector: E) -> Self {
+        Self {
+            generations: islands,
+            emigrant_selector,
+            topology: Topology::Ring,
+            replacement: Replacement::Worst,
+            migration_interval: 10,
+            num_migrants: 1,
+            generation_number: 0,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_replacement(mut self, replacement: Replacement) -> Self {
+        self.replacement = replacement;
+        self
+    }
+
+    /// Migrate every `migration_interval` generations. An interval of 0
+    /// turns migration off.
+    #[must_use]
+    pub const fn with_migration_interval(mut self, migration_interval: usize) -> Self {
+        self.migration_interval = migration_interval;
+        self
+    }
+
+    /// The number of individuals each island sends at each migration.
+    ///
+    /// If more immigrants arrive at an island than it has individuals (e.g.,
+    /// with a [fully connected](Topology::FullyConnected) topology and many
+    /// islands), a random subset of them that fits is kept.
+    #[must_use]
+    pub const fn with_num_migrants(mut self, num_migrants: usize) -> Self {
+        self.num_migrants = num_migrants;
+        self
+    }
+
+    pub fn islands(&self) -> &[Generation<P, C, S>] {
+        &self.generations
+    }
+
+    /// The number of generations that have been made so far.
+    pub const fn generation_number(&self) -> usize {
+        self.generation_number
+    }
+
+    const fn migration_due(&self) -> bool {
+        self.migration_interval > 0
+            && self
+                .generation_number
+                .is_multiple_of(self.migration_interval)
+    }
+
+    fn destinations<R: Rng + ?Sized>(&self, source: usize, rng: &mut R) -> Vec<usize> {
+        let num_islands = self.generations.len();
+        match self.topology {
+            Topology::Ring => vec![(source + 1) % num_islands],
+            Topology::FullyConnected => (0..num_islands).filter(|&i| i != source).collect(),
+            Topology::Random => {
+                // Choose from all the islands except `source` by skipping over it.
+                let destination = rng.gen_range(0..num_islands - 1);
+                vec![if destination >= source {
+                    destination + 1
+                } else {
+                    destination
+                }]
+            }
+        }
+    }
+}
+
+impl<P, C, S, E> Islands<P, C, S, E>
+where
+    P: Population + AsMut<[P::Individual]>,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Ord + Clone,
+    E: Selector<P>,
+{
+    /// Move copies of selected individuals between the islands, as
+    /// determined by the topology and replacement policy.
+    ///
+    /// # Errors
+    /// This will return an error if selecting any emigrant fails.
+    pub fn migrate<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Result<()> {
+        if self.generations.len() < 2 || self.num_migrants == 0 {
+            return Ok(());
+        }
+        let mut immigrants: Vec<Vec<P::Individual>> = vec![Vec::new(); self.generations.len()];
+        for source in 0..self.generations.len() {
+            let emigrants = (0..self.num_migrants)
+                .map(|_| {
+                    self.emigrant_selector
+                        .select(self.generations[source].population(), rng)
+                        .cloned()
+                })
+                .collect::<Result<Vec<_>>>()
+                .with_context(|| format!("Selecting emigrants from island {source} failed"))?;
+            for destination in self.destinations(source, rng) {
+                immigrants[destination].extend(emigrants.iter().cloned());
+            }
+        }
+
+        for (island, mut arrivals) in self.generations.iter_mut().zip(immigrants) {
+            let individuals = island.population_mut().as_mut();
+            // The arrivals are in the order of their source islands, so we
+            // can't just take the first ones that fit without favoring the
+            // lower numbered sources.
+            if arrivals.len() > individuals.len() {
+                arrivals.shuffle(rng);
+                arrivals.truncate(individuals.len());
+            }
+            let mut indices: Vec<usize> = (0..individuals.len()).collect();
+            match self.replacement {
+                Replacement::Worst => indices.sort_by_key(|&i| &individuals[i]),
+                Replacement::Random => indices.shuffle(rng),
+            }
+            for (index, immigrant) in indices.into_iter().zip(arrivals) {
+                individuals[index] = immigrant;
+            }
+        }
+        Ok(())
+    }
+
+    /// The best individual across all the islands.
+    ///
+    /// # Errors
+    /// This will return an error if all the islands are empty.
+    pub fn best(&self) -> Result<&P::Individual> {
+        self.generations
+            .iter()
+            .filter_map(|island| island.population().into_iter().max())
+            .max()
+            .context("All the islands were empty")
+    }
+}
+
+impl<P, C, S, E> Islands<P, C, S, E>
+where
+    P: Population + AsMut<[P::Individual]>,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Ord + Clone,
+    C: Prepare<P>,
+    C::Prepared: for<'a> Operator<&'a P, Output = P::Individual>,
+    S: Survival<P>,
+    E: Selector<P>,
+{
+    /// Make the next generation on every island, one island after another,
+    /// and then migrate if it's time to.
+    ///
+    /// # Errors
+    /// This returns an error if making the next generation on any island
+    /// fails, or if migration fails.
+    pub fn serial_next(&mut self, seed: u64) -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let seeds: Vec<u64> = self.generations.iter().map(|_| rng.gen()).collect();
+        for (island, seed) in self.generations.iter_mut().zip(seeds) {
+            island.serial_next(seed)?;
+        }
+        self.finish_generation(&mut rng)
+    }
+
+    fn finish_generation<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Result<()> {
+        self.generation_number += 1;
+        if self.migration_due() {
+            self.migrate(rng)?;
+        }
+        Ok(())
+    }
+}
+
+impl<P, C, S, E> Islands<P, C, S, E>
+where
+    P: Population + AsMut<[P::Individual]> + Send + Sync,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Ord + Clone + Send,
+    C: Prepare<P> + Send + Sync,
+    C::Prepared: for<'a> Operator<&'a P, Output = P::Individual>,
+    S: Survival<P> + Send,
+    E: Selector<P>,
+{
+    /// Make the next generation on every island, with the islands
+    /// evolving in parallel on Rayon threads, and then migrate if it's
+    /// time to.
+    ///
+    /// Each island makes its generation serially, so this gives the
+    /// same result as `serial_next` for the same `seed`.
+    ///
+    /// # Errors
+    /// This returns an error if making the next generation on any island
+    /// fails, or if migration fails.
+    pub fn par_next(&mut self, seed: u64) -> Result<()> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let seeds: Vec<u64> = self.generations.iter().map(|_| rng.gen()).collect();
+        self.generations
+            .par_iter_mut()
+            .zip(seeds.into_par_iter())
+            .try_for_each(|(island, seed)| island.serial_next(seed))?;
+        self.finish_generation(&mut rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator::{
+        selector::{best::Best, random::Random, Select},
+        Composable,
+    };
+
+    #[derive(Clone, Copy)]
+    struct Same;
+
+    impl Operator<&i32> for Same {
+        type Output = i32;
+
+        fn apply<R: Rng + ?Sized>(&self, input: &i32, _: &mut R) -> Result<Self::Output> {
+            Ok(*input)
+        }
+    }
+    impl Composable for Same {}
+
+    fn island(
+        population: Vec<i32>,
+    ) -> Generation<
+        Vec<i32>,
+        impl Prepare<Vec<i32>, Prepared: for<'a> Operator<&'a Vec<i32>, Output = i32> + Send + Sync>,
+    > {
+        Generation::new(Select::new(Best).then(Same), population)
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn ring_migration_replaces_worst() {
+        let mut islands = Islands::new(vec![island(vec![1, 2, 3]), island(vec![4, 5, 6])], Best)
+            .with_migration_interval(1);
+        islands.serial_next(0).unwrap();
+        // After one generation every island is full of copies of its best,
+        // and then the best of each island replaces the worst of the next.
+        assert_eq!(&vec![6, 3, 3], islands.islands()[0].population());
+        assert_eq!(&vec![3, 6, 6], islands.islands()[1].population());
+        assert_eq!(&6, islands.best().unwrap());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn fully_connected_sends_to_every_other_island() {
+        let mut islands = Islands::new(
+            vec![
+                island(vec![1, 1, 1]),
+                island(vec![2, 2, 2]),
+                island(vec![3, 3, 3]),
+            ],
+            Best,
+        )
+        .with_topology(Topology::FullyConnected);
+        islands.migrate(&mut rand::thread_rng()).unwrap();
+        let mut first = islands.islands()[0].population().clone();
+        first.sort_unstable();
+        assert_eq!(vec![1, 2, 3], first);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn excess_immigrants_are_chosen_fairly() {
+        // Island 0 receives 4 immigrants but only has room for 2.
+        let mut rng = rand::thread_rng();
+        let mut num_from_last = 0;
+        for _ in 0..100 {
+            let mut islands = Islands::new(
+                vec![island(vec![0, 0]), island(vec![1, 1]), island(vec![2, 2])],
+                Best,
+            )
+            .with_topology(Topology::FullyConnected)
+            .with_num_migrants(2);
+            islands.migrate(&mut rng).unwrap();
+            let first = islands.islands()[0].population();
+            assert!(first.iter().all(|&individual| individual != 0));
+            num_from_last += first.iter().filter(|&&individual| individual == 2).count();
+        }
+        // Half the immigrants that are kept should come from the last island.
+        assert!((70..130).contains(&num_from_last), "{num_from_last}");
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn random_topology_never_sends_to_itself() {
+        let islands = Islands::new(
+            vec![island(vec![1]), island(vec![2]), island(vec![3])],
+            Random,
+        )
+        .with_topology(Topology::Random);
+        let mut rng = rand::thread_rng();
+        for source in 0..3 {
+            for _ in 0..20 {
+                let destinations = islands.destinations(source, &mut rng);
+                assert_eq!(1, destinations.len());
+                assert_ne!(source, destinations[0]);
+            }
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn serial_and_parallel_agree() {
+        let make_islands = || {
+            Islands::new(
+                vec![
+                    island(vec![1, 2, 3]),
+                    island(vec![4, 5, 6]),
+                    island(vec![7, 8, 9]),
+                ],
+                Random,
+            )
+            .with_topology(Topology::Random)
+            .with_replacement(Replacement::Random)
+            .with_migration_interval(2)
+        };
+        let mut serial = make_islands();
+        let mut parallel = make_islands();
+        for seed in 0..6 {
+            serial.serial_next(seed).unwrap();
+            parallel.par_next(seed).unwrap();
+        }
+        for (s, p) in serial.islands().iter().zip(parallel.islands()) {
+            assert_eq!(s.population(), p.population());
+        }
+    }
+}
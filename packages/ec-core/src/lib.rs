@@ -1,8 +1,23 @@
+pub mod alps;
+#[cfg(feature = "serde")]
+pub mod checkpoint;
 pub mod child_maker;
+#[cfg(feature = "config")]
+pub mod config;
 pub mod distributions;
+pub mod down_sample;
 pub mod generation;
 pub mod genome;
+pub mod hall_of_fame;
 pub mod individual;
+pub mod island;
+pub mod lineage;
+pub mod map_elites;
+pub mod novelty;
 pub mod operator;
+pub mod pareto;
 pub mod population;
+pub mod run;
+pub mod statistics;
+pub mod survival;
 pub mod test_results;
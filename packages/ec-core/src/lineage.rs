@@ -0,0 +1,546 @@
+//! Tracking where individuals came from.
+//!
+//! A [`LineageLog`] gives each individual an [`IndividualId`] and records
+//! the IDs of its parents and the operators used to make it:
+//!   - the initial population is recorded with [`LineageLog::record_initial`];
+//!   - children are recorded by wrapping the part of the child maker that turns
+//!     the selected parents into a child in [`RecordLineage`], e.g.,
+//!     `Select::new(selector).apply_twice().then(RecordLineage::new(
+//!     Map::new(GenomeExtractor).then(Recombine::new(xo)).then(Mutate::new(
+//!     mutator)).wrap::<GenomeScorer<_, _>>(scorer), &log))`.
+//!
+//! The operators in a child's record are the fixed labels given to
+//! [`RecordLineage::with_operators`], followed by the names of any operators
+//! wrapped in [`Traced`] that were actually applied while making that child
+//! (e.g., a mutation that only happens with some probability).
+//!
+//! Any individual's [`Ancestry`] can then be traced back to the initial
+//! population, and exported as a Graphviz DOT or `GraphML` graph.
+//!
+//! The log keeps a record for every individual ever made unless it's
+//! [pruned](LineageLog::prune), e.g., after each generation, to just the
+//! ancestors of the current population.
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        PoisonError, RwLock,
+    },
+};
+
+use anyhow::{Context, Result};
+use rand::Rng;
+
+use crate::{
+    individual::{Identified, IndividualId},
+    operator::{Composable, Describe, Description, Operator},
+};
+
+/// How one individual was made.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineageRecord {
+    pub id: IndividualId,
+    /// The IDs of the parents, which is empty for the initial population.
+    pub parents: Vec<IndividualId>,
+    /// The names of the operators that were applied to the parents to make
+    /// this individual, in order.
+    pub operators: Vec<String>,
+}
+
+/// A record of how every individual was made, which can be shared (by
+/// reference) between any number of [`RecordLineage`] operators.
+#[derive(Debug, Default)]
+pub struct LineageLog {
+    next_id: AtomicU64,
+    records: RwLock<HashMap<IndividualId, LineageRecord>>,
+}
+
+impl LineageLog {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of individuals in the log.
+    pub fn len(&self) -> usize {
+        self.read(HashMap::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[must_use]
+    pub fn get(&self, id: IndividualId) -> Option<LineageRecord> {
+        self.read(|records| records.get(&id).cloned())
+    }
+
+    /// Give each of `individuals` an ID, and record them as having no
+    /// parents.
+    pub fn record_initial<'a, I>(&self, individuals: impl IntoIterator<Item = &'a mut I>)
+    where
+        I: Identified + 'a,
+    {
+        for individual in individuals {
+            individual.set_id(self.record(Vec::new(), Vec::new()));
+        }
+    }
+
+    /// Record a new individual with the given parents and operators,
+    /// returning its ID.
+    pub fn record(&self, parents: Vec<IndividualId>, operators: Vec<String>) -> IndividualId {
+        let id = IndividualId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        self.records
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                id,
+                LineageRecord {
+                    id,
+                    parents,
+                    operators,
+                },
+            );
+        id
+    }
+
+    /// The individual `id` along with all of its ancestors.
+    ///
+    /// # Errors
+    /// This returns an error if `id` isn't in the log. Ancestors that have
+    /// been [pruned](Self::prune) are left out.
+    pub fn ancestry(&self, id: IndividualId) -> Result<Ancestry> {
+        self.read(|records| {
+            let record = records
+                .get(&id)
+                .with_context(|| format!("Individual {id} isn't in the lineage log"))?;
+            let mut ancestry = BTreeMap::new();
+            let mut to_visit = vec![record];
+            while let Some(record) = to_visit.pop() {
+                if ancestry.insert(record.id, record.clone()).is_none() {
+                    to_visit.extend(
+                        record
+                            .parents
+                            .iter()
+                            .filter_map(|parent| records.get(parent)),
+                    );
+                }
+            }
+            Ok(Ancestry {
+                records: ancestry.into_values().collect(),
+            })
+        })
+    }
+
+    /// Remove the records of every individual that isn't one of `living`
+    /// or one of their ancestors.
+    pub fn prune(&self, living: impl IntoIterator<Item = IndividualId>) {
+        let mut records = self.records.write().unwrap_or_else(PoisonError::into_inner);
+        let mut keep = HashSet::new();
+        let mut to_visit: Vec<_> = living.into_iter().collect();
+        while let Some(id) = to_visit.pop() {
+            if keep.insert(id) {
+                if let Some(record) = records.get(&id) {
+                    to_visit.extend(&record.parents);
+                }
+            }
+        }
+        records.retain(|id, _| keep.contains(id));
+        drop(records);
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&HashMap<IndividualId, LineageRecord>) -> T) -> T {
+        f(&self.records.read().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// An individual and its ancestors, ordered by ID (so parents come before
+/// their children).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ancestry {
+    pub records: Vec<LineageRecord>,
+}
+
+impl Ancestry {
+    /// The ancestry as a Graphviz DOT graph, with an edge from each parent
+    /// to each of its children.
+    #[must_use]
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph ancestry {\n");
+        for record in &self.records {
+            let label = if record.operators.is_empty() {
+                record.id.to_string()
+            } else {
+                format!(
+                    "{}\\n{}",
+                    record.id,
+                    escape_dot(&record.operators.join(", "))
+                )
+            };
+            // Writing to a `String` can't fail.
+            let _ = writeln!(dot, "  {} [label=\"{label}\"];", record.id);
+            for parent in self.parents_in_graph(record) {
+                let _ = writeln!(dot, "  {parent} -> {};", record.id);
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The ancestry as a `GraphML` graph, with an edge from each parent to
+    /// each of its children, and the operators as a node attribute.
+    #[must_use]
+    pub fn to_graphml(&self) -> String {
+        let mut graphml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml \
+             xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n  <key id=\"operators\" \
+             for=\"node\" attr.name=\"operators\" attr.type=\"string\"/>\n  <graph \
+             id=\"ancestry\" edgedefault=\"directed\">\n",
+        );
+        for record in &self.records {
+            // Writing to a `String` can't fail.
+            let _ = writeln!(
+                graphml,
+                "    <node id=\"n{}\"><data key=\"operators\">{}</data></node>",
+                record.id,
+                escape_xml(&record.operators.join(", "))
+            );
+            for parent in self.parents_in_graph(record) {
+                let _ = writeln!(
+                    graphml,
+                    "    <edge source=\"n{parent}\" target=\"n{}\"/>",
+                    record.id
+                );
+            }
+        }
+        graphml.push_str("  </graph>\n</graphml>\n");
+        graphml
+    }
+
+    // Edges to pruned parents would add nodes that aren't in the graph.
+    fn parents_in_graph<'a>(
+        &'a self,
+        record: &'a LineageRecord,
+    ) -> impl Iterator<Item = IndividualId> + 'a {
+        record.parents.iter().copied().filter(|parent| {
+            self.records
+                .binary_search_by_key(parent, |record| record.id)
+                .is_ok()
+        })
+    }
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// An operator that turns one or more parents into a child using another
+/// operator, and then records the child (and its parents) in a
+/// [`LineageLog`], giving it a new ID.
+///
+/// Parents without IDs (e.g., initial individuals that weren't recorded)
+/// are left out of the child's record.
+///
+/// The operators in the child's record are the labels from
+/// [`with_operators`](Self::with_operators), which are the same for every
+/// child, followed by the names of the [`Traced`] operators in `make_child`
+/// that were applied to make this particular child.
+#[derive(Clone)]
+pub struct RecordLineage<'a, C> {
+    make_child: C,
+    log: &'a LineageLog,
+    operators: Vec<String>,
+}
+
+impl<'a, C> RecordLineage<'a, C> {
+    pub const fn new(make_child: C, log: &'a LineageLog) -> Self {
+        Self {
+            make_child,
+            log,
+            operators: Vec::new(),
+        }
+    }
+
+    /// Record `operators` as the operators that `make_child` applies, e.g.,
+    /// `["two_point_xo", "umad"]`.
+    ///
+    /// These labels are static: they're recorded for every child, whether or
+    /// not `make_child` actually applied those operators to make it. Wrap
+    /// operators that aren't always applied in [`Traced`] instead.
+    #[must_use]
+    pub fn with_operators(
+        mut self,
+        operators: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        self.operators = operators.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Make a child with `make_child`, collecting the names of the
+    /// [`Traced`] operators it applies, and then record it.
+    fn record<I: Identified>(
+        &self,
+        parents: Vec<IndividualId>,
+        make_child: impl FnOnce() -> Result<I>,
+    ) -> Result<I> {
+        // Saving and restoring the enclosing trace means that a nested
+        // `RecordLineage` only records the operators it applies itself.
+        let enclosing = TRACE.with(|trace| trace.replace(Some(Vec::new())));
+        let child = make_child();
+        let traced = TRACE
+            .with(|trace| trace.replace(enclosing))
+            .unwrap_or_default();
+        let mut child = child?;
+        let mut operators = self.operators.clone();
+        operators.extend(traced);
+        child.set_id(self.log.record(parents, operators));
+        Ok(child)
+    }
+}
+
+impl<'i, I, C> Operator<&'i I> for RecordLineage<'_, C>
+where
+    I: Identified,
+    C: Operator<&'i I>,
+    C::Output: Identified,
+{
+    type Output = C::Output;
+
+    fn apply<R: Rng + ?Sized>(&self, parent: &'i I, rng: &mut R) -> Result<Self::Output> {
+        let parents = parent.id().into_iter().collect();
+        self.record(parents, || self.make_child.apply(parent, rng))
+    }
+}
+
+impl<'i, I, C, const N: usize> Operator<[&'i I; N]> for RecordLineage<'_, C>
+where
+    I: Identified,
+    C: Operator<[&'i I; N]>,
+    C::Output: Identified,
+{
+    type Output = C::Output;
+
+    fn apply<R: Rng + ?Sized>(&self, parents: [&'i I; N], rng: &mut R) -> Result<Self::Output> {
+        let parent_ids = parents.iter().filter_map(|parent| parent.id()).collect();
+        self.record(parent_ids, || self.make_child.apply(parents, rng))
+    }
+}
+
+impl<C> Composable for RecordLineage<'_, C> {}
+
+impl<C> Describe for RecordLineage<'_, C>
+where
+    C: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("RecordLineage").with_child(self.make_child.describe())
+    }
+}
+
+thread_local! {
+    /// The names of the [`Traced`] operators applied so far to the child
+    /// that a [`RecordLineage`] is making on this thread, if any.
+    static TRACE: RefCell<Option<Vec<String>>> = const { RefCell::new(None) };
+}
+
+/// An operator that adds its (described) name to the lineage record of the
+/// child being made each time it's applied.
+///
+/// This only has an effect inside a [`RecordLineage`]; otherwise it just
+/// applies the wrapped operator.
+#[derive(Clone)]
+pub struct Traced<O> {
+    operator: O,
+    name: String,
+}
+
+impl<O: Describe> Traced<O> {
+    pub fn new(operator: O) -> Self {
+        let name = operator.describe().name;
+        Self { operator, name }
+    }
+}
+
+impl<I, O> Operator<I> for Traced<O>
+where
+    O: Operator<I>,
+{
+    type Output = O::Output;
+
+    fn apply<R: Rng + ?Sized>(&self, input: I, rng: &mut R) -> Result<Self::Output> {
+        let output = self.operator.apply(input, rng)?;
+        TRACE.with(|trace| {
+            if let Some(trace) = trace.borrow_mut().as_mut() {
+                trace.push(self.name.clone());
+            }
+        });
+        Ok(output)
+    }
+}
+
+impl<O> Composable for Traced<O> {}
+
+impl<O: Describe> Describe for Traced<O> {
+    fn describe(&self) -> Description {
+        self.operator.describe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::individual::ec::EcIndividual;
+
+    type Individual = EcIndividual<i32, i32>;
+
+    // Adds the genomes of the parents.
+    struct Sum;
+
+    impl<const N: usize> Operator<[&Individual; N]> for Sum {
+        type Output = Individual;
+
+        fn apply<R: Rng + ?Sized>(
+            &self,
+            parents: [&Individual; N],
+            _: &mut R,
+        ) -> Result<Self::Output> {
+            let genome = parents.iter().map(|parent| parent.genome).sum();
+            Ok(EcIndividual::new(genome, genome))
+        }
+    }
+    impl Composable for Sum {}
+
+    struct AddOne;
+
+    impl Operator<Individual> for AddOne {
+        type Output = Individual;
+
+        fn apply<R: Rng + ?Sized>(&self, input: Individual, _: &mut R) -> Result<Self::Output> {
+            Ok(EcIndividual::new(input.genome + 1, input.test_results))
+        }
+    }
+    impl Composable for AddOne {}
+
+    impl Describe for AddOne {
+        fn describe(&self) -> Description {
+            Description::new("AddOne")
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn ancestry_goes_back_to_the_initial_population() {
+        let log = LineageLog::new();
+        let mut population: Vec<Individual> = (1..=3).map(|n| EcIndividual::new(n, n)).collect();
+        log.record_initial(&mut population);
+        let [a, b, c] = [&population[0], &population[1], &population[2]];
+
+        let sum = RecordLineage::new(Sum, &log).with_operators(["sum"]);
+        let mut rng = thread_rng();
+        let ab = sum.apply([a, b], &mut rng).unwrap();
+        let abab = sum.apply([&ab, &ab], &mut rng).unwrap();
+        let ababc = sum.apply([&abab, c], &mut rng).unwrap();
+
+        assert_eq!(ababc.genome, 9);
+        assert_eq!(log.len(), 6);
+        assert_eq!(
+            log.get(abab.id.unwrap()).unwrap(),
+            LineageRecord {
+                id: abab.id.unwrap(),
+                parents: vec![ab.id.unwrap(), ab.id.unwrap()],
+                operators: vec!["sum".to_string()],
+            }
+        );
+
+        let ancestry = log.ancestry(abab.id.unwrap()).unwrap();
+        let ids: Vec<_> = ancestry.records.iter().map(|record| record.id).collect();
+        assert_eq!(
+            ids,
+            vec![
+                a.id.unwrap(),
+                b.id.unwrap(),
+                ab.id.unwrap(),
+                abab.id.unwrap()
+            ]
+        );
+        assert!(log.ancestry(IndividualId(100)).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn prune_keeps_only_the_ancestors_of_the_living() {
+        let log = LineageLog::new();
+        let mut population: Vec<Individual> = (1..=3).map(|n| EcIndividual::new(n, n)).collect();
+        log.record_initial(&mut population);
+
+        let child = RecordLineage::new(Sum, &log)
+            .apply([&population[0], &population[1]], &mut thread_rng())
+            .unwrap();
+        log.prune([child.id.unwrap()]);
+
+        assert_eq!(log.len(), 3);
+        assert!(log.get(population[2].id.unwrap()).is_none());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn only_applied_operators_are_traced() {
+        let log = LineageLog::new();
+        let mut population: Vec<Individual> = (1..=2).map(|n| EcIndividual::new(n, n)).collect();
+        log.record_initial(&mut population);
+
+        let make_child = RecordLineage::new(
+            Sum.then(Traced::new(AddOne).with_probability(0.0))
+                .then(Traced::new(AddOne)),
+            &log,
+        )
+        .with_operators(["sum"]);
+        let child = make_child
+            .apply([&population[0], &population[1]], &mut thread_rng())
+            .unwrap();
+
+        assert_eq!(child.genome, 4);
+        assert_eq!(
+            log.get(child.id.unwrap()).unwrap().operators,
+            vec!["sum".to_string(), "AddOne".to_string()]
+        );
+        // Nothing is traced outside of a `RecordLineage`.
+        Traced::new(AddOne)
+            .apply(population[0].clone(), &mut thread_rng())
+            .unwrap();
+        assert!(TRACE.with(|trace| trace.borrow().is_none()));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn exports_dot_and_graphml() {
+        let log = LineageLog::new();
+        let parent = log.record(Vec::new(), Vec::new());
+        let child = log.record(vec![parent], vec!["a \"quoted\" <op>".to_string()]);
+        let ancestry = log.ancestry(child).unwrap();
+
+        assert_eq!(
+            ancestry.to_dot(),
+            "digraph ancestry {\n  0 [label=\"0\"];\n  1 [label=\"1\\na \\\"quoted\\\" \
+             <op>\"];\n  0 -> 1;\n}\n"
+        );
+        let graphml = ancestry.to_graphml();
+        assert!(graphml.contains(
+            "<node id=\"n1\"><data key=\"operators\">a &quot;quoted&quot; &lt;op&gt;</data></node>"
+        ));
+        assert!(graphml.contains("<edge source=\"n0\" target=\"n1\"/>"));
+    }
+}
@@ -0,0 +1,479 @@
+//! MAP-Elites (Mouret and Clune), which looks for the best individual in
+//! each cell of a grid of behavioral features rather than a single best
+//! individual.
+//!
+//! The pieces are:
+//!   - [`Features`], which maps an individual to its (discretized) cell in the
+//!     grid;
+//!   - a [`GridArchive`], which holds the elite of each occupied cell, and
+//!     which is also a [`Population`] so that the usual operator pipelines can
+//!     select from it;
+//!   - [`MapElites`], which each iteration makes a batch of children from the
+//!     elites (e.g., with `Select::new(Random)`, `GenomeExtractor`, `Mutate`,
+//!     and `GenomeScorer`) and adds them to the archive.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+use anyhow::{ensure, Context, Result};
+use itertools::Itertools;
+use num_traits::ToPrimitive;
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::prelude::{IntoParallelIterator, ParallelIterator};
+
+use crate::{
+    generation::child_seeds,
+    individual::Individual,
+    operator::{Operator, Prepare},
+    population::Population,
+    test_results::{Fitness, TestResults},
+};
+
+pub trait Features<I> {
+    /// Take a reference to an individual and return the coordinates of its
+    /// cell in the grid, one per feature.
+    fn cell(&self, individual: &I) -> Vec<usize>;
+}
+
+#[derive(Clone, Copy)]
+pub struct FnFeatures<T>(pub T);
+
+impl<I, T> Features<I> for FnFeatures<T>
+where
+    T: Fn(&I) -> Vec<usize>,
+{
+    fn cell(&self, individual: &I) -> Vec<usize> {
+        self.0(individual)
+    }
+}
+
+impl<I, T> Features<I> for &T
+where
+    T: Features<I>,
+{
+    fn cell(&self, individual: &I) -> Vec<usize> {
+        (**self).cell(individual)
+    }
+}
+
+/// Discretize a feature value into one of `num_bins` equal-width bins
+/// covering `min..=max`. Values outside that range go in the first or last
+/// bin, as do `NaN`s.
+#[must_use]
+pub fn bin(value: f64, min: f64, max: f64, num_bins: usize) -> usize {
+    let last_bin = num_bins.saturating_sub(1);
+    let position = (value - min) / (max - min) * num_bins.to_f64().unwrap_or(f64::NAN);
+    position.floor().to_usize().map_or_else(
+        || if position > 0.0 { last_bin } else { 0 },
+        |bin| bin.min(last_bin),
+    )
+}
+
+/// A grid of cells, each of which holds at most one individual: the best
+/// (according to `Ord`) individual found so far whose features put it in
+/// that cell.
+#[derive(Debug, Clone)]
+pub struct GridArchive<I> {
+    dimensions: Vec<usize>,
+    elites: Vec<I>,
+    // The index in `elites` of the elite in each occupied cell.
+    cells: HashMap<Vec<usize>, usize>,
+}
+
+impl<I> GridArchive<I> {
+    /// Create an empty archive whose grid has `dimensions[i]` bins for
+    /// feature `i`.
+    #[must_use]
+    pub fn new(dimensions: Vec<usize>) -> Self {
+        Self {
+            dimensions,
+            elites: Vec::new(),
+            cells: HashMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn dimensions(&self) -> &[usize] {
+        &self.dimensions
+    }
+
+    /// The total number of cells in the grid, occupied or not.
+    #[must_use]
+    pub fn num_cells(&self) -> usize {
+        self.dimensions.iter().product()
+    }
+
+    /// The fraction of the cells that are occupied.
+    #[must_use]
+    pub fn coverage(&self) -> f64 {
+        self.elites.len().to_f64().unwrap_or(f64::NAN)
+            / self.num_cells().to_f64().unwrap_or(f64::NAN)
+    }
+
+    /// The elites in all the occupied cells, in no particular order.
+    #[must_use]
+    pub fn elites(&self) -> &[I] {
+        &self.elites
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, I> {
+        self.elites.iter()
+    }
+
+    #[must_use]
+    pub fn get(&self, cell: &[usize]) -> Option<&I> {
+        self.cells.get(cell).map(|&index| &self.elites[index])
+    }
+
+    /// The occupied cells and their elites, in no particular order.
+    pub fn cells(&self) -> impl Iterator<Item = (&[usize], &I)> {
+        self.cells
+            .iter()
+            .map(|(cell, &index)| (cell.as_slice(), &self.elites[index]))
+    }
+}
+
+impl<I: Ord> GridArchive<I> {
+    /// Put `individual` in `cell` if that cell is empty or if `individual`
+    /// is better than the current elite there. This returns whether the
+    /// individual was added.
+    ///
+    /// # Errors
+    /// This returns an error if `cell` isn't in the grid.
+    pub fn insert(&mut self, cell: Vec<usize>, individual: I) -> Result<bool> {
+        ensure!(
+            cell.len() == self.dimensions.len()
+                && cell.iter().zip(&self.dimensions).all(|(c, d)| c < d),
+            "The cell {cell:?} isn't in a grid with dimensions {:?}",
+            self.dimensions
+        );
+        if let Some(&index) = self.cells.get(&cell) {
+            if individual > self.elites[index] {
+                self.elites[index] = individual;
+                return Ok(true);
+            }
+            return Ok(false);
+        }
+        self.cells.insert(cell, self.elites.len());
+        self.elites.push(individual);
+        Ok(true)
+    }
+}
+
+impl<I, T> GridArchive<I>
+where
+    I: Individual<TestResults = TestResults<T>>,
+    T: Fitness,
+{
+    /// The QD-score: the sum of the [`Fitness`] of the total results of all
+    /// the elites.
+    ///
+    /// # Errors
+    /// This returns an error if any elite's total result doesn't have a
+    /// valid fitness.
+    pub fn qd_score(&self) -> Result<f64> {
+        self.elites
+            .iter()
+            .map(|elite| {
+                elite
+                    .test_results()
+                    .total_result
+                    .fitness()
+                    .context("An elite's total result didn't have a valid fitness")
+            })
+            .sum()
+    }
+}
+
+impl<I> Population for GridArchive<I> {
+    type Individual = I;
+
+    fn size(&self) -> usize {
+        self.elites.len()
+    }
+}
+
+impl<I> AsRef<[I]> for GridArchive<I> {
+    fn as_ref(&self) -> &[I] {
+        &self.elites
+    }
+}
+
+impl<'a, I> IntoIterator for &'a GridArchive<I> {
+    type Item = &'a I;
+    type IntoIter = std::slice::Iter<'a, I>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// What happened in one iteration of [`MapElites`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IterationReport {
+    pub iteration: usize,
+    /// The number of children that became the elite of their cell.
+    pub num_inserted: usize,
+    pub num_elites: usize,
+    pub coverage: f64,
+    pub qd_score: f64,
+}
+
+impl Display for IterationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "  elites: {} ({:.1}% coverage), {} new this iteration, QD-score {:.3}",
+            self.num_elites,
+            100.0 * self.coverage,
+            self.num_inserted,
+            self.qd_score
+        )
+    }
+}
+
+/// The MAP-Elites loop: each iteration `child_maker` makes `batch_size`
+/// children from the elites in the archive, and each child replaces the
+/// elite of its cell if it's better.
+///
+/// The child maker is [prepared](Prepare) once per iteration, like in
+/// [`Generation`](crate::generation::Generation), so it's typically
+/// something like `Select::new(Random).then(GenomeExtractor).then(...)`.
+pub struct MapElites<I, C, F> {
+    archive: GridArchive<I>,
+    child_maker: C,
+    features: F,
+    batch_size: usize,
+    iteration: usize,
+}
+
+impl<I, C, F> MapElites<I, C, F> {
+    pub const fn new(
+        child_maker: C,
+        features: F,
+        archive: GridArchive<I>,
+        batch_size: usize,
+    ) -> Self {
+        Self {
+            archive,
+            child_maker,
+            features,
+            batch_size,
+            iteration: 0,
+        }
+    }
+
+    pub const fn archive(&self) -> &GridArchive<I> {
+        &self.archive
+    }
+
+    /// The number of iterations that have been run.
+    pub const fn iteration(&self) -> usize {
+        self.iteration
+    }
+}
+
+impl<I, C, F> MapElites<I, C, F>
+where
+    I: Ord,
+    F: Features<I>,
+{
+    /// Add each of the given individuals (e.g., a randomly generated
+    /// initial population) to the cell given by its features, if it's
+    /// better than the elite there. This returns the number of individuals
+    /// that were added.
+    ///
+    /// # Errors
+    /// This returns an error if any individual's features aren't in the
+    /// grid.
+    pub fn insert_all(&mut self, individuals: impl IntoIterator<Item = I>) -> Result<usize> {
+        individuals
+            .into_iter()
+            .try_fold(0, |num_inserted, individual| {
+                let cell = self.features.cell(&individual);
+                Ok(num_inserted + usize::from(self.archive.insert(cell, individual)?))
+            })
+    }
+}
+
+impl<I, C, F, T> MapElites<I, C, F>
+where
+    I: Individual<TestResults = TestResults<T>> + Ord,
+    T: Fitness,
+    F: Features<I>,
+{
+    fn finish_iteration(&mut self, children: Vec<I>) -> Result<IterationReport> {
+        let num_inserted = self.insert_all(children)?;
+        self.iteration += 1;
+        Ok(IterationReport {
+            iteration: self.iteration,
+            num_inserted,
+            num_elites: self.archive.size(),
+            coverage: self.archive.coverage(),
+            qd_score: self.archive.qd_score()?,
+        })
+    }
+}
+
+impl<I, C, F, T> MapElites<I, C, F>
+where
+    I: Individual<TestResults = TestResults<T>> + Ord + Send + Sync,
+    T: Fitness,
+    C: Prepare<GridArchive<I>>,
+    C::Prepared: for<'a> Operator<&'a GridArchive<I>, Output = I> + Send + Sync,
+    F: Features<I>,
+{
+    /// Run one iteration, making the children using a Rayon parallel
+    /// iterator.
+    ///
+    /// As with [`Generation`](crate::generation::Generation), the `seed`
+    /// completely determines the random choices made, so this makes the
+    /// same children as `serial_next` given the same seed.
+    ///
+    /// # Errors
+    /// This returns an error if the archive is empty, or if preparing the
+    /// child maker, making the children, or adding them to the archive
+    /// fails.
+    pub fn par_next(&mut self, seed: u64) -> Result<IterationReport> {
+        ensure!(
+            !self.archive.is_empty(),
+            "There are no elites in the archive to make children from"
+        );
+        let mut rng = StdRng::seed_from_u64(seed);
+        let child_maker = self.child_maker.prepare(&self.archive)?;
+        let children = child_seeds(&mut rng, self.batch_size)
+            .into_par_iter()
+            .map(|child_seed| {
+                let mut rng = StdRng::seed_from_u64(child_seed);
+                child_maker.apply(&self.archive, &mut rng)
+            })
+            .collect::<Result<_>>()?;
+        self.finish_iteration(children)
+    }
+}
+
+impl<I, C, F, T> MapElites<I, C, F>
+where
+    I: Individual<TestResults = TestResults<T>> + Ord,
+    T: Fitness,
+    C: Prepare<GridArchive<I>>,
+    C::Prepared: for<'a> Operator<&'a GridArchive<I>, Output = I>,
+    F: Features<I>,
+{
+    /// Run one iteration, making the children serially.
+    ///
+    /// # Errors
+    /// This returns an error if the archive is empty, or if preparing the
+    /// child maker, making the children, or adding them to the archive
+    /// fails.
+    pub fn serial_next(&mut self, seed: u64) -> Result<IterationReport> {
+        ensure!(
+            !self.archive.is_empty(),
+            "There are no elites in the archive to make children from"
+        );
+        let mut rng = StdRng::seed_from_u64(seed);
+        let child_maker = self.child_maker.prepare(&self.archive)?;
+        let children = child_seeds(&mut rng, self.batch_size)
+            .into_iter()
+            .map(|child_seed| {
+                let mut rng = StdRng::seed_from_u64(child_seed);
+                child_maker.apply(&self.archive, &mut rng)
+            })
+            .try_collect()?;
+        self.finish_iteration(children)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+    use crate::{
+        individual::ec::EcIndividual,
+        operator::{
+            genome_extractor::GenomeExtractor,
+            selector::{random::Random, Select},
+            Composable,
+        },
+        test_results::Score,
+    };
+
+    type Scored = EcIndividual<i32, TestResults<Score<i32>>>;
+
+    fn scored(genome: i32) -> Scored {
+        // Genomes closer to 0 are better.
+        EcIndividual::new(
+            genome,
+            TestResults {
+                results: Vec::new(),
+                total_result: Score::from(-genome.abs()),
+            },
+        )
+    }
+
+    #[test]
+    fn values_are_binned() {
+        assert_eq!(0, bin(-1.0, 0.0, 10.0, 5));
+        assert_eq!(0, bin(1.9, 0.0, 10.0, 5));
+        assert_eq!(1, bin(2.0, 0.0, 10.0, 5));
+        assert_eq!(4, bin(10.0, 0.0, 10.0, 5));
+        assert_eq!(4, bin(100.0, 0.0, 10.0, 5));
+        assert_eq!(0, bin(f64::NAN, 0.0, 10.0, 5));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn cells_keep_the_best_individual() {
+        let mut archive = GridArchive::new(vec![2, 3]);
+        assert!(archive.insert(vec![0, 1], scored(5)).unwrap());
+        assert!(archive.insert(vec![0, 1], scored(2)).unwrap());
+        assert!(!archive.insert(vec![0, 1], scored(3)).unwrap());
+        assert!(archive.insert(vec![1, 2], scored(-1)).unwrap());
+        assert!(archive.insert(vec![2, 0], scored(0)).is_err());
+
+        assert_eq!(2, archive.get(&[0, 1]).unwrap().genome);
+        assert!(archive.get(&[1, 1]).is_none());
+        assert!((archive.coverage() - 2.0 / 6.0).abs() < f64::EPSILON);
+    }
+
+    #[derive(Clone, Copy)]
+    struct AddNoise;
+
+    impl Operator<i32> for AddNoise {
+        type Output = Scored;
+
+        fn apply<R: Rng + ?Sized>(&self, genome: i32, rng: &mut R) -> Result<Scored> {
+            Ok(scored(genome + rng.gen_range(-3..=3)))
+        }
+    }
+    impl Composable for AddNoise {}
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn iterations_fill_the_archive() {
+        // One cell for each genome from -10 to 10.
+        let features = FnFeatures(|individual: &Scored| {
+            vec![bin(f64::from(individual.genome), -10.5, 10.5, 21)]
+        });
+        let child_maker = Select::new(Random).then(GenomeExtractor).then(AddNoise);
+        let mut map_elites = MapElites::new(child_maker, features, GridArchive::new(vec![21]), 50);
+        assert_eq!(1, map_elites.insert_all([scored(0)]).unwrap());
+
+        let mut rng = rand::thread_rng();
+        let mut num_elites = 1;
+        for iteration in 1..=5 {
+            let report = map_elites.serial_next(rng.gen()).unwrap();
+            assert_eq!(iteration, report.iteration);
+            // Some of the new elites may have replaced older ones.
+            assert!((num_elites..=num_elites + report.num_inserted).contains(&report.num_elites));
+            num_elites = report.num_elites;
+        }
+        assert!(map_elites.archive().size() > 1);
+        for (cell, elite) in map_elites.archive().cells() {
+            assert_eq!(cell, features.cell(elite).as_slice());
+        }
+    }
+}
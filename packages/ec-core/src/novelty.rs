@@ -0,0 +1,312 @@
+//! Novelty search (Lehman and Stanley), which rewards individuals for
+//! behaving differently from the rest of the population and from the
+//! individuals in an archive of past novel behaviors.
+//!
+//! The pieces are:
+//!   - a [`Behavior`], which is some description of what an individual does
+//!     (e.g., its outputs on the training cases) along with a distance between
+//!     descriptions;
+//!   - a [`BehaviorDescriptor`], which gets the behavior of an individual;
+//!   - a [`NoveltyArchive`], which stores novel behaviors across generations
+//!     and measures the sparseness of new ones;
+//!   - the `Novelty` selector (in
+//!     [`operator::selector::novelty`](crate::operator::selector::novelty)),
+//!     which selects on novelty or on a blend of novelty and fitness.
+//!
+//! The archive is shared (by reference) with the selector, which updates
+//! it once per generation when it's prepared.
+
+use std::{collections::VecDeque, sync::RwLock};
+
+use anyhow::{ensure, Result};
+use num_traits::ToPrimitive;
+
+use crate::{individual::Individual, test_results::TestResults};
+
+/// A description of an individual's behavior.
+pub trait Behavior {
+    /// How different this behavior is from `other`.
+    fn distance(&self, other: &Self) -> f64;
+}
+
+/// Vectors of values (like the outputs on each case) are compared using the
+/// Euclidean distance. Values that can't be converted to an `f64` are
+/// treated as `NaN`.
+impl<T: ToPrimitive> Behavior for Vec<T> {
+    fn distance(&self, other: &Self) -> f64 {
+        self.iter()
+            .zip(other)
+            .map(|(x, y)| {
+                let difference = x.to_f64().unwrap_or(f64::NAN) - y.to_f64().unwrap_or(f64::NAN);
+                difference * difference
+            })
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+pub trait BehaviorDescriptor<I> {
+    type Behavior: Behavior;
+
+    /// Take a reference to an individual and return a description of its
+    /// behavior.
+    fn describe(&self, individual: &I) -> Self::Behavior;
+}
+
+#[derive(Clone, Copy)]
+pub struct FnDescriptor<T>(pub T);
+
+impl<I, B, T> BehaviorDescriptor<I> for FnDescriptor<T>
+where
+    T: Fn(&I) -> B,
+    B: Behavior,
+{
+    type Behavior = B;
+
+    fn describe(&self, individual: &I) -> Self::Behavior {
+        self.0(individual)
+    }
+}
+
+impl<I, T> BehaviorDescriptor<I> for &T
+where
+    T: BehaviorDescriptor<I>,
+{
+    type Behavior = T::Behavior;
+
+    fn describe(&self, individual: &I) -> Self::Behavior {
+        (**self).describe(individual)
+    }
+}
+
+/// Describe an individual by its vector of results on the individual cases.
+#[derive(Debug, Clone, Copy)]
+pub struct CaseResults;
+
+impl<I, R> BehaviorDescriptor<I> for CaseResults
+where
+    I: Individual<TestResults = TestResults<R>>,
+    R: Clone + ToPrimitive,
+{
+    type Behavior = Vec<R>;
+
+    fn describe(&self, individual: &I) -> Self::Behavior {
+        individual.test_results().results.clone()
+    }
+}
+
+/// Which behaviors from each generation get added to a [`NoveltyArchive`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchivePolicy {
+    /// Add every behavior whose sparseness is at least this threshold.
+    Threshold(f64),
+    /// Add the given number of most novel behaviors.
+    MostNovel(usize),
+}
+
+/// An archive of novel behaviors, used to measure the sparseness of new
+/// behaviors.
+///
+/// The sparseness of a behavior is its average distance to its
+/// `k_nearest` nearest neighbors among the other behaviors in the
+/// population and the behaviors in the archive.
+#[derive(Debug)]
+pub struct NoveltyArchive<B> {
+    k_nearest: usize,
+    policy: ArchivePolicy,
+    max_size: Option<usize>,
+    // The oldest behaviors are at the front.
+    behaviors: RwLock<VecDeque<B>>,
+}
+
+impl<B> NoveltyArchive<B> {
+    #[must_use]
+    pub const fn new(k_nearest: usize, policy: ArchivePolicy) -> Self {
+        Self {
+            k_nearest,
+            policy,
+            max_size: None,
+            behaviors: RwLock::new(VecDeque::new()),
+        }
+    }
+
+    /// Limit the archive to `max_size` behaviors; when it's full, the
+    /// oldest behaviors are removed to make room for new ones.
+    #[must_use]
+    pub const fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.with_behaviors(VecDeque::len)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Call `f` with the behaviors in the archive, oldest first.
+    pub fn with_behaviors<T>(&self, f: impl FnOnce(&VecDeque<B>) -> T) -> T {
+        // Only a panic while holding the write lock can poison it, and the
+        // archive is still usable then (if perhaps missing some behaviors),
+        // so poisoning can be ignored.
+        let behaviors = self
+            .behaviors
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        f(&behaviors)
+    }
+}
+
+impl<B: Behavior> NoveltyArchive<B> {
+    /// The sparseness of each of the behaviors in a population, compared to
+    /// the rest of the population and to the archive.
+    ///
+    /// A behavior with no neighbors at all (a population of one, with an
+    /// empty archive) has a sparseness of zero.
+    ///
+    /// # Errors
+    /// This returns an error if `k_nearest` is zero.
+    pub fn sparseness(&self, population: &[B]) -> Result<Vec<f64>> {
+        ensure!(
+            self.k_nearest > 0,
+            "The number of nearest neighbors used for sparseness must be positive"
+        );
+        self.with_behaviors(|archive| {
+            let mut distances = Vec::with_capacity(population.len() + archive.len());
+            Ok(population
+                .iter()
+                .enumerate()
+                .map(|(index, behavior)| {
+                    distances.clear();
+                    distances.extend(
+                        population
+                            .iter()
+                            .enumerate()
+                            .filter(|&(other_index, _)| other_index != index)
+                            .chain(archive.iter().enumerate())
+                            .map(|(_, other)| behavior.distance(other)),
+                    );
+                    let k = self.k_nearest.min(distances.len());
+                    if k == 0 {
+                        return 0.0;
+                    }
+                    distances.select_nth_unstable_by(k - 1, f64::total_cmp);
+                    distances[..k].iter().sum::<f64>() / k.to_f64().unwrap_or(f64::NAN)
+                })
+                .collect())
+        })
+    }
+
+    /// Add behaviors from a population to the archive according to the
+    /// archive's [`ArchivePolicy`], where `sparseness` is the sparseness of
+    /// each behavior (as computed by [`NoveltyArchive::sparseness`]).
+    ///
+    /// # Errors
+    /// This returns an error if there isn't one sparseness per behavior.
+    pub fn update(&self, population: Vec<B>, sparseness: &[f64]) -> Result<()> {
+        ensure!(
+            population.len() == sparseness.len(),
+            "There were {} behaviors but {} sparseness values",
+            population.len(),
+            sparseness.len()
+        );
+        let mut novel: Vec<(f64, B)> = sparseness.iter().copied().zip(population).collect();
+        match self.policy {
+            ArchivePolicy::Threshold(threshold) => {
+                novel.retain(|&(sparseness, _)| sparseness >= threshold);
+            }
+            ArchivePolicy::MostNovel(count) => {
+                novel.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+                novel.truncate(count);
+            }
+        }
+
+        let mut behaviors = self
+            .behaviors
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        behaviors.extend(novel.into_iter().map(|(_, behavior)| behavior));
+        if let Some(max_size) = self.max_size {
+            let excess = behaviors.len().saturating_sub(max_size);
+            behaviors.drain(..excess);
+        }
+        drop(behaviors);
+        Ok(())
+    }
+
+    /// Compute the sparseness of each of the behaviors in a population, and
+    /// then add them to the archive according to its [`ArchivePolicy`].
+    ///
+    /// # Errors
+    /// This returns an error if `k_nearest` is zero.
+    pub fn assess(&self, population: Vec<B>) -> Result<Vec<f64>> {
+        let sparseness = self.sparseness(&population)?;
+        self.update(population, &sparseness)?;
+        Ok(sparseness)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(expected: &[f64], actual: &[f64]) {
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(actual) {
+            assert!((expected - actual).abs() < 1e-10, "{expected} != {actual}");
+        }
+    }
+
+    #[test]
+    fn euclidean_distance() {
+        assert!((vec![0, 0].distance(&vec![3, 4]) - 5.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn sparseness_uses_the_nearest_neighbors() {
+        let archive = NoveltyArchive::new(2, ArchivePolicy::Threshold(f64::INFINITY));
+        let sparseness = archive
+            .sparseness(&[vec![0], vec![1], vec![3], vec![10]])
+            .unwrap();
+        assert_close(&[2.0, 1.5, 2.5, 8.0], &sparseness);
+
+        // Nothing is sparse enough to be added to the archive.
+        archive
+            .update(vec![vec![0], vec![1], vec![3], vec![10]], &sparseness)
+            .unwrap();
+        assert!(archive.is_empty());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn archived_behaviors_are_neighbors() {
+        let archive = NoveltyArchive::new(2, ArchivePolicy::MostNovel(1));
+        assert_close(
+            &[3.0, 3.0],
+            &archive.assess(vec![vec![0], vec![3]]).unwrap(),
+        );
+        // Ties are broken in favor of the earlier behavior.
+        assert_eq!(1, archive.len());
+        assert!(archive.with_behaviors(|behaviors| behaviors[0] == vec![0]));
+        assert_close(
+            &[2.5, 4.5],
+            &archive.sparseness(&[vec![1], vec![5]]).unwrap(),
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn oldest_behaviors_are_removed() {
+        let archive = NoveltyArchive::new(1, ArchivePolicy::Threshold(0.0)).with_max_size(3);
+        archive.update(vec![vec![0], vec![1]], &[1.0, 1.0]).unwrap();
+        archive.update(vec![vec![2], vec![3]], &[1.0, 1.0]).unwrap();
+        let archived =
+            archive.with_behaviors(|behaviors| behaviors.iter().cloned().collect::<Vec<_>>());
+        assert_eq!(vec![vec![1], vec![2], vec![3]], archived);
+    }
+}
@@ -1,11 +1,15 @@
 use anyhow::{Context, Result};
-use rand::rngs::ThreadRng;
+use rand::Rng;
 
-use super::{super::Operator, Composable};
+use super::{
+    super::{Describe, Description, Operator, Prepare},
+    Composable,
+};
 
 // TODO: May a `apply_n_times(usize)` operator
 //   to use in cases where, e.g., we need to select
 //   two parents using the same selection operator.
+#[derive(Clone)]
 pub struct And<F, G> {
     f: F,
     g: G,
@@ -25,10 +29,37 @@ where
 {
     type Output = (F::Output, G::Output);
 
-    fn apply(&self, x: A, rng: &mut ThreadRng) -> Result<Self::Output> {
+    fn apply<R: Rng + ?Sized>(&self, x: A, rng: &mut R) -> Result<Self::Output> {
         let f_value = self.f.apply(x.clone(), rng).context("f in `And` failed")?;
         let g_value = self.g.apply(x, rng).context("g in `And` failed")?;
         Ok((f_value, g_value))
     }
 }
 impl<F, G> Composable for And<F, G> {}
+
+impl<F, G> Describe for And<F, G>
+where
+    F: Describe,
+    G: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("And")
+            .with_child(self.f.describe())
+            .with_child(self.g.describe())
+    }
+}
+
+impl<P, F, G> Prepare<P> for And<F, G>
+where
+    F: Prepare<P>,
+    G: Prepare<P>,
+{
+    type Prepared = And<F::Prepared, G::Prepared>;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        Ok(And::new(
+            self.f.prepare(population)?,
+            self.g.prepare(population)?,
+        ))
+    }
+}
@@ -1,8 +1,10 @@
 use anyhow::{Context, Result};
+use rand::Rng;
 
 use super::Composable;
-use crate::operator::Operator;
+use crate::operator::{Describe, Description, Operator};
 
+#[derive(Clone)]
 pub struct Map<F> {
     f: F,
 }
@@ -20,7 +22,7 @@ where
 {
     type Output = [F::Output; 2];
 
-    fn apply(&self, [x, y]: [Input; 2], rng: &mut rand::rngs::ThreadRng) -> Result<Self::Output> {
+    fn apply<R: Rng + ?Sized>(&self, [x, y]: [Input; 2], rng: &mut R) -> Result<Self::Output> {
         let first_result = self
             .f
             .apply(x, rng)
@@ -39,11 +41,7 @@ where
 {
     type Output = (F::Output, F::Output);
 
-    fn apply(
-        &self,
-        (x, y): (Input, Input),
-        rng: &mut rand::rngs::ThreadRng,
-    ) -> Result<Self::Output> {
+    fn apply<R: Rng + ?Sized>(&self, (x, y): (Input, Input), rng: &mut R) -> Result<Self::Output> {
         let first_result = self
             .f
             .apply(x, rng)
@@ -62,7 +60,7 @@ where
 {
     type Output = Vec<F::Output>;
 
-    fn apply(&self, input: Vec<Input>, rng: &mut rand::rngs::ThreadRng) -> Result<Self::Output> {
+    fn apply<R: Rng + ?Sized>(&self, input: Vec<Input>, rng: &mut R) -> Result<Self::Output> {
         input
             .into_iter()
             .map(|x| {
@@ -77,3 +75,12 @@ where
 // TODO: Impl `Map` over iterators.
 
 impl<F> Composable for Map<F> {}
+
+impl<F> Describe for Map<F>
+where
+    F: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("Map").with_child(self.f.describe())
+    }
+}
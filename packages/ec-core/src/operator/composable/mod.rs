@@ -1,11 +1,18 @@
 // TODO: I had to make this `pub use` to get the imports in `TwoPointXoMutate`
 // to work   and I'm really not sure why. I should do homework on this.
-use self::{and::And, map::Map, repeat_with::RepeatWith, then::Then};
+pub use self::map::Map;
+use self::{
+    and::And, one_of::OneOf, repeat_with::RepeatWith, retry::Retry, then::Then,
+    with_probability::WithProbability,
+};
 
 mod and;
 mod map;
+mod one_of;
 mod repeat_with;
+mod retry;
 mod then;
+mod with_probability;
 
 pub trait Composable {
     fn then<Op>(self, op: Op) -> Then<Self, Op>
@@ -43,6 +50,34 @@ pub trait Composable {
         RepeatWith::new(self)
     }
 
+    /// Apply either this operator or `op`, chosen with probability
+    /// proportional to `weight` and `op_weight` respectively. Further
+    /// operators can be added to the choice with [`OneOf::or`].
+    fn one_of_ops<Op>(self, weight: usize, op: Op, op_weight: usize) -> OneOf<Self, Op>
+    where
+        Self: Sized,
+    {
+        OneOf::new(self, weight, op, op_weight)
+    }
+
+    /// Apply this operator with the given probability, and otherwise pass
+    /// the input through unchanged.
+    fn with_probability(self, probability: f64) -> WithProbability<Self>
+    where
+        Self: Sized,
+    {
+        WithProbability::new(self, probability)
+    }
+
+    /// Apply this operator until the result satisfies `predicate`, making
+    /// at most `max_attempts` attempts.
+    fn retry_until<P>(self, predicate: P, max_attempts: usize) -> Retry<Self, P>
+    where
+        Self: Sized,
+    {
+        Retry::new(self, predicate, max_attempts)
+    }
+
     fn map<Op>(self, op: Op) -> Map<Op>
     where
         Self: Sized,
@@ -0,0 +1,200 @@
+use anyhow::{ensure, Context, Result};
+use rand::Rng;
+
+use super::{
+    super::{Describe, Description, Operator, Prepare},
+    Composable,
+};
+
+/// An `Operator` that applies one of two `Operator`s, chosen with
+/// probability proportional to their weights.
+///
+/// More than two operators can be chosen between by calling `or`, which
+/// nests the existing choice in another `OneOf` (using its total weight),
+/// so the full set of operators is captured in the type.
+#[derive(Clone)]
+pub struct OneOf<F, G> {
+    f: F,
+    f_weight: usize,
+    g: G,
+    g_weight: usize,
+}
+
+impl<F, G> OneOf<F, G> {
+    pub const fn new(f: F, f_weight: usize, g: G, g_weight: usize) -> Self {
+        Self {
+            f,
+            f_weight,
+            g,
+            g_weight,
+        }
+    }
+
+    /// Also choose `h`, with the given weight.
+    #[must_use]
+    pub const fn or<H>(self, h: H, weight: usize) -> OneOf<Self, H> {
+        let total_weight = self.weight();
+        OneOf::new(self, total_weight, h, weight)
+    }
+
+    /// The total weight of the operators in this choice.
+    #[must_use]
+    pub const fn weight(&self) -> usize {
+        self.f_weight + self.g_weight
+    }
+}
+
+impl<A, F, G> Operator<A> for OneOf<F, G>
+where
+    F: Operator<A>,
+    G: Operator<A, Output = F::Output>,
+{
+    type Output = F::Output;
+
+    fn apply<R: Rng + ?Sized>(&self, x: A, rng: &mut R) -> Result<Self::Output> {
+        let total_weight = self.weight();
+        ensure!(
+            total_weight > 0,
+            "The total weight of the operators in `OneOf` was zero"
+        );
+        if rng.gen_range(0..total_weight) < self.f_weight {
+            self.f.apply(x, rng).context("f in `OneOf` failed")
+        } else {
+            self.g.apply(x, rng).context("g in `OneOf` failed")
+        }
+    }
+}
+impl<F, G> Composable for OneOf<F, G> {}
+
+// Each choice is described as a `Weighted` node holding its weight. Nested
+// choices (from `or`) are flattened into a single `OneOf`, but only when
+// they're chosen with their own total weight; otherwise flattening would
+// misrepresent how likely each of the nested operators is.
+impl<F, G> Describe for OneOf<F, G>
+where
+    F: Describe,
+    G: Describe,
+{
+    fn describe(&self) -> Description {
+        let weighted = |description: Description, weight| {
+            if description.name == "OneOf" && total_weight(&description) == Some(weight) {
+                description
+            } else {
+                Description::new("Weighted")
+                    .with_parameter("weight", weight)
+                    .with_child(description)
+            }
+        };
+        Description::new("OneOf")
+            .with_flattened_child(weighted(self.f.describe(), self.f_weight))
+            .with_flattened_child(weighted(self.g.describe(), self.g_weight))
+    }
+}
+
+/// The sum of the weights of the `Weighted` choices in the description of a
+/// `OneOf`, or `None` if it has any other children.
+fn total_weight(description: &Description) -> Option<usize> {
+    description
+        .children
+        .iter()
+        .map(|child| {
+            (child.name == "Weighted")
+                .then(|| child.parameters.get("weight")?.parse::<usize>().ok())
+                .flatten()
+        })
+        .sum()
+}
+
+impl<P, F, G> Prepare<P> for OneOf<F, G>
+where
+    F: Prepare<P>,
+    G: Prepare<P>,
+{
+    type Prepared = OneOf<F::Prepared, G::Prepared>;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        Ok(OneOf::new(
+            self.f.prepare(population)?,
+            self.f_weight,
+            self.g.prepare(population)?,
+            self.g_weight,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+
+    #[derive(Clone)]
+    struct Constant(i32);
+    impl Operator<()> for Constant {
+        type Output = i32;
+
+        fn apply<R: Rng + ?Sized>(&self, (): (), _: &mut R) -> Result<Self::Output> {
+            Ok(self.0)
+        }
+    }
+    impl Composable for Constant {}
+
+    impl Describe for Constant {
+        fn describe(&self) -> Description {
+            Description::new("Constant").with_parameter("value", self.0)
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn operators_with_zero_weight_are_never_chosen() {
+        let mut rng = thread_rng();
+        let one_of = Constant(1).one_of_ops(0, Constant(2), 3).or(Constant(3), 0);
+        assert_eq!(one_of.weight(), 3);
+        for _ in 0..100 {
+            assert_eq!(one_of.apply((), &mut rng).unwrap(), 2);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn every_operator_with_positive_weight_is_chosen() {
+        let mut rng = thread_rng();
+        let one_of = Constant(1).one_of_ops(1, Constant(2), 1).or(Constant(3), 1);
+        let mut counts = [0; 3];
+        for _ in 0..300 {
+            let result = one_of.apply((), &mut rng).unwrap();
+            counts[usize::try_from(result - 1).unwrap()] += 1;
+        }
+        // Each operator is expected to be chosen 100 times, so this fails
+        // with negligible probability.
+        assert!(counts.iter().all(|&count| count > 50), "{counts:?}");
+    }
+
+    #[test]
+    fn zero_total_weight_is_an_error() {
+        let one_of = Constant(1).one_of_ops(0, Constant(2), 0);
+        assert!(one_of.apply((), &mut thread_rng()).is_err());
+    }
+
+    #[test]
+    fn nested_choices_keep_their_own_weight() {
+        let nested = Constant(1).one_of_ops(1, Constant(2), 1);
+        let flattened = nested.clone().or(Constant(3), 1).describe();
+        let weights = |description: &Description| {
+            description
+                .children
+                .iter()
+                .map(|child| child.parameters["weight"].clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(weights(&flattened), ["1", "1", "1"]);
+
+        // Here the nested choice is chosen three times as often as `Constant(3)`
+        // rather than twice as often, so it can't be flattened.
+        let reweighted = OneOf::new(nested, 6, Constant(3), 2).describe();
+        assert_eq!(weights(&reweighted), ["6", "2"]);
+        assert_eq!(reweighted.children[0].children[0].name, "OneOf");
+        assert_eq!(weights(&reweighted.children[0].children[0]), ["1", "1"]);
+    }
+}
@@ -2,13 +2,15 @@ use std::iter;
 
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
+use rand::Rng;
 
 use super::Composable;
-use crate::operator::Operator;
+use crate::operator::{Describe, Description, Operator, Prepare};
 
 /// An `Operator` that applies the encapsulated `Operator`
 /// `N` times on the given input, returning an array of
 /// the `N` results.
+#[derive(Clone)]
 pub struct RepeatWith<F, const N: usize> {
     f: F,
 }
@@ -26,7 +28,7 @@ where
 {
     type Output = [F::Output; N];
 
-    fn apply(&self, input: Input, rng: &mut rand::rngs::ThreadRng) -> Result<Self::Output> {
+    fn apply<R: Rng + ?Sized>(&self, input: Input, rng: &mut R) -> Result<Self::Output> {
         iter::repeat_with(|| self.f.apply(input.clone(), rng))
             .take(N)
             .try_collect::<_, Vec<<F as Operator<Input>>::Output>, anyhow::Error>()?
@@ -43,6 +45,28 @@ where
 
 impl<F, const N: usize> Composable for RepeatWith<F, N> {}
 
+impl<F, const N: usize> Describe for RepeatWith<F, N>
+where
+    F: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("RepeatWith")
+            .with_parameter("n", N)
+            .with_child(self.f.describe())
+    }
+}
+
+impl<P, F, const N: usize> Prepare<P> for RepeatWith<F, N>
+where
+    F: Prepare<P>,
+{
+    type Prepared = RepeatWith<F::Prepared, N>;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        Ok(RepeatWith::new(self.f.prepare(population)?))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::ops::Range;
@@ -55,7 +79,7 @@ mod tests {
     impl Operator<i32> for AddOne {
         type Output = i32;
 
-        fn apply(&self, input: i32, _: &mut rand::rngs::ThreadRng) -> Result<Self::Output> {
+        fn apply<R: Rng + ?Sized>(&self, input: i32, _: &mut R) -> Result<Self::Output> {
             Ok(input + 1)
         }
     }
@@ -77,11 +101,7 @@ mod tests {
     impl Operator<Range<i32>> for UniformRange {
         type Output = i32;
 
-        fn apply(
-            &self,
-            range: Range<i32>,
-            rng: &mut rand::rngs::ThreadRng,
-        ) -> Result<Self::Output> {
+        fn apply<R: Rng + ?Sized>(&self, range: Range<i32>, rng: &mut R) -> Result<Self::Output> {
             Ok(rng.gen_range(range))
         }
     }
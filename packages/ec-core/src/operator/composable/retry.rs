@@ -0,0 +1,124 @@
+use anyhow::{ensure, Result};
+use rand::Rng;
+
+use super::{
+    super::{Describe, Description, Operator, Prepare},
+    Composable,
+};
+
+/// An `Operator` that applies the encapsulated `Operator` to (clones of) its
+/// input until the result satisfies `predicate`, or `max_attempts` results
+/// have been made.
+///
+/// If none of the results satisfy `predicate`, the last one is returned.
+/// Errors from the encapsulated `Operator` are returned immediately rather
+/// than retried.
+#[derive(Clone)]
+pub struct Retry<F, P> {
+    f: F,
+    predicate: P,
+    max_attempts: usize,
+}
+
+impl<F, P> Retry<F, P> {
+    pub const fn new(f: F, predicate: P, max_attempts: usize) -> Self {
+        Self {
+            f,
+            predicate,
+            max_attempts,
+        }
+    }
+}
+
+impl<A, F, P> Operator<A> for Retry<F, P>
+where
+    A: Clone,
+    F: Operator<A>,
+    P: Fn(&F::Output) -> bool,
+{
+    type Output = F::Output;
+
+    fn apply<R: Rng + ?Sized>(&self, x: A, rng: &mut R) -> Result<Self::Output> {
+        ensure!(
+            self.max_attempts > 0,
+            "`Retry` must make at least one attempt"
+        );
+        for _ in 1..self.max_attempts {
+            let result = self.f.apply(x.clone(), rng)?;
+            if (self.predicate)(&result) {
+                return Ok(result);
+            }
+        }
+        self.f.apply(x, rng)
+    }
+}
+impl<F, P> Composable for Retry<F, P> {}
+
+// The predicate can't be described, so only the number of attempts is
+// included.
+impl<F, P> Describe for Retry<F, P>
+where
+    F: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("Retry")
+            .with_parameter("max_attempts", self.max_attempts)
+            .with_child(self.f.describe())
+    }
+}
+
+impl<Pop, F, P> Prepare<Pop> for Retry<F, P>
+where
+    F: Prepare<Pop>,
+    P: Clone,
+{
+    type Prepared = Retry<F::Prepared, P>;
+
+    fn prepare(&self, population: &Pop) -> Result<Self::Prepared> {
+        Ok(Retry::new(
+            self.f.prepare(population)?,
+            self.predicate.clone(),
+            self.max_attempts,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rand::thread_rng;
+
+    use super::*;
+
+    // Returns 1, 2, 3, ... regardless of its input.
+    struct Counter(AtomicUsize);
+    impl Operator<()> for Counter {
+        type Output = usize;
+
+        fn apply<R: Rng + ?Sized>(&self, (): (), _: &mut R) -> Result<Self::Output> {
+            Ok(self.0.fetch_add(1, Ordering::Relaxed) + 1)
+        }
+    }
+    impl Composable for Counter {}
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn stops_when_the_predicate_holds() {
+        let retry = Counter(AtomicUsize::new(0)).retry_until(|&n: &usize| n >= 3, 10);
+        assert_eq!(retry.apply((), &mut thread_rng()).unwrap(), 3);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn returns_the_last_attempt_when_the_predicate_never_holds() {
+        let retry = Counter(AtomicUsize::new(0)).retry_until(|&n: &usize| n >= 3, 2);
+        assert_eq!(retry.apply((), &mut thread_rng()).unwrap(), 2);
+    }
+
+    #[test]
+    fn zero_attempts_is_an_error() {
+        let retry = Counter(AtomicUsize::new(0)).retry_until(|_: &usize| true, 0);
+        assert!(retry.apply((), &mut thread_rng()).is_err());
+    }
+}
@@ -1,8 +1,12 @@
 use anyhow::{Context, Result};
-use rand::rngs::ThreadRng;
+use rand::Rng;
 
-use super::{super::Operator, Composable};
+use super::{
+    super::{Describe, Description, Operator, Prepare},
+    Composable,
+};
 
+#[derive(Clone)]
 pub struct Then<F, G> {
     f: F,
     g: G,
@@ -21,13 +25,37 @@ where
 {
     type Output = G::Output;
 
-    fn apply(&self, x: A, rng: &mut ThreadRng) -> Result<Self::Output> {
+    fn apply<R: Rng + ?Sized>(&self, x: A, rng: &mut R) -> Result<Self::Output> {
         let f_result = self.f.apply(x, rng).context("f in `Then` failed")?;
         self.g.apply(f_result, rng)
     }
 }
 impl<F, G> Composable for Then<F, G> {}
 
+impl<F, G> Describe for Then<F, G>
+where
+    F: Describe,
+    G: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("Then")
+            .with_flattened_child(self.f.describe())
+            .with_flattened_child(self.g.describe())
+    }
+}
+
+impl<P, F, G> Prepare<P> for Then<F, G>
+where
+    F: Prepare<P>,
+    G: Clone,
+{
+    type Prepared = Then<F::Prepared, G>;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        Ok(Then::new(self.f.prepare(population)?, self.g.clone()))
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
     use rand::thread_rng;
@@ -38,7 +66,7 @@ pub mod tests {
     impl Operator<i32> for Increment {
         type Output = i32;
 
-        fn apply(&self, input: i32, _: &mut ThreadRng) -> Result<Self::Output> {
+        fn apply<R: Rng + ?Sized>(&self, input: i32, _: &mut R) -> Result<Self::Output> {
             Ok(input + 1)
         }
     }
@@ -48,7 +76,7 @@ pub mod tests {
     impl Operator<i32> for Double {
         type Output = i32;
 
-        fn apply(&self, input: i32, _: &mut ThreadRng) -> Result<Self::Output> {
+        fn apply<R: Rng + ?Sized>(&self, input: i32, _: &mut R) -> Result<Self::Output> {
             Ok(input * 2)
         }
     }
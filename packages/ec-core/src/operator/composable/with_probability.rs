@@ -0,0 +1,110 @@
+use anyhow::{ensure, Context, Result};
+use rand::Rng;
+
+use super::{
+    super::{Describe, Description, Operator, Prepare},
+    Composable,
+};
+
+/// An `Operator` that applies the encapsulated `Operator` with the given
+/// probability, and otherwise returns its input unchanged.
+#[derive(Clone)]
+pub struct WithProbability<F> {
+    f: F,
+    probability: f64,
+}
+
+impl<F> WithProbability<F> {
+    pub const fn new(f: F, probability: f64) -> Self {
+        Self { f, probability }
+    }
+}
+
+impl<A, F> Operator<A> for WithProbability<F>
+where
+    F: Operator<A, Output = A>,
+{
+    type Output = A;
+
+    fn apply<R: Rng + ?Sized>(&self, x: A, rng: &mut R) -> Result<Self::Output> {
+        ensure!(
+            (0.0..=1.0).contains(&self.probability),
+            "The probability in `WithProbability` must be between 0 and 1, but was {}",
+            self.probability
+        );
+        if rng.gen_bool(self.probability) {
+            self.f
+                .apply(x, rng)
+                .context("f in `WithProbability` failed")
+        } else {
+            Ok(x)
+        }
+    }
+}
+impl<F> Composable for WithProbability<F> {}
+
+impl<F> Describe for WithProbability<F>
+where
+    F: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("WithProbability")
+            .with_parameter("probability", self.probability)
+            .with_child(self.f.describe())
+    }
+}
+
+impl<P, F> Prepare<P> for WithProbability<F>
+where
+    F: Prepare<P>,
+{
+    type Prepared = WithProbability<F::Prepared>;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        Ok(WithProbability::new(
+            self.f.prepare(population)?,
+            self.probability,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+
+    struct Increment;
+    impl Operator<i32> for Increment {
+        type Output = i32;
+
+        fn apply<R: Rng + ?Sized>(&self, input: i32, _: &mut R) -> Result<Self::Output> {
+            Ok(input + 1)
+        }
+    }
+    impl Composable for Increment {}
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn certain_and_impossible() {
+        let mut rng = thread_rng();
+        assert_eq!(
+            Increment.with_probability(1.0).apply(7, &mut rng).unwrap(),
+            8
+        );
+        assert_eq!(
+            Increment.with_probability(0.0).apply(7, &mut rng).unwrap(),
+            7
+        );
+    }
+
+    #[test]
+    fn invalid_probability_is_an_error() {
+        let mut rng = thread_rng();
+        assert!(Increment.with_probability(1.5).apply(7, &mut rng).is_err());
+        assert!(Increment
+            .with_probability(f64::NAN)
+            .apply(7, &mut rng)
+            .is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Display},
+};
+
+use itertools::Itertools;
+
+/// A structured description of an operator (or selector, mutator, or
+/// recombinator): its name, its parameters, and the descriptions of the
+/// operators it's built from.
+///
+/// The `Display` implementation prints the description as an indented tree,
+/// e.g.,
+///
+/// ```text
+/// Then
+///   RepeatWith(n = 2)
+///     Select
+///       Tournament(size = 2)
+///         Natural
+///   Map
+///     GenomeExtractor
+/// ```
+///
+/// With the `serde` feature enabled descriptions can also be serialized,
+/// e.g., to record the configuration of a run in its logs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Description {
+    pub name: String,
+    pub parameters: BTreeMap<String, String>,
+    pub children: Vec<Self>,
+}
+
+impl Description {
+    #[must_use]
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            parameters: BTreeMap::new(),
+            children: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_parameter(mut self, name: impl Into<String>, value: impl Display) -> Self {
+        self.parameters.insert(name.into(), value.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn with_child(mut self, child: Self) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Add `child`, or if it has the same name as this description, its
+    /// children instead.
+    ///
+    /// This is used to flatten nested combinators, so that, e.g.,
+    /// `a.then(b).then(c)` is described as a single `Then` with three
+    /// children.
+    #[must_use]
+    pub fn with_flattened_child(mut self, child: Self) -> Self {
+        if child.name == self.name && child.parameters == self.parameters {
+            self.children.extend(child.children);
+        } else {
+            self.children.push(child);
+        }
+        self
+    }
+
+    fn write_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        write!(f, "{:indent$}{}", "", self.name, indent = 2 * depth)?;
+        if !self.parameters.is_empty() {
+            write!(
+                f,
+                "({})",
+                self.parameters
+                    .iter()
+                    .map(|(name, value)| format!("{name} = {value}"))
+                    .join(", ")
+            )?;
+        }
+        for child in &self.children {
+            writeln!(f)?;
+            child.write_tree(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Description {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_tree(f, 0)
+    }
+}
+
+/// Operators (and selectors, mutators, and recombinators) that can describe
+/// themselves, so that the configuration of a pipeline can be printed or
+/// logged.
+///
+/// Combinators include the descriptions of the operators they're built
+/// from, so they can only be described if those can.
+pub trait Describe {
+    fn describe(&self) -> Description;
+}
+
+impl<T> Describe for &T
+where
+    T: Describe + ?Sized,
+{
+    fn describe(&self) -> Description {
+        (**self).describe()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::operator::{
+        genome_extractor::GenomeExtractor,
+        selector::{tournament::Tournament, Select},
+        Composable,
+    };
+
+    #[test]
+    fn pipelines_are_described_as_trees() {
+        let pipeline = Select::new(Tournament::new(2))
+            .apply_twice()
+            .then_map(GenomeExtractor)
+            .then(GenomeExtractor.with_probability(0.5));
+        let description = pipeline.describe();
+
+        assert_eq!(description.name, "Then");
+        assert_eq!(description.children.len(), 3);
+        assert_eq!(
+            description.to_string(),
+            "Then\n  RepeatWith(n = 2)\n    Select\n      Tournament(size = 2)\n        Natural\n  Map\n    \
+             GenomeExtractor\n  WithProbability(probability = 0.5)\n    GenomeExtractor"
+        );
+    }
+
+    #[test]
+    fn weighted_choices_are_flattened() {
+        let one_of = GenomeExtractor
+            .one_of_ops(3, GenomeExtractor, 2)
+            .or(GenomeExtractor, 1);
+        let description = one_of.describe();
+
+        assert_eq!(description.name, "OneOf");
+        let weights = description
+            .children
+            .iter()
+            .map(|child| child.parameters["weight"].as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(weights, ["3", "2", "1"]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn descriptions_can_be_serialized() {
+        let description = Select::new(Tournament::new(3)).describe();
+        let json = serde_json::to_string(&description).unwrap();
+        assert_eq!(
+            json,
+            r#"{"name":"Select","parameters":{},"children":[{"name":"Tournament","parameters":{"size":"3"},"children":[{"name":"Natural","parameters":{},"children":[]}]}]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Description>(&json).unwrap(),
+            description
+        );
+    }
+}
@@ -1,8 +1,10 @@
 use anyhow::Result;
+use rand::Rng;
 
-use super::{Composable, Operator};
+use super::{Composable, Describe, Description, Operator};
 use crate::individual::Individual;
 
+#[derive(Clone, Copy)]
 pub struct GenomeExtractor;
 
 /// Returns a _cloned_ copy of the given individual's genome.
@@ -17,8 +19,14 @@ where
 {
     type Output = I::Genome;
 
-    fn apply(&self, individual: &I, _: &mut rand::rngs::ThreadRng) -> Result<Self::Output> {
+    fn apply<R: Rng + ?Sized>(&self, individual: &I, _: &mut R) -> Result<Self::Output> {
         Ok(individual.genome().clone())
     }
 }
 impl Composable for GenomeExtractor {}
+
+impl Describe for GenomeExtractor {
+    fn describe(&self) -> Description {
+        Description::new("GenomeExtractor")
+    }
+}
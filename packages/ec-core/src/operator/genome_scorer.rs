@@ -1,26 +1,166 @@
-use anyhow::Result;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use anyhow::{ensure, Result};
+use rand::Rng;
 
-use super::{composable::Wrappable, Composable, Operator};
-use crate::{
-    individual::{ec::EcIndividual, scorer::Scorer},
-    population::Population,
+use super::{composable::Wrappable, Composable, Describe, Description, Operator, Prepare};
+use crate::individual::{
+    ec::EcIndividual,
+    scorer::{InfallibleScorer, TryScorer},
 };
 
-pub struct GenomeScorer<GM, S> {
+/// What a [`GenomeScorer`] does when its scorer fails to score a genome.
+///
+/// `recover` either returns the score to give the genome anyway (`Some`),
+/// asks for the genome to be discarded and another one made (`None`), or
+/// returns an error, which aborts making the child (and so the generation).
+pub trait OnScoreError<R> {
+    /// Decide what to do about the scoring error `error`.
+    ///
+    /// # Errors
+    /// This returns an error if the failure shouldn't be recovered from.
+    fn recover(&self, error: anyhow::Error) -> Result<Option<R>>;
+
+    /// The number of genomes to make before giving up when they keep being
+    /// discarded.
+    fn max_attempts(&self) -> usize {
+        1
+    }
+}
+
+/// Scoring errors are returned, aborting the generation. This is the
+/// default policy.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Propagate;
+
+impl<R> OnScoreError<R> for Propagate {
+    fn recover(&self, error: anyhow::Error) -> Result<Option<R>> {
+        Err(error)
+    }
+}
+
+/// Genomes that can't be scored are given a (typically terrible) penalty
+/// score instead.
+#[derive(Debug, Clone)]
+pub struct Penalty<R>(pub R);
+
+impl<R: Clone> OnScoreError<R> for Penalty<R> {
+    fn recover(&self, _: anyhow::Error) -> Result<Option<R>> {
+        Ok(Some(self.0.clone()))
+    }
+}
+
+/// Genomes that can't be scored are discarded and another one is made, up
+/// to a total of `max_attempts` genomes per child, after which the last
+/// error is returned.
+#[derive(Debug, Clone, Copy)]
+pub struct Discard {
+    max_attempts: usize,
+}
+
+impl Discard {
+    #[must_use]
+    pub const fn new(max_attempts: usize) -> Self {
+        Self { max_attempts }
+    }
+}
+
+impl<R> OnScoreError<R> for Discard {
+    fn recover(&self, _: anyhow::Error) -> Result<Option<R>> {
+        Ok(None)
+    }
+
+    fn max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+}
+
+/// The number of genomes a [`GenomeScorer`] failed to score, whatever its
+/// failure policy did about them.
+///
+/// Clones share the same count, so a clone can be kept to report the count
+/// after the `GenomeScorer` has been moved into a generation, e.g., with
+/// [`Run::with_score_error_count`](crate::run::Run::with_score_error_count).
+#[derive(Debug, Clone, Default)]
+pub struct ScoreErrorCount(Arc<AtomicUsize>);
+
+impl ScoreErrorCount {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn increment(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[derive(Clone)]
+pub struct GenomeScorer<GM, S, P = Propagate> {
     genome_maker: GM,
     scorer: S,
+    on_error: P,
+    error_count: ScoreErrorCount,
+}
+
+impl<G, S> GenomeScorer<G, InfallibleScorer<S>> {
+    /// Score the genomes made by `genome_maker` with `scorer`, which can't
+    /// fail.
+    pub fn new(genome_maker: G, scorer: S) -> Self {
+        Self::fallible(genome_maker, InfallibleScorer(scorer))
+    }
 }
 
 impl<G, S> GenomeScorer<G, S> {
-    pub const fn new(genome_maker: G, scorer: S) -> Self {
+    /// Score the genomes made by `genome_maker` with `scorer`, which is a
+    /// [`TryScorer`] that can fail. Failures are handled by the failure
+    /// policy, which by default returns the error (see
+    /// [`GenomeScorer::with_on_error`]).
+    pub fn fallible(genome_maker: G, scorer: S) -> Self {
         Self {
             genome_maker,
             scorer,
+            on_error: Propagate,
+            error_count: ScoreErrorCount::new(),
+        }
+    }
+}
+
+impl<G, S, P> GenomeScorer<G, S, P> {
+    /// Use `on_error` to decide what to do when the scorer fails.
+    #[must_use]
+    pub fn with_on_error<Q>(self, on_error: Q) -> GenomeScorer<G, S, Q> {
+        GenomeScorer {
+            genome_maker: self.genome_maker,
+            scorer: self.scorer,
+            on_error,
+            error_count: self.error_count,
         }
     }
+
+    /// Count scoring failures in `error_count` (which is typically a clone of
+    /// a count that's reported elsewhere).
+    #[must_use]
+    pub fn with_error_count(mut self, error_count: ScoreErrorCount) -> Self {
+        self.error_count = error_count;
+        self
+    }
+
+    #[must_use]
+    pub const fn error_count(&self) -> &ScoreErrorCount {
+        &self.error_count
+    }
 }
 
-impl<G, S> Wrappable<G> for GenomeScorer<G, S> {
+impl<G, S> Wrappable<G> for GenomeScorer<G, InfallibleScorer<S>> {
     type Context = S;
 
     fn construct(genome_maker: G, scorer: Self::Context) -> Self {
@@ -29,20 +169,154 @@ impl<G, S> Wrappable<G> for GenomeScorer<G, S> {
 }
 
 // scorer: &Genome -> TestResults<R>
-impl<'pop, GM, S, R, P> Operator<&'pop P> for GenomeScorer<GM, S>
+//
+// The input is usually the population, but can be anything the genome maker
+// accepts, e.g., the selected parents.
+impl<GM, S, P, Input> Operator<Input> for GenomeScorer<GM, S, P>
 where
-    P: Population,
-    GM: Operator<&'pop P>,
-    S: Scorer<GM::Output, Score = R>,
+    Input: Clone,
+    GM: Operator<Input>,
+    S: TryScorer<GM::Output>,
+    P: OnScoreError<S::Score>,
 {
     type Output = EcIndividual<GM::Output, S::Score>;
 
-    fn apply(&self, population: &'pop P, rng: &mut rand::rngs::ThreadRng) -> Result<Self::Output> {
-        let genome = self.genome_maker.apply(population, rng)?;
-        let score = self.scorer.score(&genome);
+    fn apply<R: Rng + ?Sized>(&self, input: Input, rng: &mut R) -> Result<Self::Output> {
+        let max_attempts = self.on_error.max_attempts().max(1);
+        let mut num_attempts = 0;
         // TODO: We probably don't want to bake in `EcIndividual` here, but instead
         //   have things be more general than that.
-        Ok(EcIndividual::new(genome, score))
+        loop {
+            num_attempts += 1;
+            let genome = self.genome_maker.apply(input.clone(), rng)?;
+            let error = match self.scorer.try_score(&genome) {
+                Ok(score) => return Ok(EcIndividual::new(genome, score)),
+                Err(error) => error.context("Failed to score a genome"),
+            };
+            self.error_count.increment();
+            let message = format!("{error:#}");
+            if let Some(score) = self.on_error.recover(error)? {
+                return Ok(EcIndividual::new(genome, score));
+            }
+            ensure!(
+                num_attempts < max_attempts,
+                "Discarded {num_attempts} genome(s) that couldn't be scored; the last error was: \
+                 {message}"
+            );
+        }
+    }
+}
+impl<GM, S, P> Composable for GenomeScorer<GM, S, P> {}
+
+// The scorer and failure policy are typically closures, so only the genome
+// maker is described.
+impl<GM, S, P> Describe for GenomeScorer<GM, S, P>
+where
+    GM: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("GenomeScorer").with_child(self.genome_maker.describe())
+    }
+}
+
+impl<Pop, GM, S, P> Prepare<Pop> for GenomeScorer<GM, S, P>
+where
+    GM: Prepare<Pop>,
+    S: Clone,
+    P: Clone,
+{
+    type Prepared = GenomeScorer<GM::Prepared, S, P>;
+
+    fn prepare(&self, population: &Pop) -> Result<Self::Prepared> {
+        Ok(GenomeScorer {
+            genome_maker: self.genome_maker.prepare(population)?,
+            scorer: self.scorer.clone(),
+            on_error: self.on_error.clone(),
+            error_count: self.error_count.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use anyhow::bail;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::individual::scorer::FnTryScorer;
+
+    // Makes the genomes 0, 1, 2, ... regardless of its input.
+    struct Counter(AtomicUsize);
+
+    impl Operator<()> for Counter {
+        type Output = usize;
+
+        fn apply<R: Rng + ?Sized>(&self, (): (), _: &mut R) -> Result<Self::Output> {
+            Ok(self.0.fetch_add(1, Ordering::Relaxed))
+        }
+    }
+    impl Composable for Counter {}
+
+    // Only genomes that are multiples of 3 can be scored.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    fn score(genome: &usize) -> Result<usize> {
+        if !genome.is_multiple_of(3) {
+            bail!("Can't score {genome}");
+        }
+        Ok(genome * 10)
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn propagate_returns_the_error() {
+        let genome_scorer =
+            GenomeScorer::fallible(Counter(AtomicUsize::new(0)), FnTryScorer(score));
+        let mut rng = thread_rng();
+
+        assert_eq!(genome_scorer.apply((), &mut rng).unwrap().test_results, 0);
+        assert!(genome_scorer.apply((), &mut rng).is_err());
+        assert_eq!(genome_scorer.error_count().get(), 1);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn penalty_is_used_as_the_score() {
+        let genome_scorer =
+            GenomeScorer::fallible(Counter(AtomicUsize::new(1)), FnTryScorer(score))
+                .with_on_error(Penalty(1_000));
+        let child = genome_scorer.apply((), &mut thread_rng()).unwrap();
+
+        assert_eq!(child.genome, 1);
+        assert_eq!(child.test_results, 1_000);
+        assert_eq!(genome_scorer.error_count().get(), 1);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn discarded_genomes_are_replaced() {
+        let error_count = ScoreErrorCount::new();
+        let genome_scorer =
+            GenomeScorer::fallible(Counter(AtomicUsize::new(1)), FnTryScorer(score))
+                .with_on_error(Discard::new(3))
+                .with_error_count(error_count.clone());
+        let mut rng = thread_rng();
+
+        // 1 and 2 are discarded, and 3 can be scored.
+        let child = genome_scorer.apply((), &mut rng).unwrap();
+        assert_eq!(child.genome, 3);
+        assert_eq!(child.test_results, 30);
+        assert_eq!(error_count.get(), 2);
+
+        // 4 and 5 are discarded, and 6 can be scored.
+        assert_eq!(genome_scorer.apply((), &mut rng).unwrap().genome, 6);
+
+        // Every attempt fails if none of the genomes are multiples of 3.
+        let genome_scorer =
+            GenomeScorer::fallible(Counter(AtomicUsize::new(10)), FnTryScorer(score))
+                .with_on_error(Discard::new(2));
+        assert!(genome_scorer.apply((), &mut rng).is_err());
+        assert_eq!(genome_scorer.error_count().get(), 2);
     }
 }
-impl<GM, S> Composable for GenomeScorer<GM, S> {}
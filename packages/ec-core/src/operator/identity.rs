@@ -1,7 +1,7 @@
 use anyhow::Result;
-use rand::rngs::ThreadRng;
+use rand::Rng;
 
-use super::{Composable, Operator};
+use super::{Composable, Describe, Description, Operator};
 
 pub struct Identity<T> {
     value: T,
@@ -19,8 +19,14 @@ where
 {
     type Output = T;
 
-    fn apply(&self, (): (), _: &mut ThreadRng) -> Result<Self::Output> {
+    fn apply<R: Rng + ?Sized>(&self, (): (), _: &mut R) -> Result<Self::Output> {
         Ok(self.value.clone())
     }
 }
 impl<T> Composable for Identity<T> {}
+
+impl<T> Describe for Identity<T> {
+    fn describe(&self) -> Description {
+        Description::new("Identity")
+    }
+}
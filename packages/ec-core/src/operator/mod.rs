@@ -1,15 +1,18 @@
 use anyhow::Result;
-use rand::rngs::ThreadRng;
+use rand::Rng;
 
 pub mod composable;
+pub mod describe;
 pub mod genome_extractor;
 pub mod genome_scorer;
 pub mod identity;
 pub mod mutator;
 pub mod recombinator;
+pub mod select_parents;
 pub mod selector;
 
 pub use composable::Composable;
+pub use describe::{Describe, Description};
 
 pub trait Operator<Input>: Composable {
     type Output;
@@ -18,5 +21,51 @@ pub trait Operator<Input>: Composable {
     /// This will return an error if there's some problem applying the operator.
     /// Given how general this concept is, there's no good way of saying here
     /// what that might be.
-    fn apply(&self, input: Input, rng: &mut ThreadRng) -> Result<Self::Output>;
+    fn apply<R: Rng + ?Sized>(&self, input: Input, rng: &mut R) -> Result<Self::Output>;
+}
+
+/// Operators (and selectors) that can do some work once per population.
+///
+/// That work is then shared by every application to that population
+/// instead of being repeated each time, e.g., the per-case epsilons in
+/// epsilon-lexicase.
+///
+/// `Generation` prepares its child maker once at the start of each
+/// generation, and then uses the prepared version to make all the
+/// children. Composite operators prepare the parts that act on the
+/// population and clone the rest; operators that have nothing to
+/// prepare just return a copy of themselves.
+///
+/// Selectors don't need to implement this to be used in a `Generation`:
+/// [`Select`](selector::Select) prepares its selector through
+/// [`Selector::prepare_selector`](selector::Selector::prepare_selector),
+/// which by default does nothing.
+pub trait Prepare<P> {
+    type Prepared;
+
+    /// # Errors
+    /// This will return an error if there's some problem with the
+    /// preprocessing, e.g., the population is empty.
+    fn prepare(&self, population: &P) -> Result<Self::Prepared>;
+
+    /// Prepare a separate operator for each of the `num_children` children
+    /// that are about to be made from `population`.
+    ///
+    /// `Generation` calls this before making each generation's children, and
+    /// only falls back on [`prepare`](Prepare::prepare) (sharing one prepared
+    /// operator between all the children) if it returns `None`, which is the
+    /// default. [`SelectParents`](select_parents::SelectParents) uses it to
+    /// select the parents of all the children in one batch.
+    ///
+    /// # Errors
+    /// This will return an error if there's some problem with the
+    /// preprocessing, e.g., the population is empty.
+    fn prepare_children<R: Rng + ?Sized>(
+        &self,
+        _population: &P,
+        _num_children: usize,
+        _rng: &mut R,
+    ) -> Result<Option<Vec<Self::Prepared>>> {
+        Ok(None)
+    }
 }
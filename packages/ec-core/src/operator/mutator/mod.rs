@@ -1,15 +1,16 @@
 use anyhow::Result;
-use rand::rngs::ThreadRng;
+use rand::Rng;
 
-use super::{Composable, Operator};
+use super::{Composable, Describe, Description, Operator};
 
 pub trait Mutator<G> {
     /// # Errors
     /// This can return an error if there is an error mutating the given
     /// genome.
-    fn mutate(&self, genome: G, rng: &mut ThreadRng) -> Result<G>;
+    fn mutate<R: Rng + ?Sized>(&self, genome: G, rng: &mut R) -> Result<G>;
 }
 
+#[derive(Clone)]
 pub struct Mutate<M> {
     mutator: M,
 }
@@ -26,8 +27,17 @@ where
 {
     type Output = G;
 
-    fn apply(&self, genome: G, rng: &mut ThreadRng) -> Result<Self::Output> {
+    fn apply<R: Rng + ?Sized>(&self, genome: G, rng: &mut R) -> Result<Self::Output> {
         self.mutator.mutate(genome, rng)
     }
 }
 impl<M> Composable for Mutate<M> {}
+
+impl<M> Describe for Mutate<M>
+where
+    M: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("Mutate").with_child(self.mutator.describe())
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
-use rand::rngs::ThreadRng;
+use rand::Rng;
 
-use super::{Composable, Operator};
+use super::{Composable, Describe, Description, Operator};
 
 /// Recombine (usually two or more) genomes into a new
 /// genome.
@@ -19,9 +19,10 @@ pub trait Recombinator<GS> {
     /// # Errors
     /// This will return an error if there's some problem with the
     /// recombination.
-    fn recombine(&self, genomes: GS, rng: &mut ThreadRng) -> Result<Self::Output>;
+    fn recombine<R: Rng + ?Sized>(&self, genomes: GS, rng: &mut R) -> Result<Self::Output>;
 }
 
+#[derive(Clone)]
 pub struct Recombine<R> {
     recombinator: R,
 }
@@ -32,14 +33,23 @@ impl<R> Recombine<R> {
     }
 }
 
-impl<R, G> Operator<G> for Recombine<R>
+impl<Rec, G> Operator<G> for Recombine<Rec>
 where
-    R: Recombinator<G>,
+    Rec: Recombinator<G>,
 {
-    type Output = R::Output;
+    type Output = Rec::Output;
 
-    fn apply(&self, genomes: G, rng: &mut ThreadRng) -> Result<Self::Output> {
+    fn apply<R: Rng + ?Sized>(&self, genomes: G, rng: &mut R) -> Result<Self::Output> {
         self.recombinator.recombine(genomes, rng)
     }
 }
 impl<R> Composable for Recombine<R> {}
+
+impl<R> Describe for Recombine<R>
+where
+    R: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("Recombine").with_child(self.recombinator.describe())
+    }
+}
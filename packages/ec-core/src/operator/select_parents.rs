@@ -0,0 +1,271 @@
+//! Selecting the parents of a whole generation together.
+//!
+//! A child maker like `Select::new(selector).then(...)` selects each
+//! child's parents as the child is made, so batch selectors like
+//! [stochastic universal sampling](super::selector::stochastic_universal)
+//! never get to choose the parents together. [`SelectParents`] instead
+//! selects `N` parents for every child with a single call to
+//! [`Selector::select_many`] when a `Generation` [prepares its
+//! children](Prepare::prepare_children), and hands `N` of them to each
+//! child, e.g.,
+//!
+//! ```ignore
+//! SelectParents::<_, _, 2>::new(
+//!     StochasticUniversal::new(Roulette),
+//!     Map::new(GenomeExtractor)
+//!         .then(Recombine::new(TwoPointXo))
+//!         .wrap::<GenomeScorer<_, _>>(scorer),
+//! )
+//! ```
+
+use std::{ptr, sync::Arc};
+
+use anyhow::{anyhow, Context, Result};
+use rand::{prelude::SliceRandom, Rng};
+
+use super::{
+    selector::{PreparedSelect, Select, Selector},
+    Composable, Describe, Description, Operator, Prepare,
+};
+use crate::population::Population;
+
+/// A child maker that gives `N` selected parents (as an `[&Individual; N]`)
+/// to `make_child`.
+///
+/// In a `Generation` the parents of all the children are selected together
+/// with `select_many`, and then shuffled, so batch selectors like stochastic
+/// universal sampling can spread the selections over the population.
+/// Anywhere else (e.g., if the child maker is only [prepared](Prepare::prepare)
+/// once) each child's parents are selected as it's made.
+pub struct SelectParents<S, M, const N: usize> {
+    select: Select<S>,
+    make_child: Arc<M>,
+}
+
+impl<S, M, const N: usize> SelectParents<S, M, N> {
+    pub fn new(selector: S, make_child: M) -> Self {
+        Self {
+            select: Select::new(selector),
+            make_child: Arc::new(make_child),
+        }
+    }
+}
+
+impl<S, M, const N: usize> Clone for SelectParents<S, M, N> {
+    fn clone(&self) -> Self {
+        Self {
+            select: self.select.clone(),
+            make_child: Arc::clone(&self.make_child),
+        }
+    }
+}
+
+impl<S, M, const N: usize> Describe for SelectParents<S, M, N>
+where
+    S: Describe,
+    M: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("SelectParents")
+            .with_parameter("num_parents", N)
+            .with_flattened_child(self.select.describe())
+            .with_child(self.make_child.describe())
+    }
+}
+
+impl<P, S, M, const N: usize> Prepare<P> for SelectParents<S, M, N>
+where
+    P: Population + AsRef<[P::Individual]>,
+    S: Selector<P>,
+{
+    type Prepared = PreparedSelectParents<S, M, P, N>;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        Ok(PreparedSelectParents {
+            select: self.select.prepare(population)?,
+            make_child: Arc::clone(&self.make_child),
+            parents: None,
+        })
+    }
+
+    fn prepare_children<R: Rng + ?Sized>(
+        &self,
+        population: &P,
+        num_children: usize,
+        rng: &mut R,
+    ) -> Result<Option<Vec<Self::Prepared>>> {
+        let select = self.select.prepare(population)?;
+        let mut parents = select.select_many(population, num_children * N, rng)?;
+        // Batch selectors may return the parents in order (e.g., with all
+        // the copies of an individual together), so they're shuffled to pair
+        // them up at random.
+        parents.shuffle(rng);
+        let individuals = population.as_ref();
+        let indices = parents
+            .into_iter()
+            .map(|parent| index_of(individuals, parent))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(
+            (0..num_children)
+                .map(|child| {
+                    let parents = indices
+                        .get(child * N..(child + 1) * N)
+                        .context("The selector returned too few parents")?;
+                    Ok(PreparedSelectParents {
+                        select: select.clone(),
+                        make_child: Arc::clone(&self.make_child),
+                        parents: Some(into_array(parents.to_vec())?),
+                    })
+                })
+                .collect::<Result<_>>()?,
+        ))
+    }
+}
+
+/// A [`SelectParents`] that has been prepared for a population, and
+/// possibly for a particular child whose parents have already been
+/// selected.
+pub struct PreparedSelectParents<S, M, P, const N: usize> {
+    select: PreparedSelect<S, P>,
+    make_child: Arc<M>,
+    /// The indices of this child's parents in the population, if they've
+    /// already been selected.
+    parents: Option<[usize; N]>,
+}
+
+impl<'pop, P, S, M, const N: usize> Operator<&'pop P> for PreparedSelectParents<S, M, P, N>
+where
+    P: Population + AsRef<[P::Individual]>,
+    S: Selector<P>,
+    M: Operator<[&'pop P::Individual; N]>,
+{
+    type Output = M::Output;
+
+    fn apply<R: Rng + ?Sized>(&self, population: &'pop P, rng: &mut R) -> Result<Self::Output> {
+        let parents = match &self.parents {
+            Some(indices) => {
+                let individuals = population.as_ref();
+                indices
+                    .iter()
+                    .map(|&index| {
+                        individuals.get(index).with_context(|| {
+                            format!(
+                                "Parent {index} was selected, but the population only has {} \
+                                 individuals",
+                                individuals.len()
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            }
+            None => self.select.select_many(population, N, rng)?,
+        };
+        self.make_child.apply(into_array(parents)?, rng)
+    }
+}
+impl<S, M, P, const N: usize> Composable for PreparedSelectParents<S, M, P, N> {}
+
+/// The position of `individual` (which must be a reference into
+/// `individuals`) in `individuals`.
+fn index_of<I>(individuals: &[I], individual: &I) -> Result<usize> {
+    ptr::from_ref(individual)
+        .addr()
+        .checked_sub(individuals.as_ptr().addr())
+        .and_then(|offset| offset.checked_div(size_of::<I>()))
+        .filter(|&index| {
+            individuals
+                .get(index)
+                .is_some_and(|candidate| ptr::eq(candidate, individual))
+        })
+        // Zero-sized individuals all have the same address.
+        .or_else(|| {
+            individuals
+                .iter()
+                .position(|candidate| ptr::eq(candidate, individual))
+        })
+        .context("The selector returned an individual that isn't in the population")
+}
+
+fn into_array<T, const N: usize>(values: Vec<T>) -> Result<[T; N]> {
+    let num_values = values.len();
+    values
+        .try_into()
+        .map_err(|_| anyhow!("Expected {N} parents, but {num_values} were selected"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::operator::selector::random::Random;
+
+    /// Selects randomly, counting the calls to `select_many`.
+    struct CountBatches<'a>(&'a AtomicUsize);
+
+    impl Selector<Vec<i32>> for CountBatches<'_> {
+        fn select<'pop, R: Rng + ?Sized>(
+            &self,
+            population: &'pop Vec<i32>,
+            rng: &mut R,
+        ) -> Result<&'pop i32> {
+            Random.select(population, rng)
+        }
+
+        fn select_many<'pop, R: Rng + ?Sized>(
+            &self,
+            population: &'pop Vec<i32>,
+            count: usize,
+            rng: &mut R,
+        ) -> Result<Vec<&'pop i32>> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            (0..count).map(|_| Random.select(population, rng)).collect()
+        }
+    }
+
+    struct Sum;
+
+    impl Operator<[&i32; 2]> for Sum {
+        type Output = i32;
+
+        fn apply<R: Rng + ?Sized>(&self, [x, y]: [&i32; 2], _: &mut R) -> Result<i32> {
+            Ok(x + y)
+        }
+    }
+    impl Composable for Sum {}
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn children_share_one_batch_of_parents() {
+        let num_batches = AtomicUsize::new(0);
+        let select_parents = SelectParents::<_, _, 2>::new(CountBatches(&num_batches), Sum);
+        let population: Vec<i32> = (0..10).map(|n| n * 100).collect();
+        let mut rng = StdRng::seed_from_u64(0);
+        let children = select_parents
+            .prepare_children(&population, 5, &mut rng)
+            .unwrap()
+            .unwrap();
+        assert_eq!(1, num_batches.load(Ordering::Relaxed));
+        assert_eq!(5, children.len());
+        for child in &children {
+            let sum = child.apply(&population, &mut rng).unwrap();
+            assert!((0..=1800).contains(&sum) && sum % 100 == 0, "{sum}");
+        }
+        // The parents were chosen in the batch.
+        assert_eq!(1, num_batches.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn parents_have_to_be_in_the_population() {
+        let population = [1, 2, 3];
+        assert_eq!(
+            2,
+            index_of(&population, &population[2])
+                .ok()
+                .unwrap_or_default()
+        );
+        assert!(index_of(&population, &3).is_err());
+    }
+}
@@ -1,18 +1,36 @@
 use anyhow::{Context, Result};
-use rand::rngs::ThreadRng;
+use rand::Rng;
 
 use super::Selector;
-use crate::population::Population;
+use crate::{
+    individual::comparator::Comparator,
+    operator::{Describe, Description, Prepare},
+    population::Population,
+};
 
+#[derive(Debug, Clone, Copy)]
 pub struct Best;
 
+impl Best {
+    /// Select the best individual according to `comparator` instead of
+    /// the individuals' `Ord` implementation.
+    #[must_use]
+    pub const fn by<C>(comparator: C) -> BestBy<C> {
+        BestBy { comparator }
+    }
+}
+
 impl<P> Selector<P> for Best
 where
     P: Population,
     for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
     P::Individual: Ord,
 {
-    fn select<'pop>(&self, population: &'pop P, _: &mut ThreadRng) -> Result<&'pop P::Individual> {
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        _: &mut R,
+    ) -> Result<&'pop P::Individual> {
         population
             .into_iter()
             .max()
@@ -20,9 +38,63 @@ where
     }
 }
 
+impl<P> Prepare<P> for Best {
+    type Prepared = Self;
+
+    fn prepare(&self, _: &P) -> Result<Self> {
+        Ok(*self)
+    }
+}
+
+impl Describe for Best {
+    fn describe(&self) -> Description {
+        Description::new("Best")
+    }
+}
+
+/// Select the best individual according to a [`Comparator`]; see
+/// [`Best::by`].
+#[derive(Debug, Clone, Copy)]
+pub struct BestBy<C> {
+    comparator: C,
+}
+
+impl<P, C> Selector<P> for BestBy<C>
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    C: Comparator<P::Individual>,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        _: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        population
+            .into_iter()
+            .max_by(|x, y| self.comparator.compare(x, y))
+            .context("The population was empty")
+    }
+}
+
+impl<P, C: Clone> Prepare<P> for BestBy<C> {
+    type Prepared = Self;
+
+    fn prepare(&self, _: &P) -> Result<Self> {
+        Ok(self.clone())
+    }
+}
+
+impl<C: Describe> Describe for BestBy<C> {
+    fn describe(&self) -> Description {
+        Description::new("Best").with_child(self.comparator.describe())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::individual::{comparator::LexicographicParsimony, ec::EcIndividual};
 
     #[test]
     #[allow(clippy::unwrap_used)]
@@ -32,4 +104,20 @@ mod tests {
         assert_eq!(&9, Best.select(&pop, &mut rng).unwrap());
         assert_eq!(&9, Best.select(&pop, &mut rng).unwrap());
     }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn best_by_parsimony_prefers_smaller_genomes() {
+        let pop = vec![
+            EcIndividual::new(vec![0; 3], 9),
+            EcIndividual::new(vec![0; 1], 9),
+            EcIndividual::new(vec![0; 2], 9),
+            EcIndividual::new(vec![0; 1], 4),
+        ];
+        let mut rng = rand::thread_rng();
+        let best = Best::by(LexicographicParsimony)
+            .select(&pop, &mut rng)
+            .unwrap();
+        assert_eq!((best.genome.len(), best.test_results), (1, 9));
+    }
 }
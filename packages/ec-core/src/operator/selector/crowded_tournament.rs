@@ -0,0 +1,149 @@
+use anyhow::{ensure, Context, Result};
+use num_traits::ToPrimitive;
+use rand::{seq::index::sample, Rng};
+
+use super::{PreparedSelector, Selector};
+use crate::{
+    individual::Individual,
+    operator::{Describe, Description, Prepare},
+    pareto::ParetoRanking,
+    population::Population,
+    test_results::TestResults,
+};
+
+/// NSGA-II's crowded tournament selection: the winner of the tournament is
+/// the individual in the best non-dominated front, with ties broken in
+/// favor of the less crowded individual.
+///
+/// Ranking the population requires sorting it into non-dominated fronts.
+/// Using this directly as a `Selector` does that on every selection;
+/// [`Generation`](crate::generation::Generation) instead
+/// [prepares](Prepare) it once per generation.
+#[derive(Debug, Clone, Copy)]
+pub struct CrowdedTournament {
+    size: usize,
+}
+
+impl CrowdedTournament {
+    #[must_use]
+    pub const fn new(size: usize) -> Self {
+        Self { size }
+    }
+}
+
+impl<P, T> Prepare<P> for CrowdedTournament
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual<TestResults = TestResults<T>>,
+    T: Ord + ToPrimitive,
+{
+    type Prepared = PreparedCrowdedTournament;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        let results: Vec<_> = population
+            .into_iter()
+            .map(Individual::test_results)
+            .collect();
+        Ok(PreparedCrowdedTournament {
+            size: self.size,
+            ranking: ParetoRanking::new(&results)?,
+        })
+    }
+}
+
+impl<P, T> Selector<P> for CrowdedTournament
+where
+    P: Population + AsRef<[P::Individual]>,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual<TestResults = TestResults<T>>,
+    T: Ord + ToPrimitive,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        self.prepare(population)?.select(population, rng)
+    }
+
+    fn prepare_selector(&self, population: &P) -> Result<Option<PreparedSelector<P>>> {
+        PreparedSelector::prepare(self, population).map(Some)
+    }
+}
+
+/// A [`CrowdedTournament`] selector whose Pareto ranking has been computed
+/// for a particular population. This should only be used to select from
+/// the population it was prepared with.
+#[derive(Debug, Clone)]
+pub struct PreparedCrowdedTournament {
+    size: usize,
+    ranking: ParetoRanking,
+}
+
+impl PreparedCrowdedTournament {
+    #[must_use]
+    pub const fn ranking(&self) -> &ParetoRanking {
+        &self.ranking
+    }
+}
+
+impl<P> Selector<P> for PreparedCrowdedTournament
+where
+    P: Population + AsRef<[P::Individual]>,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        let individuals = population.as_ref();
+        ensure!(
+            individuals.len() == self.ranking.ranks().len(),
+            "This selector was prepared for a population of size {}, not {}",
+            self.ranking.ranks().len(),
+            individuals.len()
+        );
+        ensure!(
+            individuals.len() >= self.size,
+            "The population had size {} and we wanted a tournament of size {}",
+            individuals.len(),
+            self.size
+        );
+        let winner = sample(rng, individuals.len(), self.size)
+            .into_iter()
+            .max_by(|&a, &b| self.ranking.crowded_cmp(a, b))
+            .with_context(|| format!("The tournament was empty; should have been {}", self.size))?;
+        Ok(&individuals[winner])
+    }
+}
+
+impl Describe for CrowdedTournament {
+    fn describe(&self) -> Description {
+        Description::new("CrowdedTournament").with_parameter("size", self.size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{individual::ec::EcIndividual, test_results::Error};
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn full_tournament_picks_an_extreme_of_the_first_front() {
+        let population: Vec<_> = [[0, 4], [1, 2], [2, 1], [4, 0], [3, 3]]
+            .into_iter()
+            .enumerate()
+            .map(|(genome, objectives)| {
+                EcIndividual::<_, TestResults<Error<i32>>>::new(genome, objectives.to_vec().into())
+            })
+            .collect();
+        let selector = CrowdedTournament::new(5).prepare(&population).unwrap();
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let winner = selector.select(&population, &mut rng).unwrap().genome;
+            assert!(winner == 0 || winner == 3);
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::{ensure, Context, Result};
+use rand::{prelude::IndexedRandom, Rng};
+
+use super::Selector;
+use crate::{
+    genome::GenomeSize,
+    individual::Individual,
+    operator::{Describe, Description, Prepare},
+    population::Population,
+};
+
+/// Double tournament selection (Luke & Panait), a form of bloat control: a
+/// fitness tournament whose contestants are the winners of size
+/// tournaments.
+///
+/// Each size tournament chooses two individuals at random, and the smaller
+/// one wins with probability `parsimony_pressure / 2`. The parsimony
+/// pressure is between 1 (size doesn't matter) and 2 (the smaller one always
+/// wins); Luke & Panait found values around 1.4 to work well.
+#[derive(Debug, Clone, Copy)]
+pub struct DoubleTournament {
+    fitness_size: usize,
+    parsimony_pressure: f64,
+}
+
+impl DoubleTournament {
+    #[must_use]
+    pub const fn new(fitness_size: usize, parsimony_pressure: f64) -> Self {
+        Self {
+            fitness_size,
+            parsimony_pressure,
+        }
+    }
+
+    fn size_tournament<'pop, I, R>(&self, population: &'pop [I], rng: &mut R) -> Result<&'pop I>
+    where
+        I: Individual,
+        I::Genome: GenomeSize,
+        R: Rng + ?Sized,
+    {
+        let mut contestants = population.choose_multiple(rng, 2);
+        let first = contestants
+            .next()
+            .context("The population for a size tournament was empty")?;
+        let second = contestants.next().unwrap_or(first);
+        let (smaller, larger) = if first.genome().genome_size() <= second.genome().genome_size() {
+            (first, second)
+        } else {
+            (second, first)
+        };
+        Ok(if rng.gen_bool(self.parsimony_pressure / 2.0) {
+            smaller
+        } else {
+            larger
+        })
+    }
+}
+
+impl<P> Selector<P> for DoubleTournament
+where
+    P: Population + AsRef<[P::Individual]>,
+    P::Individual: Individual + Ord,
+    <P::Individual as Individual>::Genome: GenomeSize,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        ensure!(
+            (1.0..=2.0).contains(&self.parsimony_pressure),
+            "The parsimony pressure must be between 1 and 2, but was {}",
+            self.parsimony_pressure
+        );
+        ensure!(
+            self.fitness_size > 0,
+            "The fitness tournament must have at least one contestant"
+        );
+        (0..self.fitness_size)
+            .map(|_| self.size_tournament(population.as_ref(), rng))
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .max()
+            .context("The fitness tournament was empty")
+    }
+}
+
+impl<P> Prepare<P> for DoubleTournament {
+    type Prepared = Self;
+
+    fn prepare(&self, _: &P) -> Result<Self> {
+        Ok(*self)
+    }
+}
+
+impl Describe for DoubleTournament {
+    fn describe(&self) -> Description {
+        Description::new("DoubleTournament")
+            .with_parameter("fitness_size", self.fitness_size)
+            .with_parameter("parsimony_pressure", self.parsimony_pressure)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::individual::ec::EcIndividual;
+
+    type Scored = EcIndividual<Vec<u8>, i32>;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn full_parsimony_pressure_always_picks_the_smaller() {
+        // The larger individual is fitter, but never makes it past the size
+        // tournament when there are only two individuals.
+        let population: Vec<Scored> = vec![
+            EcIndividual::new(vec![0; 10], 100),
+            EcIndividual::new(vec![0; 2], 1),
+        ];
+        let selector = DoubleTournament::new(3, 2.0);
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            assert_eq!(
+                selector.select(&population, &mut rng).unwrap().test_results,
+                1
+            );
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn no_parsimony_pressure_ignores_size() {
+        let population: Vec<Scored> = vec![
+            EcIndividual::new(vec![0; 10], 100),
+            EcIndividual::new(vec![0; 2], 1),
+        ];
+        let selector = DoubleTournament::new(3, 1.0);
+        let mut rng = StdRng::seed_from_u64(0);
+        let num_larger = (0..1000)
+            .filter(|_| selector.select(&population, &mut rng).unwrap().test_results == 100)
+            .count();
+        // The larger one loses only if it loses all three size tournaments.
+        assert!((800..950).contains(&num_larger), "{num_larger}");
+    }
+
+    #[test]
+    fn invalid_parsimony_pressure_is_an_error() {
+        let population: Vec<Scored> = vec![EcIndividual::new(vec![0], 1)];
+        let mut rng = StdRng::seed_from_u64(0);
+        assert!(DoubleTournament::new(2, 2.5)
+            .select(&population, &mut rng)
+            .is_err());
+    }
+}
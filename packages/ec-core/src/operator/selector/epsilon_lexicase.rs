@@ -0,0 +1,396 @@
+use std::mem::swap;
+
+use anyhow::{ensure, Context, Result};
+use num_traits::ToPrimitive;
+use rand::{
+    prelude::{IndexedRandom, SliceRandom},
+    Rng,
+};
+
+use super::{PreparedSelector, Selector};
+use crate::{
+    individual::Individual,
+    operator::{Describe, Description, Prepare},
+    population::Population,
+    test_results::TestResults,
+};
+
+/// How close to the best result on a test case a candidate has to be to
+/// survive that case.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Epsilon {
+    /// The same epsilon is used for every test case.
+    Fixed(f64),
+    /// Each test case gets its own epsilon, which is the median absolute
+    /// deviation of the results on that case.
+    MedianAbsoluteDeviation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// Candidates survive a case if they're within epsilon of the best
+    /// remaining candidate, where a median absolute deviation epsilon is
+    /// computed from the results of the whole population.
+    SemiDynamic,
+    /// Like `SemiDynamic`, except that a median absolute deviation
+    /// epsilon is recomputed at each step from the results of the
+    /// remaining candidates.
+    Dynamic,
+}
+
+/// Epsilon-lexicase selection (La Cava et al.), a version of lexicase for
+/// real-valued results where exact ties are rare.
+///
+/// Computing the per-case epsilons requires a pass over the entire
+/// population. Using this directly as a `Selector` does that on every
+/// selection; [`Generation`](crate::generation::Generation) instead
+/// [prepares](Prepare) it once per generation.
+#[derive(Debug, Clone, Copy)]
+pub struct EpsilonLexicase {
+    num_test_cases: usize,
+    epsilon: Epsilon,
+    variant: Variant,
+}
+
+impl EpsilonLexicase {
+    /// Semi-dynamic epsilon-lexicase with per-case epsilons computed from
+    /// the median absolute deviation of the population's results.
+    #[must_use]
+    pub const fn new(num_test_cases: usize) -> Self {
+        Self {
+            num_test_cases,
+            epsilon: Epsilon::MedianAbsoluteDeviation,
+            variant: Variant::SemiDynamic,
+        }
+    }
+
+    #[must_use]
+    pub const fn with_epsilon(mut self, epsilon: Epsilon) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+}
+
+impl<P, T> Prepare<P> for EpsilonLexicase
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual<TestResults = TestResults<T>>,
+    T: ToPrimitive,
+{
+    type Prepared = PreparedEpsilonLexicase;
+
+    /// Compute everything that depends only on the population (i.e., the
+    /// per-case epsilons), returning a selector that can be used for all
+    /// the selections from this population.
+    ///
+    /// # Errors
+    /// This returns an error if any result can't be converted to an `f64`,
+    /// or if an individual has too few test results.
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        let epsilons = match (self.epsilon, self.variant) {
+            (Epsilon::Fixed(epsilon), _) => {
+                CaseEpsilons::PerCase(vec![epsilon; self.num_test_cases])
+            }
+            (Epsilon::MedianAbsoluteDeviation, Variant::SemiDynamic) => {
+                let mut values = Vec::with_capacity(population.size());
+                let epsilons = (0..self.num_test_cases)
+                    .map(|case| {
+                        values.clear();
+                        for individual in population {
+                            values.push(case_value(individual, case)?);
+                        }
+                        Ok(median_absolute_deviation(&mut values))
+                    })
+                    .collect::<Result<_>>()?;
+                CaseEpsilons::PerCase(epsilons)
+            }
+            (Epsilon::MedianAbsoluteDeviation, Variant::Dynamic) => CaseEpsilons::FromCandidates,
+        };
+        Ok(PreparedEpsilonLexicase {
+            num_test_cases: self.num_test_cases,
+            epsilons,
+        })
+    }
+}
+
+impl<P, T> Selector<P> for EpsilonLexicase
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual<TestResults = TestResults<T>>,
+    T: Ord + ToPrimitive,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        self.prepare(population)?.select(population, rng)
+    }
+
+    fn prepare_selector(&self, population: &P) -> Result<Option<PreparedSelector<P>>> {
+        PreparedSelector::prepare(self, population).map(Some)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum CaseEpsilons {
+    PerCase(Vec<f64>),
+    FromCandidates,
+}
+
+/// An [`EpsilonLexicase`] selector whose per-case epsilons have been
+/// computed for a particular population. This should only be used to
+/// select from the population it was prepared with.
+#[derive(Debug, Clone)]
+pub struct PreparedEpsilonLexicase {
+    num_test_cases: usize,
+    epsilons: CaseEpsilons,
+}
+
+impl PreparedEpsilonLexicase {
+    /// The per-case epsilons, or `None` for dynamic epsilon-lexicase with
+    /// median absolute deviation epsilons, where they're computed during
+    /// each selection.
+    #[must_use]
+    pub fn epsilons(&self) -> Option<&[f64]> {
+        match &self.epsilons {
+            CaseEpsilons::PerCase(epsilons) => Some(epsilons),
+            CaseEpsilons::FromCandidates => None,
+        }
+    }
+}
+
+impl<P, T> Selector<P> for PreparedEpsilonLexicase
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual<TestResults = TestResults<T>>,
+    T: Ord + ToPrimitive,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        let mut case_indices: Vec<usize> = (0..self.num_test_cases).collect();
+        case_indices.shuffle(rng);
+
+        let mut candidates: Vec<_> = population.into_iter().collect();
+        let mut winners = Vec::with_capacity(candidates.len());
+        let mut values = Vec::with_capacity(candidates.len());
+        // Scratch space for the median absolute deviation, which reorders
+        // and overwrites the values it's given.
+        let mut deviations = Vec::new();
+        for test_case_index in case_indices {
+            ensure!(
+                !candidates.is_empty(),
+                "The set of epsilon-lexicase candidates shouldn't be empty"
+            );
+            if candidates.len() == 1 {
+                break;
+            }
+            values.clear();
+            for candidate in &candidates {
+                values.push(case_value(*candidate, test_case_index)?);
+            }
+            let best = candidates
+                .iter()
+                .map(|candidate| &candidate.test_results().results[test_case_index])
+                .max()
+                .context("The set of epsilon-lexicase candidates was empty")?;
+            let best_value = best
+                .to_f64()
+                .context("A test result couldn't be converted to an f64")?;
+            let epsilon = match &self.epsilons {
+                CaseEpsilons::PerCase(epsilons) => epsilons[test_case_index],
+                CaseEpsilons::FromCandidates => {
+                    deviations.clone_from(&values);
+                    median_absolute_deviation(&mut deviations)
+                }
+            };
+            // The median absolute deviation is NaN if more than half the
+            // values are NaN, and would let everything through if it were
+            // infinite, so those fall back on exact lexicase.
+            let epsilon = if epsilon.is_finite() { epsilon } else { 0.0 };
+
+            // The candidates that tie with the best are always kept, which
+            // guarantees that at least one candidate survives even if the
+            // best value is NaN or infinite (where `best_value - value` is
+            // NaN or infinite for every candidate).
+            winners.clear();
+            winners.extend(
+                candidates
+                    .iter()
+                    .zip(&values)
+                    .filter(|(candidate, &value)| {
+                        candidate.test_results().results[test_case_index] == *best
+                            || (best_value - value).abs() <= epsilon
+                    })
+                    .map(|(&candidate, _)| candidate),
+            );
+            swap(&mut candidates, &mut winners);
+        }
+
+        candidates
+            .choose(rng)
+            .copied()
+            .context("The pool of candidates was empty")
+    }
+}
+
+fn case_value<I, T>(individual: &I, test_case_index: usize) -> Result<f64>
+where
+    I: Individual<TestResults = TestResults<T>>,
+    T: ToPrimitive,
+{
+    individual
+        .test_results()
+        .results
+        .get(test_case_index)
+        .with_context(|| format!("An individual had no result for test case {test_case_index}"))?
+        .to_f64()
+        .context("A test result couldn't be converted to an f64")
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_unstable_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        f64::midpoint(values[middle - 1], values[middle])
+    } else {
+        values[middle]
+    }
+}
+
+/// The median of the absolute differences between each value and the
+/// median of all the values. This reorders `values`.
+fn median_absolute_deviation(values: &mut [f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let median_value = median(values);
+    for value in values.iter_mut() {
+        *value = (*value - median_value).abs();
+    }
+    median(values)
+}
+
+impl Describe for EpsilonLexicase {
+    fn describe(&self) -> Description {
+        Description::new("EpsilonLexicase")
+            .with_parameter("num_test_cases", self.num_test_cases)
+            .with_parameter("epsilon", format!("{:?}", self.epsilon))
+            .with_parameter("variant", format!("{:?}", self.variant))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{individual::ec::EcIndividual, test_results::Error};
+
+    type TestIndividual = EcIndividual<usize, TestResults<Error<i32>>>;
+
+    fn population() -> Vec<TestIndividual> {
+        vec![
+            EcIndividual::new(0, vec![0, 10, 5].into()),
+            EcIndividual::new(1, vec![1, 11, 6].into()),
+            EcIndividual::new(2, vec![100, 100, 100].into()),
+        ]
+    }
+
+    #[test]
+    fn median_absolute_deviation_ignores_outliers() {
+        assert!(
+            (median_absolute_deviation(&mut [1.0, 2.0, 3.0, 4.0, 100.0]) - 1.0).abs()
+                < f64::EPSILON
+        );
+        assert!((median_absolute_deviation(&mut [1.0, 2.0, 4.0, 6.0]) - 1.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn semi_dynamic_epsilons_are_computed_per_case() {
+        let prepared = EpsilonLexicase::new(3).prepare(&population()).unwrap();
+        assert_eq!(Some(&[1.0, 1.0, 1.0][..]), prepared.epsilons());
+        let dynamic = EpsilonLexicase::new(3)
+            .with_variant(Variant::Dynamic)
+            .prepare(&population())
+            .unwrap();
+        assert_eq!(None, dynamic.epsilons());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn zero_epsilon_is_exact_lexicase() {
+        let population = population();
+        let selector = EpsilonLexicase::new(3).with_epsilon(Epsilon::Fixed(0.0));
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            assert_eq!(0, selector.select(&population, &mut rng).unwrap().genome);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn individuals_within_epsilon_can_be_selected() {
+        let population = population();
+        let mut rng = rand::thread_rng();
+        let selector = EpsilonLexicase::new(3).prepare(&population).unwrap();
+        let selected: Vec<_> = (0..100)
+            .map(|_| selector.select(&population, &mut rng).unwrap().genome)
+            .collect();
+        assert!(selected.contains(&0));
+        assert!(selected.contains(&1));
+        assert!(!selected.contains(&2));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn nan_and_infinite_results_leave_a_candidate() {
+        type FloatIndividual = EcIndividual<usize, TestResults<Error<f64>>>;
+        let nan = f64::NAN;
+        let inf = f64::INFINITY;
+        // The first case has a NaN median absolute deviation, the second
+        // has no finite results, and the third is all NaN.
+        let population: Vec<FloatIndividual> = vec![
+            EcIndividual::new(0, vec![nan, inf, nan].into()),
+            EcIndividual::new(1, vec![nan, inf, nan].into()),
+            EcIndividual::new(2, vec![3.0, inf, nan].into()),
+        ];
+        let mut rng = rand::thread_rng();
+        for variant in [Variant::SemiDynamic, Variant::Dynamic] {
+            for epsilon in [Epsilon::MedianAbsoluteDeviation, Epsilon::Fixed(nan)] {
+                let selector = EpsilonLexicase::new(3)
+                    .with_epsilon(epsilon)
+                    .with_variant(variant);
+                for _ in 0..20 {
+                    assert_eq!(2, selector.select(&population, &mut rng).unwrap().genome);
+                }
+            }
+        }
+
+        // When every result is NaN or infinite, the ties with the best
+        // survive.
+        let population: Vec<FloatIndividual> = vec![
+            EcIndividual::new(0, vec![nan, inf].into()),
+            EcIndividual::new(1, vec![nan, -inf].into()),
+            EcIndividual::new(2, vec![nan, -inf].into()),
+        ];
+        let selector = EpsilonLexicase::new(2).with_variant(Variant::Dynamic);
+        let selected: Vec<_> = (0..100)
+            .map(|_| selector.select(&population, &mut rng).unwrap().genome)
+            .collect();
+        assert!(selected.contains(&1));
+        assert!(selected.contains(&2));
+        assert!(!selected.contains(&0));
+    }
+}
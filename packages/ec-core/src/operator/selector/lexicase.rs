@@ -1,11 +1,17 @@
 use std::{mem::swap, ops::Not};
 
 use anyhow::{Context, Result};
-use rand::{prelude::SliceRandom, rngs::ThreadRng};
+use rand::{prelude::SliceRandom, Rng};
 
 use super::Selector;
-use crate::{individual::Individual, population::Population, test_results::TestResults};
+use crate::{
+    individual::Individual,
+    operator::{Describe, Description, Prepare},
+    population::Population,
+    test_results::TestResults,
+};
 
+#[derive(Debug, Clone, Copy)]
 pub struct Lexicase {
     num_test_cases: usize,
 }
@@ -17,7 +23,7 @@ impl Lexicase {
     }
 }
 
-impl<P, R> Selector<P> for Lexicase
+impl<P, T> Selector<P> for Lexicase
 where
     P: Population,
     // TODO: We don't really use the iterator here as we immediately
@@ -31,13 +37,13 @@ where
     //   bare `Vec`s and will be forced to wrap them like we currently
     //   do with `VecPop`.
     for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
-    P::Individual: Individual<TestResults = TestResults<R>>,
-    R: Ord,
+    P::Individual: Individual<TestResults = TestResults<T>>,
+    T: Ord,
 {
-    fn select<'pop>(
+    fn select<'pop, R: Rng + ?Sized>(
         &self,
         population: &'pop P,
-        rng: &mut ThreadRng,
+        rng: &mut R,
     ) -> Result<&'pop P::Individual> {
         // Candidate set is initially the whole population.
         // Shuffle the (indices of the) test cases.
@@ -89,3 +95,17 @@ where
             .context("The pool of candidates was empty")
     }
 }
+
+impl<P> Prepare<P> for Lexicase {
+    type Prepared = Self;
+
+    fn prepare(&self, _: &P) -> Result<Self> {
+        Ok(*self)
+    }
+}
+
+impl Describe for Lexicase {
+    fn describe(&self) -> Description {
+        Description::new("Lexicase").with_parameter("num_test_cases", self.num_test_cases)
+    }
+}
@@ -1,12 +1,24 @@
+use std::sync::Arc;
+
 use anyhow::Result;
-use rand::rngs::ThreadRng;
+use rand::{Rng, RngCore};
 
-use super::{Composable, Operator};
+use super::{Composable, Describe, Description, Operator, Prepare};
 use crate::population::Population;
 
 pub mod best;
+pub mod crowded_tournament;
+pub mod double_tournament;
+pub mod epsilon_lexicase;
+pub mod fast_lexicase;
 pub mod lexicase;
+pub mod novelty;
+pub mod proportional;
 pub mod random;
+pub mod rank;
+pub mod roulette;
+pub mod stochastic_universal;
+pub mod tarpeian;
 pub mod tournament;
 pub mod weighted;
 
@@ -18,18 +30,163 @@ where
     /// This will return an error if there's some problem selecting. That will
     /// usually be because the population is empty or not large enough for
     /// the desired selector.
-    fn select<'pop>(&self, population: &'pop P, rng: &mut ThreadRng)
-    -> Result<&'pop P::Individual>;
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual>;
+
+    /// Select `count` individuals from the population.
+    ///
+    /// By default this just calls `select` `count` times, but selectors
+    /// that choose a whole batch of individuals together, like stochastic
+    /// universal sampling, override it. A `Generation` whose child maker is
+    /// a [`SelectParents`](crate::operator::select_parents::SelectParents)
+    /// calls this once per generation to select all the parents.
+    ///
+    /// # Errors
+    /// This will return an error if any of the selections fail.
+    fn select_many<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        count: usize,
+        rng: &mut R,
+    ) -> Result<Vec<&'pop P::Individual>> {
+        (0..count).map(|_| self.select(population, rng)).collect()
+    }
+
+    /// Prepare this selector for making many selections from `population`.
+    ///
+    /// Selectors that do some work up front for each population, like
+    /// computing the weights for roulette selection, override this to return
+    /// a [`PreparedSelector`] that reuses that work for every selection. The
+    /// default returns `None`, meaning there's nothing to prepare and the
+    /// selector is used as is, so a custom selector only has to implement
+    /// `select` to be used in a [`Generation`](crate::generation::Generation).
+    ///
+    /// # Errors
+    /// This will return an error if the selector can't be prepared for this
+    /// population, e.g., because the population is empty.
+    fn prepare_selector(&self, _population: &P) -> Result<Option<PreparedSelector<P>>> {
+        Ok(None)
+    }
+}
+
+trait ErasedSelector<P: Population>: Send + Sync {
+    fn select<'pop>(
+        &self,
+        population: &'pop P,
+        rng: &mut dyn RngCore,
+    ) -> Result<&'pop P::Individual>;
+
+    fn select_many<'pop>(
+        &self,
+        population: &'pop P,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<&'pop P::Individual>>;
+}
+
+impl<P, S> ErasedSelector<P> for S
+where
+    P: Population,
+    S: Selector<P> + Send + Sync,
+{
+    fn select<'pop>(
+        &self,
+        population: &'pop P,
+        rng: &mut dyn RngCore,
+    ) -> Result<&'pop P::Individual> {
+        Selector::select(self, population, rng)
+    }
+
+    fn select_many<'pop>(
+        &self,
+        population: &'pop P,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Result<Vec<&'pop P::Individual>> {
+        Selector::select_many(self, population, count, rng)
+    }
+}
+
+/// A selector that has been prepared for a particular population by
+/// [`Selector::prepare_selector`].
+///
+/// The prepared selector's type is erased (the random number generator is
+/// passed along as a `&mut dyn RngCore`), so selectors of any type can be
+/// prepared without `Select` needing to name the result.
+pub struct PreparedSelector<P>(Arc<dyn ErasedSelector<P>>);
+
+impl<P: Population> PreparedSelector<P> {
+    pub fn new(selector: impl Selector<P> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(selector))
+    }
+
+    /// Prepare `selector` for `population` using its [`Prepare`]
+    /// implementation; this is how the built-in selectors implement
+    /// [`Selector::prepare_selector`].
+    ///
+    /// # Errors
+    /// This will return an error if preparing the selector fails.
+    pub fn prepare<S>(selector: &S, population: &P) -> Result<Self>
+    where
+        S: Prepare<P>,
+        S::Prepared: Selector<P> + Send + Sync + 'static,
+    {
+        Ok(Self::new(selector.prepare(population)?))
+    }
+
+    pub(crate) fn shared<S>(selector: Arc<S>) -> Self
+    where
+        S: Selector<P> + Send + Sync + 'static,
+    {
+        Self(selector)
+    }
+}
+
+impl<P> Clone for PreparedSelector<P> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<P: Population> Selector<P> for PreparedSelector<P> {
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        mut rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        self.0.select(population, &mut rng)
+    }
+
+    fn select_many<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        count: usize,
+        mut rng: &mut R,
+    ) -> Result<Vec<&'pop P::Individual>> {
+        self.0.select_many(population, count, &mut rng)
+    }
 }
 
-#[derive(Clone)]
 pub struct Select<S> {
-    selector: S,
+    selector: Arc<S>,
 }
 
 impl<S> Select<S> {
-    pub const fn new(selector: S) -> Self {
-        Self { selector }
+    pub fn new(selector: S) -> Self {
+        Self {
+            selector: Arc::new(selector),
+        }
+    }
+}
+
+impl<S> Clone for Select<S> {
+    fn clone(&self) -> Self {
+        Self {
+            selector: Arc::clone(&self.selector),
+        }
     }
 }
 
@@ -40,22 +197,139 @@ where
 {
     type Output = &'pop P::Individual;
 
-    fn apply(&self, population: &'pop P, rng: &mut ThreadRng) -> Result<Self::Output> {
+    fn apply<R: Rng + ?Sized>(&self, population: &'pop P, rng: &mut R) -> Result<Self::Output> {
         self.selector.select(population, rng)
     }
 }
 impl<S> Composable for Select<S> {}
 
+impl<S> Describe for Select<S>
+where
+    S: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("Select").with_child(self.selector.describe())
+    }
+}
+
+impl<P, S> Prepare<P> for Select<S>
+where
+    P: Population,
+    S: Selector<P>,
+{
+    type Prepared = PreparedSelect<S, P>;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        Ok(PreparedSelect {
+            selector: Arc::clone(&self.selector),
+            prepared: self.selector.prepare_selector(population)?,
+        })
+    }
+}
+
+/// A [`Select`] operator that has been prepared for a population, using
+/// the selector's [`PreparedSelector`] if it has one.
+pub struct PreparedSelect<S, P> {
+    selector: Arc<S>,
+    prepared: Option<PreparedSelector<P>>,
+}
+
+impl<'pop, P, S> Operator<&'pop P> for PreparedSelect<S, P>
+where
+    P: Population,
+    S: Selector<P>,
+{
+    type Output = &'pop P::Individual;
+
+    fn apply<R: Rng + ?Sized>(&self, population: &'pop P, rng: &mut R) -> Result<Self::Output> {
+        if let Some(prepared) = &self.prepared {
+            Selector::select(prepared, population, rng)
+        } else {
+            self.selector.select(population, rng)
+        }
+    }
+}
+impl<S, P> Composable for PreparedSelect<S, P> {}
+
+impl<S, P> Clone for PreparedSelect<S, P> {
+    fn clone(&self) -> Self {
+        Self {
+            selector: Arc::clone(&self.selector),
+            prepared: self.prepared.clone(),
+        }
+    }
+}
+
+impl<P, S> Selector<P> for PreparedSelect<S, P>
+where
+    P: Population,
+    S: Selector<P>,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        self.apply(population, rng)
+    }
+
+    fn select_many<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        count: usize,
+        rng: &mut R,
+    ) -> Result<Vec<&'pop P::Individual>> {
+        if let Some(prepared) = &self.prepared {
+            Selector::select_many(prepared, population, count, rng)
+        } else {
+            self.selector.select_many(population, count, rng)
+        }
+    }
+}
+
 impl<T, P> Selector<P> for &T
 where
     P: Population,
     T: Selector<P>,
 {
-    fn select<'pop>(
+    fn select<'pop, R: Rng + ?Sized>(
         &self,
         population: &'pop P,
-        rng: &mut ThreadRng,
+        rng: &mut R,
     ) -> Result<&'pop P::Individual> {
         (*self).select(population, rng)
     }
+
+    fn select_many<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        count: usize,
+        rng: &mut R,
+    ) -> Result<Vec<&'pop P::Individual>> {
+        (*self).select_many(population, count, rng)
+    }
+
+    fn prepare_selector(&self, population: &P) -> Result<Option<PreparedSelector<P>>> {
+        (*self).prepare_selector(population)
+    }
+}
+
+impl<T, P> Prepare<P> for &T
+where
+    T: Prepare<P>,
+{
+    type Prepared = T::Prepared;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        (*self).prepare(population)
+    }
+
+    fn prepare_children<R: Rng + ?Sized>(
+        &self,
+        population: &P,
+        num_children: usize,
+        rng: &mut R,
+    ) -> Result<Option<Vec<Self::Prepared>>> {
+        (*self).prepare_children(population, num_children, rng)
+    }
 }
@@ -0,0 +1,258 @@
+use anyhow::{ensure, Context, Result};
+use rand::{seq::index::sample, Rng};
+
+use super::{PreparedSelector, Selector};
+use crate::{
+    individual::Individual,
+    novelty::{Behavior, BehaviorDescriptor, NoveltyArchive},
+    operator::{Describe, Description, Prepare},
+    population::Population,
+    test_results::{Fitness, TestResults},
+};
+
+/// Tournament selection on novelty, or on a weighted blend of novelty and
+/// fitness.
+///
+/// The novelty of an individual is the sparseness of its behavior (from the
+/// [`BehaviorDescriptor`]) as measured by the [`NoveltyArchive`]. With a
+/// fitness weight `w`, the tournament is won by the individual with the
+/// largest `(1 - w) * novelty + w * fitness`, where both the novelty and
+/// the fitness (from [`Fitness`] on the total results) are rescaled to
+/// `[0, 1]` across the population. The default weight is 0, which is pure
+/// novelty search.
+///
+/// Preparing this selector adds behaviors from the population to the
+/// archive. [`Generation`](crate::generation::Generation) prepares it once
+/// per generation; using this directly as a `Selector` instead prepares it
+/// (and so updates the archive) on every selection.
+#[derive(Debug, Clone, Copy)]
+pub struct Novelty<'a, D, B> {
+    size: usize,
+    descriptor: D,
+    archive: &'a NoveltyArchive<B>,
+    fitness_weight: f64,
+}
+
+impl<'a, D, B> Novelty<'a, D, B> {
+    #[must_use]
+    pub const fn new(size: usize, descriptor: D, archive: &'a NoveltyArchive<B>) -> Self {
+        Self {
+            size,
+            descriptor,
+            archive,
+            fitness_weight: 0.0,
+        }
+    }
+
+    /// Blend novelty with fitness, where `fitness_weight` is between 0 (pure
+    /// novelty) and 1 (pure fitness).
+    #[must_use]
+    pub const fn with_fitness_weight(mut self, fitness_weight: f64) -> Self {
+        self.fitness_weight = fitness_weight;
+        self
+    }
+}
+
+impl<P, D, B, T> Prepare<P> for Novelty<'_, D, B>
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual<TestResults = TestResults<T>>,
+    D: BehaviorDescriptor<P::Individual, Behavior = B>,
+    B: Behavior,
+    T: Fitness,
+{
+    type Prepared = PreparedNovelty;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        ensure!(
+            (0.0..=1.0).contains(&self.fitness_weight),
+            "The fitness weight must be between 0 and 1, but was {}",
+            self.fitness_weight
+        );
+        let behaviors = population
+            .into_iter()
+            .map(|individual| self.descriptor.describe(individual))
+            .collect();
+        let mut scores = self.archive.assess(behaviors)?;
+        if self.fitness_weight > 0.0 {
+            let fitnesses = population
+                .into_iter()
+                .map(|individual| {
+                    individual
+                        .test_results()
+                        .total_result
+                        .fitness()
+                        .context("An individual's total result didn't have a valid fitness")
+                })
+                .collect::<Result<Vec<_>>>()?;
+            scores = rescaled(scores)
+                .into_iter()
+                .zip(rescaled(fitnesses))
+                .map(|(novelty, fitness)| {
+                    (1.0 - self.fitness_weight).mul_add(novelty, self.fitness_weight * fitness)
+                })
+                .collect();
+        }
+        Ok(PreparedNovelty {
+            size: self.size,
+            scores,
+        })
+    }
+}
+
+impl<P, D, B, T> Selector<P> for Novelty<'_, D, B>
+where
+    P: Population + AsRef<[P::Individual]>,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual<TestResults = TestResults<T>>,
+    D: BehaviorDescriptor<P::Individual, Behavior = B>,
+    B: Behavior,
+    T: Fitness,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        self.prepare(population)?.select(population, rng)
+    }
+
+    fn prepare_selector(&self, population: &P) -> Result<Option<PreparedSelector<P>>> {
+        PreparedSelector::prepare(self, population).map(Some)
+    }
+}
+
+/// Rescale the values so the smallest is 0 and the largest is 1; if they're
+/// all the same, they all become 0.
+fn rescaled(values: Vec<f64>) -> Vec<f64> {
+    let smallest = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let largest = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let range = largest - smallest;
+    values
+        .into_iter()
+        .map(|value| {
+            if range > 0.0 {
+                (value - smallest) / range
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+/// A [`Novelty`] selector whose scores have been computed for a particular
+/// population. This should only be used to select from the population it
+/// was prepared with.
+#[derive(Debug, Clone)]
+pub struct PreparedNovelty {
+    size: usize,
+    scores: Vec<f64>,
+}
+
+impl PreparedNovelty {
+    /// The score of each individual, which is either its novelty or the
+    /// blend of its novelty and fitness.
+    #[must_use]
+    pub fn scores(&self) -> &[f64] {
+        &self.scores
+    }
+}
+
+impl<P> Selector<P> for PreparedNovelty
+where
+    P: Population + AsRef<[P::Individual]>,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        let individuals = population.as_ref();
+        ensure!(
+            individuals.len() == self.scores.len(),
+            "This selector was prepared for a population of size {}, not {}",
+            self.scores.len(),
+            individuals.len()
+        );
+        ensure!(
+            individuals.len() >= self.size,
+            "The population had size {} and we wanted a tournament of size {}",
+            individuals.len(),
+            self.size
+        );
+        let winner = sample(rng, individuals.len(), self.size)
+            .into_iter()
+            .max_by(|&a, &b| self.scores[a].total_cmp(&self.scores[b]))
+            .with_context(|| format!("The tournament was empty; should have been {}", self.size))?;
+        Ok(&individuals[winner])
+    }
+}
+
+// The behavior descriptor is typically a closure, so it isn't described.
+impl<D, B> Describe for Novelty<'_, D, B> {
+    fn describe(&self) -> Description {
+        Description::new("Novelty")
+            .with_parameter("size", self.size)
+            .with_parameter("fitness_weight", self.fitness_weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        individual::ec::EcIndividual,
+        novelty::{ArchivePolicy, FnDescriptor},
+        test_results::Error,
+    };
+
+    // The genome is the behavior, and the total error is as given.
+    fn population(individuals: &[(i32, i32)]) -> Vec<EcIndividual<i32, TestResults<Error<i32>>>> {
+        individuals
+            .iter()
+            .map(|&(genome, error)| {
+                EcIndividual::new(
+                    genome,
+                    TestResults {
+                        results: Vec::new(),
+                        total_result: Error::from(error),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn full_tournament_picks_the_most_novel() {
+        let population = population(&[(0, 0), (1, 0), (2, 0), (10, 5)]);
+        let archive = NoveltyArchive::new(1, ArchivePolicy::MostNovel(1));
+        let descriptor = FnDescriptor(|individual: &EcIndividual<i32, _>| vec![individual.genome]);
+        let selector = Novelty::new(4, descriptor, &archive)
+            .prepare(&population)
+            .unwrap();
+        let mut rng = rand::thread_rng();
+        assert_eq!(10, selector.select(&population, &mut rng).unwrap().genome);
+        assert!(archive.with_behaviors(|behaviors| behaviors[0] == vec![10]));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn fitness_can_outweigh_novelty() {
+        let population = population(&[(0, 0), (1, 0), (2, 0), (10, 5)]);
+        let archive = NoveltyArchive::new(1, ArchivePolicy::Threshold(f64::INFINITY));
+        let descriptor = FnDescriptor(|individual: &EcIndividual<i32, _>| vec![individual.genome]);
+        let selector = Novelty::new(4, descriptor, &archive)
+            .with_fitness_weight(0.9)
+            .prepare(&population)
+            .unwrap();
+        let mut rng = rand::thread_rng();
+        assert_ne!(10, selector.select(&population, &mut rng).unwrap().genome);
+        assert!(archive.is_empty());
+        assert!(Novelty::new(4, descriptor, &archive)
+            .with_fitness_weight(1.5)
+            .prepare(&population)
+            .is_err());
+    }
+}
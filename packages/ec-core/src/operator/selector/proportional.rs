@@ -0,0 +1,181 @@
+use anyhow::{ensure, Context, Result};
+use num_traits::ToPrimitive;
+use rand::Rng;
+
+use super::Selector;
+use crate::population::Population;
+
+/// Selects individuals with probability proportional to a (non-negative)
+/// weight per individual.
+///
+/// This is what [`Roulette`](super::roulette::Roulette) and
+/// [`Rank`](super::rank::Rank) selection prepare for a population; they only
+/// differ in how they weight the individuals. It should only be used to
+/// select from the population it was prepared with.
+#[derive(Debug, Clone)]
+pub struct PreparedProportional {
+    /// The running total of the weights, so the last value is the total
+    /// weight.
+    cumulative_weights: Vec<f64>,
+    /// Whether `select_many` uses stochastic universal sampling.
+    universal: bool,
+}
+
+impl PreparedProportional {
+    /// If every weight is zero (e.g., every individual has the same fitness
+    /// after shifting away negative values), then every individual is
+    /// equally likely to be selected.
+    pub(crate) fn new(weights: impl IntoIterator<Item = f64>) -> Result<Self> {
+        let mut total = 0.0;
+        let mut cumulative_weights = weights
+            .into_iter()
+            .map(|weight| {
+                ensure!(
+                    weight.is_finite() && weight >= 0.0,
+                    "Selection weights must be finite and non-negative, but got {weight}"
+                );
+                total += weight;
+                Ok(total)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        ensure!(
+            !cumulative_weights.is_empty(),
+            "Can't select from an empty population"
+        );
+        ensure!(total.is_finite(), "The total selection weight overflowed");
+        if total == 0.0 {
+            cumulative_weights = (1..=cumulative_weights.len())
+                .map(|count| count.to_f64().unwrap_or(f64::NAN))
+                .collect();
+        }
+        Ok(Self {
+            cumulative_weights,
+            universal: false,
+        })
+    }
+
+    pub(crate) const fn with_universal_sampling(mut self) -> Self {
+        self.universal = true;
+        self
+    }
+
+    /// The probability that each individual is chosen by a single
+    /// selection, in the same order as the population.
+    #[must_use]
+    pub fn probabilities(&self) -> Vec<f64> {
+        let total = self.total_weight();
+        let mut previous = 0.0;
+        self.cumulative_weights
+            .iter()
+            .map(|&cumulative| {
+                let weight = cumulative - previous;
+                previous = cumulative;
+                weight / total
+            })
+            .collect()
+    }
+
+    fn total_weight(&self) -> f64 {
+        self.cumulative_weights[self.cumulative_weights.len() - 1]
+    }
+
+    /// The index of the individual whose slice of the wheel contains
+    /// `point`.
+    fn index_at(&self, point: f64) -> usize {
+        self.cumulative_weights
+            .partition_point(|&cumulative| cumulative <= point)
+            .min(self.cumulative_weights.len() - 1)
+    }
+
+    fn check_size<P: Population>(&self, population: &P) -> Result<()> {
+        ensure!(
+            population.size() == self.cumulative_weights.len(),
+            "This selector was prepared for a population of size {}, not {}",
+            self.cumulative_weights.len(),
+            population.size()
+        );
+        Ok(())
+    }
+}
+
+impl<P> Selector<P> for PreparedProportional
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        self.check_size(population)?;
+        let index = self.index_at(rng.gen_range(0.0..self.total_weight()));
+        population
+            .into_iter()
+            .nth(index)
+            .context("The population was smaller than when this selector was prepared")
+    }
+
+    /// With stochastic universal sampling, this spins the wheel once and
+    /// uses `count` evenly spaced pointers, so each individual is selected
+    /// either the floor or the ceiling of its expected number of times.
+    fn select_many<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        count: usize,
+        rng: &mut R,
+    ) -> Result<Vec<&'pop P::Individual>> {
+        if !self.universal {
+            return (0..count).map(|_| self.select(population, rng)).collect();
+        }
+        self.check_size(population)?;
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let individuals: Vec<_> = population.into_iter().collect();
+        let spacing = self.total_weight()
+            / count
+                .to_f64()
+                .context("The number of selections couldn't be converted to an f64")?;
+        let start = rng.gen_range(0.0..spacing);
+        (0..count)
+            .map(|pointer| {
+                let point = pointer.to_f64().unwrap_or(f64::NAN).mul_add(spacing, start);
+                individuals
+                    .get(self.index_at(point))
+                    .copied()
+                    .context("The population was smaller than when this selector was prepared")
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn zero_weights_are_uniform() {
+        let prepared = PreparedProportional::new([0.0, 0.0, 0.0, 0.0]).unwrap();
+        assert_eq!(vec![0.25; 4], prepared.probabilities());
+    }
+
+    #[test]
+    fn negative_weights_are_an_error() {
+        assert!(PreparedProportional::new([1.0, -1.0]).is_err());
+        assert!(PreparedProportional::new([1.0, f64::NAN]).is_err());
+        assert!(PreparedProportional::new([]).is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn zero_weight_is_never_selected() {
+        let population = vec![0, 1, 2];
+        let prepared = PreparedProportional::new([1.0, 0.0, 3.0]).unwrap();
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            assert_ne!(&1, prepared.select(&population, &mut rng).unwrap());
+        }
+    }
+}
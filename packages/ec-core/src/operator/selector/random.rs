@@ -1,19 +1,23 @@
 use anyhow::{Context, Result};
-use rand::{prelude::IndexedRandom, rngs::ThreadRng};
+use rand::{prelude::IndexedRandom, Rng};
 
 use super::Selector;
-use crate::population::Population;
+use crate::{
+    operator::{Describe, Description, Prepare},
+    population::Population,
+};
 
+#[derive(Debug, Clone, Copy)]
 pub struct Random;
 
 impl<P> Selector<P> for Random
 where
     P: Population + AsRef<[P::Individual]>,
 {
-    fn select<'pop>(
+    fn select<'pop, R: Rng + ?Sized>(
         &self,
         population: &'pop P,
-        rng: &mut ThreadRng,
+        rng: &mut R,
     ) -> Result<&'pop P::Individual> {
         population
             .as_ref()
@@ -21,3 +25,17 @@ where
             .context("The population was empty")
     }
 }
+
+impl<P> Prepare<P> for Random {
+    type Prepared = Self;
+
+    fn prepare(&self, _: &P) -> Result<Self> {
+        Ok(*self)
+    }
+}
+
+impl Describe for Random {
+    fn describe(&self) -> Description {
+        Description::new("Random")
+    }
+}
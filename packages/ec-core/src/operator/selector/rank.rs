@@ -0,0 +1,237 @@
+use anyhow::{ensure, Result};
+use num_traits::ToPrimitive;
+use rand::Rng;
+
+use super::{proportional::PreparedProportional, PreparedSelector, Selector};
+use crate::{
+    individual::Individual,
+    operator::{Describe, Description, Prepare},
+    population::Population,
+    test_results::TestResults,
+};
+
+/// How the rank of an individual (0 for the worst, up to `n - 1` for the
+/// best) is turned into its selection weight.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Ranking {
+    /// The weight increases linearly with rank. The best individual is
+    /// expected to be selected `selection_pressure` times per `n`
+    /// selections and the worst `2 - selection_pressure` times, so the
+    /// pressure has to be between 1 (uniform) and 2 (the worst is never
+    /// selected).
+    Linear { selection_pressure: f64 },
+    /// The weight of each individual is `base` times the weight of the
+    /// next better one, where `base` has to be between 0 and 1.
+    Exponential { base: f64 },
+}
+
+/// Rank-based selection on the total results.
+///
+/// Since this only depends on the order of the individuals, it works the
+/// same way for scores and errors, and isn't affected by zero or negative
+/// values. Individuals whose total results are tied share the average of
+/// the weights of their ranks. NaN totals are worse than every other total,
+/// so they get the weight of the worst rank.
+#[derive(Debug, Clone, Copy)]
+pub struct Rank {
+    ranking: Ranking,
+}
+
+impl Rank {
+    #[must_use]
+    pub const fn new(ranking: Ranking) -> Self {
+        Self { ranking }
+    }
+
+    #[must_use]
+    pub const fn linear(selection_pressure: f64) -> Self {
+        Self::new(Ranking::Linear { selection_pressure })
+    }
+
+    #[must_use]
+    pub const fn exponential(base: f64) -> Self {
+        Self::new(Ranking::Exponential { base })
+    }
+
+    fn weight(&self, rank: usize, population_size: usize) -> f64 {
+        let rank = rank.to_f64().unwrap_or(f64::NAN);
+        let best_rank = population_size
+            .saturating_sub(1)
+            .to_f64()
+            .unwrap_or(f64::NAN);
+        match self.ranking {
+            Ranking::Linear { .. } if best_rank == 0.0 => 1.0,
+            Ranking::Linear { selection_pressure } => (2.0 * (selection_pressure - 1.0))
+                .mul_add(rank / best_rank, 2.0 - selection_pressure),
+            Ranking::Exponential { base } => base.powf(best_rank - rank),
+        }
+    }
+}
+
+impl<P, T> Prepare<P> for Rank
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual<TestResults = TestResults<T>>,
+    T: Ord,
+{
+    type Prepared = PreparedProportional;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        match self.ranking {
+            Ranking::Linear { selection_pressure } => ensure!(
+                (1.0..=2.0).contains(&selection_pressure),
+                "The selection pressure for linear ranking must be between 1 and 2, but was \
+                 {selection_pressure}"
+            ),
+            Ranking::Exponential { base } => ensure!(
+                base > 0.0 && base <= 1.0,
+                "The base for exponential ranking must be in (0, 1], but was {base}"
+            ),
+        }
+
+        let totals: Vec<&T> = population
+            .into_iter()
+            .map(|individual| &individual.test_results().total_result)
+            .collect();
+        let mut order: Vec<usize> = (0..totals.len()).collect();
+        order.sort_unstable_by(|&a, &b| totals[a].cmp(totals[b]));
+
+        let mut weights = vec![0.0; totals.len()];
+        let mut start = 0;
+        while start < order.len() {
+            let end = start
+                + order[start..]
+                    .iter()
+                    .take_while(|&&index| totals[index] == totals[order[start]])
+                    .count();
+            let tied_weight = (start..end)
+                .map(|rank| self.weight(rank, totals.len()))
+                .sum::<f64>()
+                / (end - start).to_f64().unwrap_or(f64::NAN);
+            for &index in &order[start..end] {
+                weights[index] = tied_weight;
+            }
+            start = end;
+        }
+        PreparedProportional::new(weights)
+    }
+}
+
+impl<P, T> Selector<P> for Rank
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual<TestResults = TestResults<T>>,
+    T: Ord,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        self.prepare(population)?.select(population, rng)
+    }
+
+    fn prepare_selector(&self, population: &P) -> Result<Option<PreparedSelector<P>>> {
+        PreparedSelector::prepare(self, population).map(Some)
+    }
+}
+
+impl Describe for Rank {
+    fn describe(&self) -> Description {
+        Description::new("Rank").with_parameter("ranking", format!("{:?}", self.ranking))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{individual::ec::EcIndividual, test_results::Error};
+
+    fn population(errors: &[i32]) -> Vec<EcIndividual<usize, TestResults<Error<i32>>>> {
+        errors
+            .iter()
+            .enumerate()
+            .map(|(genome, &error)| {
+                EcIndividual::new(
+                    genome,
+                    TestResults {
+                        results: Vec::new(),
+                        total_result: Error::from(error),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    fn assert_probabilities(expected: &[f64], actual: &[f64]) {
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(actual) {
+            assert!((expected - actual).abs() < 1e-10, "{expected} != {actual}");
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn linear_ranking() {
+        // The ranks are 1, 2, 0 since smaller errors are better.
+        let prepared = Rank::linear(2.0)
+            .prepare(&population(&[5, -3, 10]))
+            .unwrap();
+        assert_probabilities(&[1.0 / 3.0, 2.0 / 3.0, 0.0], &prepared.probabilities());
+        let uniform = Rank::linear(1.0)
+            .prepare(&population(&[5, -3, 10]))
+            .unwrap();
+        assert_probabilities(&[1.0 / 3.0; 3], &uniform.probabilities());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn exponential_ranking() {
+        let prepared = Rank::exponential(0.5)
+            .prepare(&population(&[1, 0, 2]))
+            .unwrap();
+        assert_probabilities(
+            &[2.0 / 7.0, 4.0 / 7.0, 1.0 / 7.0],
+            &prepared.probabilities(),
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn ties_share_their_weight() {
+        let prepared = Rank::linear(2.0).prepare(&population(&[0, 0, 1])).unwrap();
+        // The weights for ranks 0, 1, and 2 are 0, 1, and 2, so the tied
+        // individuals each get 1.5.
+        assert_probabilities(&[0.5, 0.5, 0.0], &prepared.probabilities());
+    }
+
+    #[test]
+    fn invalid_parameters_are_an_error() {
+        assert!(Rank::linear(2.5).prepare(&population(&[0, 1])).is_err());
+        assert!(Rank::exponential(0.0)
+            .prepare(&population(&[0, 1]))
+            .is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn nans_get_the_worst_rank() {
+        let population: Vec<_> = [1.0, f64::NAN, 0.0]
+            .into_iter()
+            .enumerate()
+            .map(|(genome, error)| {
+                EcIndividual::new(
+                    genome,
+                    TestResults {
+                        results: Vec::new(),
+                        total_result: Error::from(error),
+                    },
+                )
+            })
+            .collect();
+        let prepared = Rank::linear(2.0).prepare(&population).unwrap();
+        assert_probabilities(&[1.0 / 3.0, 0.0, 2.0 / 3.0], &prepared.probabilities());
+    }
+}
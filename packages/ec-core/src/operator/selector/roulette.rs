@@ -0,0 +1,166 @@
+use anyhow::{Context, Result};
+use rand::Rng;
+
+use super::{proportional::PreparedProportional, PreparedSelector, Selector};
+use crate::{
+    individual::Individual,
+    operator::{Describe, Description, Prepare},
+    population::Population,
+    test_results::{Fitness, TestResults},
+};
+
+/// Fitness-proportional ("roulette wheel") selection on the total results.
+///
+/// The fitness of each individual comes from [`Fitness`], so errors are
+/// inverted. If any fitness is negative, all the fitnesses are shifted up
+/// so the smallest is zero; the least fit individual will then never be
+/// selected. Individuals with a NaN fitness (e.g., a NaN total error) get
+/// the same weight as the least fit individual after shifting, i.e., zero.
+/// If every individual ends up with a weight of zero, they're all equally
+/// likely to be selected.
+#[derive(Debug, Clone, Copy)]
+pub struct Roulette;
+
+impl<P, T> Prepare<P> for Roulette
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual<TestResults = TestResults<T>>,
+    T: Fitness,
+{
+    type Prepared = PreparedProportional;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        let fitnesses = population
+            .into_iter()
+            .map(|individual| {
+                individual
+                    .test_results()
+                    .total_result
+                    .fitness()
+                    .context("An individual's total result didn't have a valid fitness")
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // `f64::min` ignores NaNs, so they don't affect the shift.
+        let smallest = fitnesses.iter().copied().fold(0.0, f64::min);
+        PreparedProportional::new(fitnesses.into_iter().map(|fitness| {
+            if fitness.is_nan() {
+                0.0
+            } else {
+                fitness - smallest
+            }
+        }))
+    }
+}
+
+impl<P, T> Selector<P> for Roulette
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual<TestResults = TestResults<T>>,
+    T: Fitness,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        self.prepare(population)?.select(population, rng)
+    }
+
+    fn prepare_selector(&self, population: &P) -> Result<Option<PreparedSelector<P>>> {
+        PreparedSelector::prepare(self, population).map(Some)
+    }
+}
+
+impl Describe for Roulette {
+    fn describe(&self) -> Description {
+        Description::new("Roulette")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        individual::ec::EcIndividual,
+        test_results::{Error, Score},
+    };
+
+    fn individual<R>(genome: usize, total: R) -> EcIndividual<usize, TestResults<R>> {
+        EcIndividual::new(
+            genome,
+            TestResults {
+                results: Vec::new(),
+                total_result: total,
+            },
+        )
+    }
+
+    fn assert_probabilities(expected: &[f64], actual: &[f64]) {
+        assert_eq!(expected.len(), actual.len());
+        for (expected, actual) in expected.iter().zip(actual) {
+            assert!((expected - actual).abs() < 1e-10, "{expected} != {actual}");
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn scores_are_proportional() {
+        let population: Vec<_> = [1, 3, 0, 4]
+            .into_iter()
+            .enumerate()
+            .map(|(genome, score)| individual(genome, Score::from(score)))
+            .collect();
+        let prepared = Roulette.prepare(&population).unwrap();
+        assert_probabilities(&[0.125, 0.375, 0.0, 0.5], &prepared.probabilities());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn negative_scores_are_shifted() {
+        let population: Vec<_> = [-2, 0, 2]
+            .into_iter()
+            .enumerate()
+            .map(|(genome, score)| individual(genome, Score::from(score)))
+            .collect();
+        let prepared = Roulette.prepare(&population).unwrap();
+        assert_probabilities(&[0.0, 1.0 / 3.0, 2.0 / 3.0], &prepared.probabilities());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn errors_are_inverted() {
+        let population: Vec<_> = [0, 1, 3]
+            .into_iter()
+            .enumerate()
+            .map(|(genome, error)| individual(genome, Error::from(error)))
+            .collect();
+        let prepared = Roulette.prepare(&population).unwrap();
+        // The fitnesses are 1, 1/2, and 1/4.
+        assert_probabilities(
+            &[4.0 / 7.0, 2.0 / 7.0, 1.0 / 7.0],
+            &prepared.probabilities(),
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn nans_get_the_worst_weight() {
+        let population: Vec<_> = [0.0, f64::NAN, 1.0]
+            .into_iter()
+            .enumerate()
+            .map(|(genome, error)| individual(genome, Error::from(error)))
+            .collect();
+        let prepared = Roulette.prepare(&population).unwrap();
+        assert_probabilities(&[2.0 / 3.0, 0.0, 1.0 / 3.0], &prepared.probabilities());
+
+        let population: Vec<_> = [-1.0, f64::NAN, 1.0]
+            .into_iter()
+            .enumerate()
+            .map(|(genome, score)| individual(genome, Score::from(score)))
+            .collect();
+        let prepared = Roulette.prepare(&population).unwrap();
+        assert_probabilities(&[0.0, 0.0, 1.0], &prepared.probabilities());
+    }
+}
@@ -0,0 +1,168 @@
+use anyhow::Result;
+use rand::Rng;
+
+use super::{proportional::PreparedProportional, PreparedSelector, Selector};
+use crate::{
+    operator::{Describe, Description, Prepare},
+    population::Population,
+};
+
+/// Stochastic universal sampling (Baker) using the weights from a
+/// fitness-proportional or rank-based selector, e.g.,
+/// `StochasticUniversal::new(Roulette)`.
+///
+/// Instead of spinning the wheel once per selection, `select_many` spins
+/// it once and uses evenly spaced pointers, which keeps the number of
+/// times each individual is selected close to its expected value. A
+/// single `select` is the same as the underlying selector, so the parents
+/// have to be selected together (e.g., by using
+/// [`SelectParents`](crate::operator::select_parents::SelectParents) as the
+/// child maker in a `Generation`) for this to be any different.
+#[derive(Debug, Clone, Copy)]
+pub struct StochasticUniversal<S> {
+    weighting: S,
+}
+
+impl<S> StochasticUniversal<S> {
+    pub const fn new(weighting: S) -> Self {
+        Self { weighting }
+    }
+}
+
+impl<P, S> Prepare<P> for StochasticUniversal<S>
+where
+    S: Prepare<P, Prepared = PreparedProportional>,
+{
+    type Prepared = PreparedProportional;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        Ok(self
+            .weighting
+            .prepare(population)?
+            .with_universal_sampling())
+    }
+}
+
+impl<P, S> Selector<P> for StochasticUniversal<S>
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    S: Prepare<P, Prepared = PreparedProportional>,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        self.prepare(population)?.select(population, rng)
+    }
+
+    fn select_many<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        count: usize,
+        rng: &mut R,
+    ) -> Result<Vec<&'pop P::Individual>> {
+        self.prepare(population)?
+            .select_many(population, count, rng)
+    }
+
+    fn prepare_selector(&self, population: &P) -> Result<Option<PreparedSelector<P>>> {
+        PreparedSelector::prepare(self, population).map(Some)
+    }
+}
+
+impl<S> Describe for StochasticUniversal<S>
+where
+    S: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("StochasticUniversal").with_child(self.weighting.describe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generation::Generation,
+        individual::ec::EcIndividual,
+        operator::{
+            select_parents::SelectParents, selector::roulette::Roulette, Composable, Operator,
+        },
+        test_results::{Score, TestResults},
+    };
+
+    type Scored = EcIndividual<usize, TestResults<Score<i32>>>;
+
+    fn population(scores: &[i32]) -> Vec<Scored> {
+        scores
+            .iter()
+            .enumerate()
+            .map(|(genome, &score)| {
+                EcIndividual::new(
+                    genome,
+                    TestResults {
+                        results: Vec::new(),
+                        total_result: Score::from(score),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    struct Copy;
+
+    impl Operator<[&Scored; 1]> for Copy {
+        type Output = Scored;
+
+        fn apply<R: Rng + ?Sized>(&self, [parent]: [&Scored; 1], _: &mut R) -> Result<Scored> {
+            Ok(parent.clone())
+        }
+    }
+    impl Composable for Copy {}
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn selections_match_expected_counts() {
+        let population = population(&[1, 2, 0, 5]);
+        let selector = StochasticUniversal::new(Roulette);
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            // The expected counts are exactly 1, 2, 0, and 5.
+            let mut counts = [0; 4];
+            for individual in selector.select_many(&population, 8, &mut rng).unwrap() {
+                counts[individual.genome] += 1;
+            }
+            assert_eq!([1, 2, 0, 5], counts);
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn generations_select_parents_in_one_batch() {
+        // The scores add up to the population size, so the expected numbers
+        // of children of each individual are exactly its score.
+        let scores = [1, 0, 3, 0, 1];
+        let make_generation = || {
+            Generation::new(
+                SelectParents::<_, _, 1>::new(StochasticUniversal::new(Roulette), Copy),
+                population(&scores),
+            )
+        };
+        for seed in 0..20 {
+            let mut serial = make_generation();
+            let mut parallel = make_generation();
+            serial.serial_next(seed).unwrap();
+            parallel.par_next(seed).unwrap();
+            for generation in [serial, parallel] {
+                // Each child is a copy of its parent.
+                let mut counts = [0; 5];
+                for individual in generation.population() {
+                    counts[individual.genome] += 1;
+                }
+                assert_eq!(scores, counts);
+            }
+        }
+    }
+}
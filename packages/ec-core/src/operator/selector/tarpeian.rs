@@ -0,0 +1,187 @@
+use anyhow::{ensure, Context, Result};
+use num_traits::ToPrimitive;
+use rand::{prelude::IndexedRandom, Rng};
+
+use super::{PreparedSelector, Selector};
+use crate::{
+    genome::GenomeSize,
+    individual::Individual,
+    operator::{Describe, Description, Prepare},
+    population::Population,
+};
+
+/// Tournament selection with Tarpeian bloat control (Poli).
+///
+/// Each contestant that's larger than the population's average size is, with
+/// probability `kill_probability`, treated as worse than every other
+/// contestant.
+///
+/// Poli suggests a kill probability of around 1 / n for n generations. The
+/// decision to "kill" a contestant is made each time it takes part in a
+/// tournament, rather than once per generation.
+///
+/// Computing the average size requires a pass over the entire population.
+/// Using this directly as a `Selector` does that on every selection;
+/// [`Generation`](crate::generation::Generation) instead
+/// [prepares](Prepare) it once per generation.
+#[derive(Debug, Clone, Copy)]
+pub struct Tarpeian {
+    size: usize,
+    kill_probability: f64,
+}
+
+impl Tarpeian {
+    #[must_use]
+    pub const fn new(size: usize, kill_probability: f64) -> Self {
+        Self {
+            size,
+            kill_probability,
+        }
+    }
+}
+
+impl<P> Prepare<P> for Tarpeian
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual,
+    <P::Individual as Individual>::Genome: GenomeSize,
+{
+    type Prepared = PreparedTarpeian;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        ensure!(
+            (0.0..=1.0).contains(&self.kill_probability),
+            "The kill probability must be between 0 and 1, but was {}",
+            self.kill_probability
+        );
+        let total_size: usize = population
+            .into_iter()
+            .map(|individual| individual.genome().genome_size())
+            .sum();
+        let average_size = total_size.to_f64().unwrap_or(f64::NAN)
+            / population.size().to_f64().unwrap_or(f64::NAN);
+        Ok(PreparedTarpeian {
+            size: self.size,
+            kill_probability: self.kill_probability,
+            average_size,
+        })
+    }
+}
+
+impl<P> Selector<P> for Tarpeian
+where
+    P: Population + AsRef<[P::Individual]>,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual + Ord,
+    <P::Individual as Individual>::Genome: GenomeSize,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        self.prepare(population)?.select(population, rng)
+    }
+
+    fn prepare_selector(&self, population: &P) -> Result<Option<PreparedSelector<P>>> {
+        PreparedSelector::prepare(self, population).map(Some)
+    }
+}
+
+impl Describe for Tarpeian {
+    fn describe(&self) -> Description {
+        Description::new("Tarpeian")
+            .with_parameter("size", self.size)
+            .with_parameter("kill_probability", self.kill_probability)
+    }
+}
+
+/// A [`Tarpeian`] selector that has been prepared for a population.
+///
+/// It knows the population's average size.
+#[derive(Debug, Clone, Copy)]
+pub struct PreparedTarpeian {
+    size: usize,
+    kill_probability: f64,
+    average_size: f64,
+}
+
+impl<P> Selector<P> for PreparedTarpeian
+where
+    P: Population + AsRef<[P::Individual]>,
+    P::Individual: Individual + Ord,
+    <P::Individual as Individual>::Genome: GenomeSize,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        ensure!(
+            population.size() >= self.size,
+            "The population had size {} and we wanted a tournament of size {}",
+            population.size(),
+            self.size
+        );
+        // Contestants that survive (`true`) beat those that are killed.
+        population
+            .as_ref()
+            .choose_multiple(rng, self.size)
+            .map(|contestant| {
+                let is_large = contestant
+                    .genome()
+                    .genome_size()
+                    .to_f64()
+                    .is_some_and(|size| size > self.average_size);
+                let survives = !(is_large && rng.gen_bool(self.kill_probability));
+                (survives, contestant)
+            })
+            .max()
+            .map(|(_, winner)| winner)
+            .with_context(|| format!("The tournament was empty; should have been {}", self.size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::individual::ec::EcIndividual;
+
+    fn population() -> Vec<EcIndividual<Vec<u8>, i32>> {
+        // The average size is 4, so only the best individual is large.
+        vec![
+            EcIndividual::new(vec![0; 10], 100),
+            EcIndividual::new(vec![0; 1], 2),
+            EcIndividual::new(vec![0; 1], 1),
+        ]
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn large_individuals_are_always_killed_with_probability_one() {
+        let population = population();
+        let selector = Tarpeian::new(3, 1.0).prepare(&population).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            selector.select(&population, &mut rng).unwrap().test_results,
+            2
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn without_killing_this_is_a_tournament() {
+        let population = population();
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            Tarpeian::new(3, 0.0)
+                .select(&population, &mut rng)
+                .unwrap()
+                .test_results,
+            100
+        );
+    }
+}
@@ -1,29 +1,53 @@
 use anyhow::{ensure, Context, Result};
-use rand::{prelude::IndexedRandom, rngs::ThreadRng};
+use rand::{prelude::IndexedRandom, Rng};
 
 use super::Selector;
-use crate::population::Population;
+use crate::{
+    individual::comparator::{Comparator, Natural},
+    operator::{Describe, Description, Prepare},
+    population::Population,
+};
 
-pub struct Tournament {
+/// Choose `size` individuals at random, and select the best of them
+/// according to the comparator (by default, their `Ord` implementation).
+#[derive(Debug, Clone, Copy)]
+pub struct Tournament<C = Natural> {
     size: usize,
+    comparator: C,
 }
 
 impl Tournament {
     #[must_use]
     pub const fn new(size: usize) -> Self {
-        Self { size }
+        Self {
+            size,
+            comparator: Natural,
+        }
     }
 }
 
-impl<P> Selector<P> for Tournament
+impl<C> Tournament<C> {
+    /// Use `comparator` to decide which contestant wins, e.g.,
+    /// [`LexicographicParsimony`](crate::individual::comparator::LexicographicParsimony)
+    /// to prefer smaller individuals when there are ties.
+    #[must_use]
+    pub fn with_comparator<D>(self, comparator: D) -> Tournament<D> {
+        Tournament {
+            size: self.size,
+            comparator,
+        }
+    }
+}
+
+impl<P, C> Selector<P> for Tournament<C>
 where
     P: Population + AsRef<[P::Individual]>,
-    P::Individual: Ord,
+    C: Comparator<P::Individual>,
 {
-    fn select<'pop>(
+    fn select<'pop, R: Rng + ?Sized>(
         &self,
         population: &'pop P,
-        rng: &mut ThreadRng,
+        rng: &mut R,
     ) -> Result<&'pop P::Individual> {
         ensure!(
             population.size() >= self.size,
@@ -34,7 +58,23 @@ where
         population
             .as_ref()
             .choose_multiple(rng, self.size)
-            .max()
+            .max_by(|x, y| self.comparator.compare(x, y))
             .with_context(|| "The tournament was empty; should have been {size}")
     }
 }
+
+impl<P, C: Clone> Prepare<P> for Tournament<C> {
+    type Prepared = Self;
+
+    fn prepare(&self, _: &P) -> Result<Self> {
+        Ok(self.clone())
+    }
+}
+
+impl<C: Describe> Describe for Tournament<C> {
+    fn describe(&self) -> Description {
+        Description::new("Tournament")
+            .with_parameter("size", self.size)
+            .with_child(self.comparator.describe())
+    }
+}
@@ -1,50 +1,285 @@
-use anyhow::{Context, Result};
-use rand::{rngs::ThreadRng, seq::IndexedRandom};
+use std::sync::Arc;
 
-use super::Selector;
-use crate::population::Population;
+use anyhow::{ensure, Result};
+use rand::Rng;
 
-pub struct Weighted<P: Population> {
-    selectors: Vec<(Box<dyn Selector<P> + Send + Sync>, usize)>,
+use super::{PreparedSelector, Selector};
+use crate::{
+    operator::{Describe, Description, Prepare},
+    population::Population,
+};
+
+/// A selector that has a (non-negative) weight, used when choosing
+/// between several selectors in a [`Weighted`] selector.
+pub trait WithWeight {
+    fn weight(&self) -> usize;
+}
+
+/// Choose between one or more selectors with probability proportional to
+/// their weights.
+///
+/// Since `Selector::select` is generic over the random number generator,
+/// selectors can't be stored as trait objects. Instead each call to
+/// `with_selector` nests the existing selector(s) in a [`WeightedPair`],
+/// so the full set of selectors is captured in the type.
+pub struct Weighted<S> {
+    selector: S,
+    weight: usize,
 }
 
-impl<P: Population> Weighted<P> {
+impl<S> Weighted<S> {
     // Since we should never have an empty collection of weighted selectors,
-    // the `new` implementation takes an initial selector so `selectors` is
-    // guaranteed to never be empty.
+    // the `new` implementation takes an initial selector so there is
+    // guaranteed to always be at least one selector.
+    #[must_use]
+    pub const fn new(selector: S, weight: usize) -> Self {
+        Self { selector, weight }
+    }
+
     #[must_use]
-    pub fn new<S>(selector: S, weight: usize) -> Self
-    where
-        S: Selector<P> + Send + Sync + 'static,
-    {
+    pub fn with_selector<T>(self, selector: T, weight: usize) -> WeightedPair<Self, Weighted<T>> {
+        WeightedPair::new(self, Weighted::new(selector, weight))
+    }
+}
+
+impl<S> WithWeight for Weighted<S> {
+    fn weight(&self) -> usize {
+        self.weight
+    }
+}
+
+impl<P, S> Selector<P> for Weighted<S>
+where
+    P: Population,
+    S: Selector<P>,
+{
+    fn select<'pop, R: Rng + ?Sized>(
+        &self,
+        population: &'pop P,
+        rng: &mut R,
+    ) -> Result<&'pop P::Individual> {
+        self.selector.select(population, rng)
+    }
+
+    fn prepare_selector(&self, population: &P) -> Result<Option<PreparedSelector<P>>> {
+        self.selector.prepare_selector(population)
+    }
+}
+
+impl<P, S> Prepare<P> for Weighted<S>
+where
+    S: Prepare<P>,
+{
+    type Prepared = Weighted<S::Prepared>;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        Ok(Weighted::new(
+            self.selector.prepare(population)?,
+            self.weight,
+        ))
+    }
+}
+
+/// A pair of weighted selectors; this is what's built up by repeated
+/// calls to `with_selector`.
+///
+/// The two halves are shared so that, when only one of them needs
+/// [preparing](Selector::prepare_selector), the prepared pair can still use
+/// the other one as is.
+pub struct WeightedPair<A, B> {
+    a: Arc<A>,
+    b: Arc<B>,
+}
+
+impl<A, B> WeightedPair<A, B> {
+    fn new(a: A, b: B) -> Self {
         Self {
-            selectors: vec![(Box::new(selector), weight)],
+            a: Arc::new(a),
+            b: Arc::new(b),
         }
     }
 
     #[must_use]
-    pub fn with_selector<S>(mut self, selector: S, weight: usize) -> Self
-    where
-        S: Selector<P> + Send + Sync + 'static,
-    {
-        self.selectors.push((Box::new(selector), weight));
-        self
+    pub fn with_selector<T>(self, selector: T, weight: usize) -> WeightedPair<Self, Weighted<T>> {
+        WeightedPair::new(self, Weighted::new(selector, weight))
     }
 }
 
-impl<P> Selector<P> for Weighted<P>
+impl<A, B> WithWeight for WeightedPair<A, B>
 where
-    P: Population,
+    A: WithWeight,
+    B: WithWeight,
 {
-    fn select<'pop>(
+    fn weight(&self) -> usize {
+        self.a.weight() + self.b.weight()
+    }
+}
+
+impl<P, A, B> Selector<P> for WeightedPair<A, B>
+where
+    P: Population + 'static,
+    A: Selector<P> + WithWeight + Send + Sync + 'static,
+    B: Selector<P> + WithWeight + Send + Sync + 'static,
+{
+    fn select<'pop, R: Rng + ?Sized>(
         &self,
         population: &'pop P,
-        rng: &mut ThreadRng,
+        rng: &mut R,
     ) -> Result<&'pop P::Individual> {
-        let (selector, _) = self
-            .selectors
-            .choose_weighted(rng, |(_, w)| *w)
-            .context("The set of selectors was empty")?;
-        selector.select(population, rng)
+        let total_weight = self.weight();
+        ensure!(
+            total_weight > 0,
+            "The total weight of the weighted selectors was zero"
+        );
+        if rng.gen_range(0..total_weight) < self.a.weight() {
+            self.a.select(population, rng)
+        } else {
+            self.b.select(population, rng)
+        }
+    }
+
+    fn prepare_selector(&self, population: &P) -> Result<Option<PreparedSelector<P>>> {
+        let (a, b) = match (
+            self.a.prepare_selector(population)?,
+            self.b.prepare_selector(population)?,
+        ) {
+            (None, None) => return Ok(None),
+            (a, b) => (
+                a.unwrap_or_else(|| PreparedSelector::shared(Arc::clone(&self.a))),
+                b.unwrap_or_else(|| PreparedSelector::shared(Arc::clone(&self.b))),
+            ),
+        };
+        Ok(Some(PreparedSelector::new(WeightedPair::new(
+            Weighted::new(a, self.a.weight()),
+            Weighted::new(b, self.b.weight()),
+        ))))
+    }
+}
+
+impl<P, A, B> Prepare<P> for WeightedPair<A, B>
+where
+    A: Prepare<P>,
+    B: Prepare<P>,
+{
+    type Prepared = WeightedPair<A::Prepared, B::Prepared>;
+
+    fn prepare(&self, population: &P) -> Result<Self::Prepared> {
+        Ok(WeightedPair::new(
+            self.a.prepare(population)?,
+            self.b.prepare(population)?,
+        ))
+    }
+}
+
+impl<S> Describe for Weighted<S>
+where
+    S: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("Weighted")
+            .with_parameter("weight", self.weight)
+            .with_child(self.selector.describe())
+    }
+}
+
+// Nested pairs (from repeated calls to `with_selector`) are flattened into a
+// single choice, in the same shape as the operator choice from
+// `Composable::one_of_ops`.
+impl<A, B> Describe for WeightedPair<A, B>
+where
+    A: Describe,
+    B: Describe,
+{
+    fn describe(&self) -> Description {
+        Description::new("OneOf")
+            .with_flattened_child(self.a.describe())
+            .with_flattened_child(self.b.describe())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::bail;
+
+    use super::*;
+    use crate::operator::{
+        selector::{best::Best, Select},
+        Operator,
+    };
+
+    struct Worst;
+
+    impl Selector<Vec<i32>> for Worst {
+        fn select<'pop, R: Rng + ?Sized>(
+            &self,
+            population: &'pop Vec<i32>,
+            _: &mut R,
+        ) -> Result<&'pop i32> {
+            population
+                .iter()
+                .min()
+                .ok_or_else(|| anyhow::anyhow!("The population was empty"))
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn zero_weight_is_never_chosen() {
+        let pop = vec![5, 8, 9, 6, 3, 2, 0];
+        let mut rng = rand::thread_rng();
+        let selector = Weighted::new(Best, 1).with_selector(Worst, 0);
+        for _ in 0..100 {
+            assert_eq!(&9, selector.select(&pop, &mut rng).unwrap());
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn chooses_both_selectors() {
+        let pop = vec![5, 8, 9, 6, 3, 2, 0];
+        let mut rng = rand::thread_rng();
+        let selector = Weighted::new(Best, 1)
+            .with_selector(Worst, 1)
+            .with_selector(Best, 1);
+        let results: Vec<i32> = (0..100)
+            .map(|_| *selector.select(&pop, &mut rng).unwrap())
+            .collect();
+        assert!(results.contains(&9));
+        assert!(results.contains(&0));
+    }
+
+    /// Selects the best individual, but only once it's been prepared.
+    struct NeedsPreparing;
+
+    impl Selector<Vec<i32>> for NeedsPreparing {
+        fn select<'pop, R: Rng + ?Sized>(&self, _: &'pop Vec<i32>, _: &mut R) -> Result<&'pop i32> {
+            bail!("This selector has to be prepared before selecting")
+        }
+
+        fn prepare_selector(&self, _: &Vec<i32>) -> Result<Option<PreparedSelector<Vec<i32>>>> {
+            Ok(Some(PreparedSelector::new(Best)))
+        }
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn pairs_prepare_only_the_selectors_that_need_it() {
+        let pop = vec![5, 8, 9, 6, 3, 2, 0];
+        let mut rng = rand::thread_rng();
+        let select = Select::new(Weighted::new(NeedsPreparing, 1).with_selector(Worst, 1));
+        let prepared = select.prepare(&pop).unwrap();
+        let results: Vec<i32> = (0..100)
+            .map(|_| *prepared.apply(&pop, &mut rng).unwrap())
+            .collect();
+        assert!(results.contains(&9));
+        assert!(results.contains(&0));
+    }
+
+    #[test]
+    fn weights_are_summed() {
+        let selector = Weighted::new(Best, 1)
+            .with_selector(Worst, 5)
+            .with_selector(Best, 3);
+        assert_eq!(9, selector.weight());
     }
 }
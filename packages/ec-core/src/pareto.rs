@@ -0,0 +1,198 @@
+//! Pareto ranking for multi-objective problems, as used by NSGA-II (Deb et
+//! al.).
+//!
+//! Each entry of an individual's [`TestResults::results`] is treated as a
+//! separate objective, with bigger being better as usual (so `Error`s are
+//! minimized).
+
+use std::cmp::Ordering;
+
+use anyhow::{Context, Result};
+use num_traits::ToPrimitive;
+
+use crate::test_results::TestResults;
+
+/// The non-domination rank and crowding distance of every individual in a
+/// population.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParetoRanking {
+    fronts: Vec<Vec<usize>>,
+    ranks: Vec<usize>,
+    crowding_distances: Vec<f64>,
+}
+
+impl ParetoRanking {
+    /// Sort the given results into non-dominated fronts and compute the
+    /// crowding distance of each individual within its front.
+    ///
+    /// # Errors
+    /// This returns an error if any result can't be converted to an `f64`.
+    pub fn new<R>(results: &[&TestResults<R>]) -> Result<Self>
+    where
+        R: Ord + ToPrimitive,
+    {
+        let fronts = non_dominated_sort(results);
+        let mut ranks = vec![0; results.len()];
+        let mut crowding_distances = vec![0.0; results.len()];
+        for (rank, front) in fronts.iter().enumerate() {
+            let distances = crowding_distances_in(results, front)?;
+            for (&index, distance) in front.iter().zip(distances) {
+                ranks[index] = rank;
+                crowding_distances[index] = distance;
+            }
+        }
+        Ok(Self {
+            fronts,
+            ranks,
+            crowding_distances,
+        })
+    }
+
+    /// The indices of the individuals in each front, starting with the
+    /// non-dominated individuals.
+    #[must_use]
+    pub fn fronts(&self) -> &[Vec<usize>] {
+        &self.fronts
+    }
+
+    /// The front each individual is in, where 0 is the non-dominated front.
+    #[must_use]
+    pub fn ranks(&self) -> &[usize] {
+        &self.ranks
+    }
+
+    /// How isolated each individual is from its neighbors in the same front;
+    /// the individuals at the extremes of a front have infinite distance.
+    #[must_use]
+    pub fn crowding_distances(&self) -> &[f64] {
+        &self.crowding_distances
+    }
+
+    /// The crowded-comparison operator: an individual in a better (lower)
+    /// front is better, and within a front the less crowded individual is
+    /// better. `Greater` means that individual `a` is better.
+    #[must_use]
+    pub fn crowded_cmp(&self, a: usize, b: usize) -> Ordering {
+        self.ranks[b]
+            .cmp(&self.ranks[a])
+            .then_with(|| self.crowding_distances[a].total_cmp(&self.crowding_distances[b]))
+    }
+}
+
+/// Deb et al.'s fast non-dominated sort, which returns the indices of the
+/// individuals in each front, starting with the non-dominated individuals.
+#[must_use]
+pub fn non_dominated_sort<R: Ord>(results: &[&TestResults<R>]) -> Vec<Vec<usize>> {
+    // For each individual, the individuals it dominates and the number of
+    // individuals that dominate it.
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); results.len()];
+    let mut num_dominating = vec![0_usize; results.len()];
+    for i in 0..results.len() {
+        for j in (i + 1)..results.len() {
+            if results[i].dominates(results[j]) {
+                dominated[i].push(j);
+                num_dominating[j] += 1;
+            } else if results[j].dominates(results[i]) {
+                dominated[j].push(i);
+                num_dominating[i] += 1;
+            }
+        }
+    }
+
+    let mut fronts = Vec::new();
+    let mut front: Vec<usize> = (0..results.len())
+        .filter(|&index| num_dominating[index] == 0)
+        .collect();
+    while !front.is_empty() {
+        let mut next_front = Vec::new();
+        for &index in &front {
+            for &other in &dominated[index] {
+                num_dominating[other] -= 1;
+                if num_dominating[other] == 0 {
+                    next_front.push(other);
+                }
+            }
+        }
+        fronts.push(front);
+        front = next_front;
+    }
+    fronts
+}
+
+/// The crowding distance of each individual in `front`, in the same order
+/// as `front`.
+fn crowding_distances_in<R: ToPrimitive>(
+    results: &[&TestResults<R>],
+    front: &[usize],
+) -> Result<Vec<f64>> {
+    let mut distances = vec![0.0; front.len()];
+    let Some(num_objectives) = front.first().map(|&index| results[index].results.len()) else {
+        return Ok(distances);
+    };
+    let mut values: Vec<(usize, f64)> = Vec::with_capacity(front.len());
+    for objective in 0..num_objectives {
+        values.clear();
+        for (position, &index) in front.iter().enumerate() {
+            let value = results[index]
+                .results
+                .get(objective)
+                .context("The individuals don't all have the same number of objectives")?
+                .to_f64()
+                .context("An objective value couldn't be converted to an f64")?;
+            values.push((position, value));
+        }
+        values.sort_unstable_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let (first, last) = (values[0], values[values.len() - 1]);
+        distances[first.0] = f64::INFINITY;
+        distances[last.0] = f64::INFINITY;
+        let range = last.1 - first.1;
+        if range > 0.0 {
+            for window in values.windows(3) {
+                distances[window[1].0] += (window[2].1 - window[0].1) / range;
+            }
+        }
+    }
+    Ok(distances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_results::Error;
+
+    fn results(objectives: &[[i32; 2]]) -> Vec<TestResults<Error<i32>>> {
+        objectives
+            .iter()
+            .map(|objectives| objectives.to_vec().into())
+            .collect()
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn sorts_into_fronts() {
+        let results = results(&[[0, 4], [1, 1], [4, 0], [2, 2], [3, 3], [5, 5]]);
+        let results: Vec<_> = results.iter().collect();
+        let ranking = ParetoRanking::new(&results).unwrap();
+        assert_eq!(
+            &[vec![0, 1, 2], vec![3], vec![4], vec![5]],
+            ranking.fronts()
+        );
+        assert_eq!(&[0, 0, 0, 1, 2, 3], ranking.ranks());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn extremes_are_least_crowded() {
+        let results = results(&[[0, 4], [1, 2], [2, 1], [4, 0]]);
+        let results: Vec<_> = results.iter().collect();
+        let ranking = ParetoRanking::new(&results).unwrap();
+        let distances = ranking.crowding_distances();
+        assert!(distances[0].is_infinite() && distances[3].is_infinite());
+        // Each interior point's neighbors are 2 apart on one objective and
+        // 3 apart on the other, out of ranges of 4.
+        assert!((distances[1] - 1.25).abs() < f64::EPSILON);
+        assert!((distances[2] - 1.25).abs() < f64::EPSILON);
+        assert_eq!(Ordering::Greater, ranking.crowded_cmp(0, 1));
+    }
+}
@@ -0,0 +1,609 @@
+use std::{
+    cmp::Ordering,
+    time::{Duration, Instant},
+};
+
+use anyhow::{ensure, Context, Result};
+use rand::Rng;
+
+use crate::{
+    generation::Generation,
+    hall_of_fame::HallOfFame,
+    individual::Individual,
+    operator::{genome_scorer::ScoreErrorCount, Operator, Prepare},
+    population::Population,
+    survival::{Generational, Survival},
+};
+
+/// Why a [`Run`] stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The maximum number of generations was reached.
+    MaxGenerations,
+    /// An individual satisfying the target predicate was found.
+    TargetReached,
+    /// The best individual didn't improve for the configured number of
+    /// generations.
+    Stagnated,
+    /// The wall-clock budget for the run was used up.
+    TimeBudgetExhausted,
+    /// The maximum number of evaluations was reached.
+    MaxEvaluations,
+}
+
+/// Information about the state of a run that is passed to observers
+/// after each generation.
+pub struct GenerationReport<'a, P: Population> {
+    /// The number of the generation that was just created, starting at 0.
+    pub generation_number: usize,
+    pub population: &'a P,
+    /// The best individual in the current population.
+    pub best: &'a P::Individual,
+    /// The best individual seen in any generation of this run so far.
+    pub best_ever: &'a P::Individual,
+    /// The number of individuals evaluated so far, including the
+    /// initial population.
+    pub num_evaluations: usize,
+    /// The number of genomes that couldn't be scored so far; see
+    /// [`Run::with_score_error_count`].
+    pub num_score_errors: usize,
+    pub elapsed: Duration,
+}
+
+/// The result of a completed [`Run`].
+#[derive(Debug, Clone)]
+pub struct RunSummary<I> {
+    /// The best individual seen in any generation of the run.
+    pub best: I,
+    /// The number of generations that were created.
+    pub num_generations: usize,
+    pub num_evaluations: usize,
+    /// The number of genomes that couldn't be scored; see
+    /// [`Run::with_score_error_count`].
+    pub num_score_errors: usize,
+    pub elapsed: Duration,
+    pub stop_reason: StopReason,
+}
+
+/// The stop conditions of a [`Run`]. A condition that is `None` is
+/// never checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunParameters {
+    pub max_generations: Option<usize>,
+    pub stagnation_limit: Option<usize>,
+    pub time_budget: Option<Duration>,
+    pub max_evaluations: Option<usize>,
+}
+
+/// How far a [`Run`] has progressed; this is everything (besides the
+/// population and the random number generator) that is needed to
+/// continue a run where it left off.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RunProgress<I> {
+    /// The number of generations that have been created.
+    pub generation_number: usize,
+    pub num_evaluations: usize,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub num_score_errors: usize,
+    pub best_ever: I,
+    pub generations_since_improvement: usize,
+    /// The total time spent in the run, across all the times it was
+    /// resumed.
+    pub elapsed: Duration,
+}
+
+type Target<'a, I> = Box<dyn Fn(&I) -> bool + 'a>;
+type Observer<'a, P> = Box<dyn FnMut(&GenerationReport<'_, P>) + 'a>;
+
+/// Drive a [`Generation`] forward until one of the configured stop
+/// conditions is met, calling the registered observers after every
+/// generation.
+///
+/// At least one stop condition has to be provided before the run is
+/// started.
+pub struct Run<'a, P: Population, C, S = Generational> {
+    generation: Generation<P, C, S>,
+    parameters: RunParameters,
+    progress: Option<RunProgress<P::Individual>>,
+    target: Option<Target<'a, P::Individual>>,
+    score_errors: Option<ScoreErrorCount>,
+    observers: Vec<Observer<'a, P>>,
+}
+
+impl<'a, P: Population, C, S> Run<'a, P, C, S> {
+    pub fn new(generation: Generation<P, C, S>) -> Self {
+        Self {
+            generation,
+            parameters: RunParameters::default(),
+            progress: None,
+            target: None,
+            score_errors: None,
+            observers: Vec::new(),
+        }
+    }
+
+    pub const fn generation(&self) -> &Generation<P, C, S> {
+        &self.generation
+    }
+
+    pub const fn parameters(&self) -> &RunParameters {
+        &self.parameters
+    }
+
+    /// How far this run has progressed, or `None` if it hasn't been
+    /// started (or resumed) yet.
+    pub const fn progress(&self) -> Option<&RunProgress<P::Individual>> {
+        self.progress.as_ref()
+    }
+
+    /// Replace all the stop conditions (other than the target) with
+    /// those in `parameters`.
+    #[must_use]
+    pub const fn with_parameters(mut self, parameters: RunParameters) -> Self {
+        self.parameters = parameters;
+        self
+    }
+
+    /// Continue a previous run from `progress` instead of starting a
+    /// new one. The generation this run was created with should hold
+    /// the population from the same point in the previous run.
+    #[must_use]
+    pub fn resume_from(mut self, progress: RunProgress<P::Individual>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    #[must_use]
+    pub const fn with_max_generations(mut self, max_generations: usize) -> Self {
+        self.parameters.max_generations = Some(max_generations);
+        self
+    }
+
+    /// Stop as soon as the best individual in a generation satisfies
+    /// `target`, e.g., when its total error reaches zero. The initial
+    /// population is checked too, so a new run whose initial population
+    /// already meets the target doesn't make any generations.
+    #[must_use]
+    pub fn with_target(mut self, target: impl Fn(&P::Individual) -> bool + 'a) -> Self {
+        self.target = Some(Box::new(target));
+        self
+    }
+
+    /// Stop if the best individual ever seen hasn't improved in
+    /// `num_generations` consecutive generations.
+    #[must_use]
+    pub const fn with_stagnation_limit(mut self, num_generations: usize) -> Self {
+        self.parameters.stagnation_limit = Some(num_generations);
+        self
+    }
+
+    /// Stop once the run has taken at least `budget`. This is checked
+    /// after each generation, so a run can exceed its budget by up to
+    /// the time it takes to make one generation.
+    #[must_use]
+    pub const fn with_time_budget(mut self, budget: Duration) -> Self {
+        self.parameters.time_budget = Some(budget);
+        self
+    }
+
+    /// Stop once at least `max_evaluations` individuals have been
+    /// evaluated, counting the initial population.
+    #[must_use]
+    pub const fn with_max_evaluations(mut self, max_evaluations: usize) -> Self {
+        self.parameters.max_evaluations = Some(max_evaluations);
+        self
+    }
+
+    /// Report the number of genomes that couldn't be scored, as counted in
+    /// `score_errors` (typically a clone of the
+    /// [`GenomeScorer`](crate::operator::genome_scorer::GenomeScorer)'s
+    /// [error count](crate::operator::genome_scorer::GenomeScorer::error_count)),
+    /// in each [`GenerationReport`] and the [`RunSummary`].
+    #[must_use]
+    pub fn with_score_error_count(mut self, score_errors: ScoreErrorCount) -> Self {
+        self.score_errors = Some(score_errors);
+        self
+    }
+
+    /// Add an observer that is called after each generation is created.
+    #[must_use]
+    pub fn with_observer(mut self, observer: impl FnMut(&GenerationReport<'_, P>) + 'a) -> Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    const fn has_stop_condition(&self) -> bool {
+        let parameters = &self.parameters;
+        parameters.max_generations.is_some()
+            || self.target.is_some()
+            || parameters.stagnation_limit.is_some()
+            || parameters.time_budget.is_some()
+            || parameters.max_evaluations.is_some()
+    }
+}
+
+impl<'a, P, C, S> Run<'a, P, C, S>
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual + Clone,
+    <P::Individual as Individual>::Genome: PartialEq,
+{
+    /// Keep `hall_of_fame` up to date with the best individuals seen in
+    /// this run. It's updated with the current population straight away,
+    /// and then with each new population (before any other observers are
+    /// called).
+    #[must_use]
+    pub fn with_hall_of_fame<Cmp>(
+        mut self,
+        hall_of_fame: &'a HallOfFame<P::Individual, Cmp>,
+    ) -> Self
+    where
+        Cmp: Fn(&P::Individual, &P::Individual) -> Ordering,
+    {
+        hall_of_fame.update(self.generation.population());
+        self.observers.insert(
+            0,
+            Box::new(|report| {
+                hall_of_fame.update(report.population);
+            }),
+        );
+        self
+    }
+}
+
+impl<P, C, S> Run<'_, P, C, S>
+where
+    P: Population,
+    S: Survival<P>,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Ord + Clone,
+{
+    /// Run until a stop condition is met, using `next` to make each new
+    /// generation from a seed drawn from `rng`, and calling
+    /// `after_generation` once each new generation has been reported to
+    /// the observers.
+    pub(crate) fn run_with<R, F, H>(
+        &mut self,
+        rng: &mut R,
+        mut next: F,
+        mut after_generation: H,
+    ) -> Result<RunSummary<P::Individual>>
+    where
+        R: Rng + ?Sized,
+        F: FnMut(&mut Generation<P, C, S>, u64) -> Result<()>,
+        H: FnMut(
+            &Generation<P, C, S>,
+            &RunParameters,
+            &RunProgress<P::Individual>,
+            &R,
+        ) -> Result<()>,
+    {
+        ensure!(
+            self.has_stop_condition(),
+            "A run needs at least one stop condition or it will never end"
+        );
+        let start = Instant::now();
+        let (mut progress, initial_target_reached) = if let Some(progress) = self.progress.take() {
+            (progress, false)
+        } else {
+            let best = best_in(self.generation.population())?;
+            // There's no point making (and evaluating) any new
+            // generations if the initial population already meets the
+            // target.
+            let target_reached = self.target.as_ref().is_some_and(|target| target(best));
+            let progress = RunProgress {
+                generation_number: 0,
+                num_evaluations: self.generation.population().size(),
+                num_score_errors: 0,
+                best_ever: best.clone(),
+                generations_since_improvement: 0,
+                elapsed: Duration::ZERO,
+            };
+            (progress, target_reached)
+        };
+        let elapsed_before = progress.elapsed;
+        // The count may have been used before this run was started or
+        // resumed, so only the errors from now on are added.
+        let score_errors_before = progress.num_score_errors;
+        let score_errors_at_start = self.score_errors.as_ref().map_or(0, ScoreErrorCount::get);
+        let parameters = self.parameters;
+
+        let stop_reason = loop {
+            if initial_target_reached {
+                break StopReason::TargetReached;
+            }
+            if parameters
+                .max_generations
+                .is_some_and(|max_generations| progress.generation_number >= max_generations)
+            {
+                break StopReason::MaxGenerations;
+            }
+
+            progress.num_evaluations += self.generation.num_children();
+            next(&mut self.generation, rng.gen())?;
+
+            let population = self.generation.population();
+            let best = best_in(population)?;
+            if *best > progress.best_ever {
+                progress.best_ever = best.clone();
+                progress.generations_since_improvement = 0;
+            } else {
+                progress.generations_since_improvement += 1;
+            }
+            progress.elapsed = elapsed_before + start.elapsed();
+            if let Some(score_errors) = &self.score_errors {
+                progress.num_score_errors =
+                    score_errors_before + (score_errors.get() - score_errors_at_start);
+            }
+
+            let report = GenerationReport {
+                generation_number: progress.generation_number,
+                population,
+                best,
+                best_ever: &progress.best_ever,
+                num_evaluations: progress.num_evaluations,
+                num_score_errors: progress.num_score_errors,
+                elapsed: progress.elapsed,
+            };
+            for observer in &mut self.observers {
+                observer(&report);
+            }
+            progress.generation_number += 1;
+            let target_reached = self.target.as_ref().is_some_and(|target| target(best));
+            after_generation(&self.generation, &parameters, &progress, rng)?;
+
+            if target_reached {
+                break StopReason::TargetReached;
+            }
+            if parameters
+                .stagnation_limit
+                .is_some_and(|limit| progress.generations_since_improvement >= limit)
+            {
+                break StopReason::Stagnated;
+            }
+            if parameters
+                .time_budget
+                .is_some_and(|budget| progress.elapsed >= budget)
+            {
+                break StopReason::TimeBudgetExhausted;
+            }
+            if parameters
+                .max_evaluations
+                .is_some_and(|max_evaluations| progress.num_evaluations >= max_evaluations)
+            {
+                break StopReason::MaxEvaluations;
+            }
+        };
+
+        progress.elapsed = elapsed_before + start.elapsed();
+        let summary = RunSummary {
+            best: progress.best_ever.clone(),
+            num_generations: progress.generation_number,
+            num_evaluations: progress.num_evaluations,
+            num_score_errors: progress.num_score_errors,
+            elapsed: progress.elapsed,
+            stop_reason,
+        };
+        self.progress = Some(progress);
+        Ok(summary)
+    }
+}
+
+fn best_in<P>(population: &P) -> Result<&P::Individual>
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Ord,
+{
+    population
+        .into_iter()
+        .max()
+        .context("The population was empty")
+}
+
+impl<P, C, S> Run<'_, P, C, S>
+where
+    P: Population,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Ord + Clone,
+    C: Prepare<P>,
+    C::Prepared: for<'pop> Operator<&'pop P, Output = P::Individual>,
+    S: Survival<P>,
+{
+    /// Run the evolutionary process, making each generation serially.
+    ///
+    /// The seed for each generation is drawn from `rng`, so the run can be
+    /// replayed by providing an identically seeded `rng`.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if no stop condition was provided, if the
+    /// population is empty, or if making any generation fails.
+    pub fn serial_run<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+    ) -> Result<RunSummary<P::Individual>> {
+        self.run_with(rng, Generation::serial_next, |_, _, _, _| Ok(()))
+    }
+}
+
+impl<P, C, S> Run<'_, P, C, S>
+where
+    P: Population + Send + Sync,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Ord + Clone + Send,
+    C: Prepare<P>,
+    C::Prepared: for<'pop> Operator<&'pop P, Output = P::Individual> + Send + Sync,
+    S: Survival<P>,
+{
+    /// Run the evolutionary process, making each generation in parallel.
+    ///
+    /// The seed for each generation is drawn from `rng`, so the run can be
+    /// replayed by providing an identically seeded `rng`.
+    ///
+    /// # Errors
+    ///
+    /// This returns an error if no stop condition was provided, if the
+    /// population is empty, or if making any generation fails.
+    pub fn par_run<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Result<RunSummary<P::Individual>> {
+        self.run_with(rng, Generation::par_next, |_, _, _, _| Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::bail;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{
+        individual::{ec::EcIndividual, scorer::FnTryScorer},
+        operator::{
+            genome_extractor::GenomeExtractor,
+            genome_scorer::{GenomeScorer, Penalty},
+            selector::{best::Best, random::Random, Select},
+            Composable,
+        },
+    };
+
+    #[derive(Clone, Copy)]
+    struct Increment;
+
+    impl Operator<&i32> for Increment {
+        type Output = i32;
+
+        fn apply<R: Rng + ?Sized>(&self, input: &i32, _: &mut R) -> Result<Self::Output> {
+            Ok(input + 1)
+        }
+    }
+    impl Composable for Increment {}
+
+    #[derive(Clone, Copy)]
+    struct Same;
+
+    impl Operator<&i32> for Same {
+        type Output = i32;
+
+        fn apply<R: Rng + ?Sized>(&self, input: &i32, _: &mut R) -> Result<Self::Output> {
+            Ok(*input)
+        }
+    }
+    impl Composable for Same {}
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn stops_after_max_generations() {
+        let generation = Generation::new(Select::new(Best).then(Increment), vec![0, 1, 2]);
+        let mut num_reports = 0;
+        let summary = Run::new(generation)
+            .with_max_generations(5)
+            .with_observer(|_| num_reports += 1)
+            .serial_run(&mut StdRng::seed_from_u64(0))
+            .unwrap();
+        assert_eq!(StopReason::MaxGenerations, summary.stop_reason);
+        assert_eq!(5, summary.num_generations);
+        assert_eq!(7, summary.best);
+        assert_eq!(3 * 6, summary.num_evaluations);
+        assert_eq!(5, num_reports);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn stops_when_target_reached() {
+        let generation = Generation::new(Select::new(Best).then(Increment), vec![0, 1, 2]);
+        let summary = Run::new(generation)
+            .with_max_generations(100)
+            .with_target(|best| *best >= 10)
+            .par_run(&mut StdRng::seed_from_u64(0))
+            .unwrap();
+        assert_eq!(StopReason::TargetReached, summary.stop_reason);
+        assert_eq!(10, summary.best);
+        assert_eq!(8, summary.num_generations);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn stops_before_the_first_generation_if_target_already_reached() {
+        let generation = Generation::new(Select::new(Best).then(Increment), vec![0, 1, 12]);
+        let mut num_reports = 0;
+        let summary = Run::new(generation)
+            .with_max_generations(100)
+            .with_target(|best| *best >= 10)
+            .with_observer(|_| num_reports += 1)
+            .serial_run(&mut StdRng::seed_from_u64(0))
+            .unwrap();
+        assert_eq!(StopReason::TargetReached, summary.stop_reason);
+        assert_eq!(12, summary.best);
+        assert_eq!(0, summary.num_generations);
+        assert_eq!(3, summary.num_evaluations);
+        assert_eq!(0, num_reports);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn stops_when_stagnated() {
+        let generation = Generation::new(Select::new(Best).then(Same), vec![0, 1, 2]);
+        let summary = Run::new(generation)
+            .with_max_generations(100)
+            .with_stagnation_limit(3)
+            .serial_run(&mut StdRng::seed_from_u64(0))
+            .unwrap();
+        assert_eq!(StopReason::Stagnated, summary.stop_reason);
+        assert_eq!(3, summary.num_generations);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn stops_after_max_evaluations() {
+        let generation = Generation::new(Select::new(Best).then(Increment), vec![0, 1, 2]);
+        let summary = Run::new(generation)
+            .with_max_evaluations(10)
+            .serial_run(&mut StdRng::seed_from_u64(0))
+            .unwrap();
+        assert_eq!(StopReason::MaxEvaluations, summary.stop_reason);
+        assert_eq!(12, summary.num_evaluations);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn score_errors_are_reported() {
+        // Odd genomes can't be scored.
+        let scorer = FnTryScorer(|genome: &i32| {
+            if genome % 2 != 0 {
+                bail!("Can't score {genome}");
+            }
+            Ok(*genome)
+        });
+        let child_maker = GenomeScorer::fallible(Select::new(Random).then(GenomeExtractor), scorer)
+            .with_on_error(Penalty(-1));
+        let score_errors = child_maker.error_count().clone();
+        let population: Vec<_> = (0..10)
+            .map(|genome| EcIndividual::new(genome, genome))
+            .collect();
+
+        let mut reported = Vec::new();
+        let summary = Run::new(Generation::new(child_maker, population))
+            .with_max_generations(3)
+            .with_score_error_count(score_errors.clone())
+            .with_observer(|report| reported.push(report.num_score_errors))
+            .serial_run(&mut StdRng::seed_from_u64(0))
+            .unwrap();
+
+        assert!(summary.num_score_errors > 0);
+        assert_eq!(summary.num_score_errors, score_errors.get());
+        assert_eq!(reported.len(), 3);
+        assert!(reported.is_sorted());
+        assert_eq!(reported.last(), Some(&summary.num_score_errors));
+    }
+
+    #[test]
+    fn needs_a_stop_condition() {
+        let generation = Generation::new(Select::new(Best).then(Increment), vec![0, 1, 2]);
+        assert!(Run::new(generation)
+            .serial_run(&mut StdRng::seed_from_u64(0))
+            .is_err());
+    }
+}
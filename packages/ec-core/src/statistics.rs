@@ -0,0 +1,496 @@
+//! Summary statistics about a population, typically computed once per
+//! generation and printed or logged.
+
+use std::fmt::{Debug, Display};
+
+use anyhow::{ensure, Context, Result};
+use num_traits::ToPrimitive;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{individual::Individual, population::Population, test_results::TestResults};
+
+type IsSolved<'a, R> = Box<dyn Fn(&R) -> bool + 'a>;
+type GenomeLength<'a, G> = Box<dyn Fn(&G) -> usize + 'a>;
+type Distance<'a, G> = Box<dyn Fn(&G, &G) -> f64 + 'a>;
+
+/// The default maximum number of pairs of individuals used to compute
+/// mean pairwise distances.
+pub const DEFAULT_MAX_PAIRS: usize = 10_000;
+
+/// Computes a [`StatisticsReport`] for populations of individuals with
+/// genomes of type `G` and test results of type `TestResults<R>`.
+///
+/// By default the per-case solve counts, the genome lengths, and the
+/// mean distance between genomes aren't computed, since that requires
+/// knowing what it means for a test case to be solved and how to measure
+/// (the distance between) genomes; these can be provided with
+/// `with_is_solved`, `with_genome_length`, and `with_genome_distance`.
+pub struct Statistics<'a, G, R> {
+    is_solved: Option<IsSolved<'a, R>>,
+    genome_length: Option<GenomeLength<'a, G>>,
+    genome_distance: Option<Distance<'a, G>>,
+    max_pairs: usize,
+}
+
+impl<G, R> Default for Statistics<'_, G, R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a, G, R> Statistics<'a, G, R> {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            is_solved: None,
+            genome_length: None,
+            genome_distance: None,
+            max_pairs: DEFAULT_MAX_PAIRS,
+        }
+    }
+
+    /// Count how many individuals solve each test case, where a result
+    /// solves a case if `is_solved` returns `true` for it, e.g., if the
+    /// error is zero.
+    #[must_use]
+    pub fn with_is_solved(mut self, is_solved: impl Fn(&R) -> bool + 'a) -> Self {
+        self.is_solved = Some(Box::new(is_solved));
+        self
+    }
+
+    /// Report the distribution of genome lengths, using `genome_length`
+    /// to compute the length of each genome.
+    #[must_use]
+    pub fn with_genome_length(mut self, genome_length: impl Fn(&G) -> usize + 'a) -> Self {
+        self.genome_length = Some(Box::new(genome_length));
+        self
+    }
+
+    /// Report the mean distance between genomes, using `genome_distance`
+    /// (typically
+    /// [`GenomeDistance::distance`](crate::genome::GenomeDistance::distance))
+    /// to compute the distance between two genomes.
+    #[must_use]
+    pub fn with_genome_distance(mut self, genome_distance: impl Fn(&G, &G) -> f64 + 'a) -> Self {
+        self.genome_distance = Some(Box::new(genome_distance));
+        self
+    }
+
+    /// Compute mean pairwise distances from (at most) `max_pairs` pairs of
+    /// individuals, sampling the pairs if there are more than that. The
+    /// default is [`DEFAULT_MAX_PAIRS`].
+    #[must_use]
+    pub const fn with_max_pairs(mut self, max_pairs: usize) -> Self {
+        self.max_pairs = max_pairs;
+        self
+    }
+
+    /// Compute the statistics for `population`.
+    ///
+    /// # Errors
+    /// This returns an error if the population is empty, if the
+    /// individuals don't all have the same number of test results, or
+    /// if a total result can't be converted to an `f64`.
+    pub fn compute<P>(&self, population: &P) -> Result<StatisticsReport<R>>
+    where
+        P: Population,
+        for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+        P::Individual: Individual<Genome = G, TestResults = TestResults<R>>,
+        G: PartialEq,
+        R: Ord + Clone + ToPrimitive,
+    {
+        let individuals: Vec<_> = population.into_iter().collect();
+        ensure!(
+            !individuals.is_empty(),
+            "Can't compute statistics for an empty population"
+        );
+
+        let mut totals: Vec<&R> = individuals
+            .iter()
+            .map(|individual| &individual.test_results().total_result)
+            .collect();
+        totals.sort_unstable();
+        let total_values = totals
+            .iter()
+            .map(|total| {
+                total
+                    .to_f64()
+                    .context("A total result couldn't be converted to an f64")
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let (mean_total, std_dev_total) = mean_and_std_dev(&total_values);
+
+        let pairs = sample_pairs(individuals.len(), self.max_pairs);
+        let error_vectors = individuals
+            .iter()
+            .map(|individual| {
+                individual
+                    .test_results()
+                    .results
+                    .iter()
+                    .map(|result| {
+                        result
+                            .to_f64()
+                            .context("A result couldn't be converted to an f64")
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(StatisticsReport {
+            population_size: individuals.len(),
+            best_total: (*totals[totals.len() - 1]).clone(),
+            median_total: (*totals[totals.len() / 2]).clone(),
+            worst_total: (*totals[0]).clone(),
+            mean_total,
+            std_dev_total,
+            cases: self.case_statistics(&individuals)?,
+            genome_lengths: self.genome_length.as_ref().map(|genome_length| {
+                LengthDistribution::new(
+                    individuals
+                        .iter()
+                        .map(|individual| genome_length(individual.genome()))
+                        .collect(),
+                )
+            }),
+            num_distinct_genomes: num_distinct_genomes(&individuals),
+            num_distinct_error_vectors: num_distinct_error_vectors(&individuals),
+            error_vector_entropy: error_vector_entropy(&individuals),
+            mean_error_vector_distance: mean_distance(&pairs, |first, second| {
+                euclidean_distance(&error_vectors[first], &error_vectors[second])
+            }),
+            mean_genome_distance: self.genome_distance.as_ref().map(|genome_distance| {
+                mean_distance(&pairs, |first, second| {
+                    genome_distance(individuals[first].genome(), individuals[second].genome())
+                })
+            }),
+        })
+    }
+
+    fn case_statistics<I>(&self, individuals: &[&I]) -> Result<Vec<CaseStatistics<R>>>
+    where
+        I: Individual<Genome = G, TestResults = TestResults<R>>,
+        R: Ord + Clone,
+    {
+        let num_cases = individuals[0].test_results().results.len();
+        ensure!(
+            individuals
+                .iter()
+                .all(|individual| individual.test_results().results.len() == num_cases),
+            "The individuals don't all have the same number of test results"
+        );
+        (0..num_cases)
+            .map(|case| {
+                let results = individuals
+                    .iter()
+                    .map(|individual| &individual.test_results().results[case]);
+                let best = results.clone().max().context("No results for a case")?;
+                Ok(CaseStatistics {
+                    best: best.clone(),
+                    num_solved: self
+                        .is_solved
+                        .as_ref()
+                        .map(|is_solved| results.filter(|&result| is_solved(result)).count()),
+                })
+            })
+            .collect()
+    }
+}
+
+fn mean_and_std_dev(values: &[f64]) -> (f64, f64) {
+    let count = values.len().to_f64().unwrap_or(f64::NAN);
+    let mean = values.iter().sum::<f64>() / count;
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / count;
+    (mean, variance.sqrt())
+}
+
+// Genomes may only implement `PartialEq`, so this is quadratic in the
+// number of distinct genomes.
+fn num_distinct_genomes<I>(individuals: &[&I]) -> usize
+where
+    I: Individual,
+    I::Genome: PartialEq,
+{
+    let mut distinct: Vec<&I::Genome> = Vec::new();
+    for individual in individuals {
+        if !distinct.contains(&individual.genome()) {
+            distinct.push(individual.genome());
+        }
+    }
+    distinct.len()
+}
+
+fn num_distinct_error_vectors<I, R>(individuals: &[&I]) -> usize
+where
+    I: Individual<TestResults = TestResults<R>>,
+    R: Ord,
+{
+    let mut error_vectors: Vec<&Vec<R>> = individuals
+        .iter()
+        .map(|individual| &individual.test_results().results)
+        .collect();
+    error_vectors.sort_unstable();
+    error_vectors.dedup();
+    error_vectors.len()
+}
+
+/// The Shannon entropy (in bits) of the distribution of error vectors,
+/// which is zero when every individual has the same errors, and largest
+/// when every individual's errors are different.
+fn error_vector_entropy<I, R>(individuals: &[&I]) -> f64
+where
+    I: Individual<TestResults = TestResults<R>>,
+    R: Ord,
+{
+    let mut error_vectors: Vec<&Vec<R>> = individuals
+        .iter()
+        .map(|individual| &individual.test_results().results)
+        .collect();
+    error_vectors.sort_unstable();
+    let num_individuals = error_vectors.len().to_f64().unwrap_or(f64::NAN);
+    error_vectors
+        .chunk_by(|first, second| first == second)
+        .map(|group| {
+            let probability = group.len().to_f64().unwrap_or(f64::NAN) / num_individuals;
+            -probability * probability.log2()
+        })
+        .sum()
+}
+
+/// The pairs of (indices of) individuals to compute distances between:
+/// all of them if there are at most `max_pairs`, and otherwise a random
+/// sample of `max_pairs` of them.
+fn sample_pairs(num_individuals: usize, max_pairs: usize) -> Vec<(usize, usize)> {
+    let num_pairs = num_individuals * num_individuals.saturating_sub(1) / 2;
+    if num_pairs <= max_pairs {
+        return (0..num_individuals)
+            .flat_map(|first| ((first + 1)..num_individuals).map(move |second| (first, second)))
+            .collect();
+    }
+    // A fixed seed keeps the statistics the same from run to run, and
+    // doesn't disturb the run's random number generator.
+    let mut rng = StdRng::seed_from_u64(0);
+    (0..max_pairs)
+        .map(|_| {
+            let first = rng.gen_range(0..num_individuals);
+            let second = rng.gen_range(0..num_individuals - 1);
+            // Skip over `first` so the two are always different.
+            (first, if second >= first { second + 1 } else { second })
+        })
+        .collect()
+}
+
+/// The mean of `distance` over `pairs`, or zero if there aren't any pairs.
+fn mean_distance(pairs: &[(usize, usize)], distance: impl Fn(usize, usize) -> f64) -> f64 {
+    if pairs.is_empty() {
+        return 0.0;
+    }
+    let total: f64 = pairs
+        .iter()
+        .map(|&(first, second)| distance(first, second))
+        .sum();
+    total / pairs.len().to_f64().unwrap_or(f64::NAN)
+}
+
+fn euclidean_distance(first: &[f64], second: &[f64]) -> f64 {
+    first
+        .iter()
+        .zip(second)
+        .map(|(first, second)| (first - second).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+/// Statistics about a single population.
+///
+/// The "best" and "worst" results are the greatest and least results
+/// according to their `Ord` implementation, so (for example) the best
+/// `Error` is the smallest.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatisticsReport<R> {
+    pub population_size: usize,
+    pub best_total: R,
+    pub median_total: R,
+    pub worst_total: R,
+    pub mean_total: f64,
+    pub std_dev_total: f64,
+    /// Statistics for each test case, in the same order as the test
+    /// results.
+    pub cases: Vec<CaseStatistics<R>>,
+    /// `None` unless a genome length function was provided.
+    pub genome_lengths: Option<LengthDistribution>,
+    pub num_distinct_genomes: usize,
+    pub num_distinct_error_vectors: usize,
+    /// The Shannon entropy (in bits) of the distribution of error vectors.
+    pub error_vector_entropy: f64,
+    /// The mean Euclidean distance between the error vectors of (a sample
+    /// of) pairs of individuals, a measure of behavioral diversity.
+    pub mean_error_vector_distance: f64,
+    /// The mean distance between the genomes of (a sample of) pairs of
+    /// individuals, or `None` unless a genome distance function was
+    /// provided.
+    pub mean_genome_distance: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CaseStatistics<R> {
+    /// The best result on this case across the whole population.
+    pub best: R,
+    /// The number of individuals that solve this case, or `None` unless
+    /// a solve predicate was provided.
+    pub num_solved: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LengthDistribution {
+    pub min: usize,
+    pub median: usize,
+    pub max: usize,
+    pub mean: f64,
+}
+
+impl LengthDistribution {
+    fn new(mut lengths: Vec<usize>) -> Self {
+        lengths.sort_unstable();
+        let total: usize = lengths.iter().sum();
+        Self {
+            min: lengths.first().copied().unwrap_or_default(),
+            median: lengths.get(lengths.len() / 2).copied().unwrap_or_default(),
+            max: lengths.last().copied().unwrap_or_default(),
+            mean: total.to_f64().unwrap_or(f64::NAN) / lengths.len().to_f64().unwrap_or(f64::NAN),
+        }
+    }
+}
+
+impl<R: Debug> Display for StatisticsReport<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "  total: best {:?}, median {:?}, worst {:?}, mean {:.3} (std dev {:.3})",
+            self.best_total,
+            self.median_total,
+            self.worst_total,
+            self.mean_total,
+            self.std_dev_total
+        )?;
+        if let Some(lengths) = &self.genome_lengths {
+            writeln!(
+                f,
+                "  genome length: min {}, median {}, max {}, mean {:.1}",
+                lengths.min, lengths.median, lengths.max, lengths.mean
+            )?;
+        }
+        writeln!(
+            f,
+            "  distinct genomes: {}/{}, distinct error vectors: {}/{}",
+            self.num_distinct_genomes,
+            self.population_size,
+            self.num_distinct_error_vectors,
+            self.population_size
+        )?;
+        write!(
+            f,
+            "  diversity: error vector entropy {:.3} bits, mean error vector distance {:.3}",
+            self.error_vector_entropy, self.mean_error_vector_distance
+        )?;
+        if let Some(mean_genome_distance) = self.mean_genome_distance {
+            write!(f, ", mean genome distance {mean_genome_distance:.3}")?;
+        }
+        writeln!(f)?;
+        let best_per_case: Vec<_> = self.cases.iter().map(|case| &case.best).collect();
+        write!(f, "  best per case: {best_per_case:?}")?;
+        let solved: Option<Vec<_>> = self.cases.iter().map(|case| case.num_solved).collect();
+        if let Some(solved) = solved {
+            write!(f, "\n  solved per case: {solved:?}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{individual::ec::EcIndividual, test_results::Error};
+
+    fn individual(genome: &str, errors: Vec<i32>) -> EcIndividual<String, TestResults<Error<i32>>> {
+        EcIndividual::new(genome.to_string(), errors.into())
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn computes_statistics() {
+        let population = vec![
+            individual("a", vec![0, 4]),
+            individual("bb", vec![1, 1]),
+            individual("ccc", vec![0, 2]),
+            individual("bb", vec![1, 1]),
+        ];
+        let report = Statistics::new()
+            .with_is_solved(|error: &Error<i32>| error.error == 0)
+            .with_genome_length(String::len)
+            .with_genome_distance(|first: &String, second: &String| {
+                first.len().abs_diff(second.len()).to_f64().unwrap()
+            })
+            .compute(&population)
+            .unwrap();
+
+        assert_eq!(4, report.population_size);
+        assert_eq!(Error::from(2), report.best_total);
+        assert_eq!(Error::from(4), report.worst_total);
+        assert_eq!(Error::from(2), report.median_total);
+        assert!((report.mean_total - 2.5).abs() < f64::EPSILON);
+        assert!((report.std_dev_total - 0.75_f64.sqrt()).abs() < 1e-10);
+        assert_eq!(
+            vec![
+                CaseStatistics {
+                    best: Error::from(0),
+                    num_solved: Some(2)
+                },
+                CaseStatistics {
+                    best: Error::from(1),
+                    num_solved: Some(0)
+                }
+            ],
+            report.cases
+        );
+        assert_eq!(
+            Some(LengthDistribution {
+                min: 1,
+                median: 2,
+                max: 3,
+                mean: 2.0
+            }),
+            report.genome_lengths
+        );
+        assert_eq!(3, report.num_distinct_genomes);
+        assert_eq!(3, report.num_distinct_error_vectors);
+        // Two of the four individuals have the same errors.
+        assert!((report.error_vector_entropy - 1.5).abs() < 1e-10);
+        let expected_distance = 2.0f64.mul_add(10.0f64.sqrt() + 2.0f64.sqrt(), 2.0) / 6.0;
+        assert!((report.mean_error_vector_distance - expected_distance).abs() < 1e-10);
+        assert!((report.mean_genome_distance.unwrap() - 1.0).abs() < 1e-10);
+    }
+
+    #[test]
+    fn sampled_pairs_are_of_different_individuals() {
+        assert_eq!(sample_pairs(3, 10), vec![(0, 1), (0, 2), (1, 2)]);
+        let pairs = sample_pairs(100, 50);
+        assert_eq!(pairs.len(), 50);
+        assert!(pairs
+            .iter()
+            .all(|&(first, second)| first != second && first < 100 && second < 100));
+    }
+
+    #[test]
+    fn empty_population_is_an_error() {
+        let population: Vec<EcIndividual<String, TestResults<Error<i32>>>> = Vec::new();
+        assert!(Statistics::new().compute(&population).is_err());
+    }
+}
@@ -0,0 +1,413 @@
+//! Strategies for deciding which individuals make up the next population
+//! once a generation's children have been made.
+//!
+//! The default, [`Generational`], replaces the whole population with
+//! its children. The other strategies allow some or all of the parents
+//! to compete with their children for a place in the next population.
+
+use std::cmp::Reverse;
+
+use anyhow::{ensure, Context, Result};
+use num_traits::ToPrimitive;
+use rand::{prelude::IndexedRandom, Rng};
+
+use crate::{
+    individual::Individual, pareto::ParetoRanking, population::Population,
+    test_results::TestResults,
+};
+
+pub trait Survival<P>
+where
+    P: Population,
+{
+    /// The number of children that need to be made from `population`
+    /// in this generation.
+    fn num_children(&self, population: &P) -> usize;
+
+    /// Combine the `parents` and their newly made `children` into the next
+    /// population.
+    ///
+    /// # Errors
+    /// This will return an error if the strategy can't be applied to
+    /// the given parents and children, e.g., if there are too few children.
+    fn survivors<R: Rng + ?Sized>(
+        &self,
+        parents: &P,
+        children: Vec<P::Individual>,
+        rng: &mut R,
+    ) -> Result<P>;
+}
+
+/// Replace the entire population with the same number of children.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Generational;
+
+impl<P> Survival<P> for Generational
+where
+    P: Population + FromIterator<P::Individual>,
+{
+    fn num_children(&self, population: &P) -> usize {
+        population.size()
+    }
+
+    fn survivors<R: Rng + ?Sized>(
+        &self,
+        _: &P,
+        children: Vec<P::Individual>,
+        _: &mut R,
+    ) -> Result<P> {
+        Ok(children.into_iter().collect())
+    }
+}
+
+/// Carry the best `num_elites` parents over unchanged, and fill the
+/// rest of the population with children.
+#[derive(Debug, Clone, Copy)]
+pub struct Elitism {
+    num_elites: usize,
+}
+
+impl Elitism {
+    #[must_use]
+    pub const fn new(num_elites: usize) -> Self {
+        Self { num_elites }
+    }
+}
+
+impl<P> Survival<P> for Elitism
+where
+    P: Population + FromIterator<P::Individual>,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Ord + Clone,
+{
+    fn num_children(&self, population: &P) -> usize {
+        population.size().saturating_sub(self.num_elites)
+    }
+
+    fn survivors<R: Rng + ?Sized>(
+        &self,
+        parents: &P,
+        children: Vec<P::Individual>,
+        _: &mut R,
+    ) -> Result<P> {
+        ensure!(
+            parents.size() >= self.num_elites,
+            "The population had size {} and we wanted to keep {} elites",
+            parents.size(),
+            self.num_elites
+        );
+        let mut ranked_parents: Vec<_> = parents.into_iter().collect();
+        ranked_parents.sort_unstable_by_key(|&parent| Reverse(parent));
+        Ok(ranked_parents
+            .into_iter()
+            .take(self.num_elites)
+            .cloned()
+            .chain(children)
+            .collect())
+    }
+}
+
+/// (μ+λ) survivor selection: make `num_children` (λ) children, and keep
+/// the best μ of the parents and children combined, where μ is the
+/// current population size.
+#[derive(Debug, Clone, Copy)]
+pub struct MuPlusLambda {
+    num_children: usize,
+}
+
+impl MuPlusLambda {
+    #[must_use]
+    pub const fn new(num_children: usize) -> Self {
+        Self { num_children }
+    }
+}
+
+impl<P> Survival<P> for MuPlusLambda
+where
+    P: Population + FromIterator<P::Individual>,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Ord + Clone,
+{
+    fn num_children(&self, _: &P) -> usize {
+        self.num_children
+    }
+
+    fn survivors<R: Rng + ?Sized>(
+        &self,
+        parents: &P,
+        mut children: Vec<P::Individual>,
+        _: &mut R,
+    ) -> Result<P> {
+        let mu = parents.size();
+        let mut ranked_parents: Vec<_> = parents.into_iter().collect();
+        ranked_parents.sort_unstable_by_key(|&parent| Reverse(parent));
+        children.sort_unstable_by(|a, b| b.cmp(a));
+
+        // Merge the two (sorted) lists, only cloning the parents that
+        // actually survive.
+        let mut ranked_parents = ranked_parents.into_iter().peekable();
+        let mut children = children.into_iter().peekable();
+        let mut survivors = Vec::with_capacity(mu);
+        while survivors.len() < mu {
+            let take_parent = match (ranked_parents.peek(), children.peek()) {
+                (Some(parent), Some(child)) => *parent >= child,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            if take_parent {
+                survivors.extend(ranked_parents.next().cloned());
+            } else {
+                survivors.extend(children.next());
+            }
+        }
+        Ok(survivors.into_iter().collect())
+    }
+}
+
+/// (μ,λ) survivor selection: make `num_children` (λ) children, and keep
+/// the best μ children, where μ is the current population size. None of
+/// the parents survive, so λ must be at least μ.
+#[derive(Debug, Clone, Copy)]
+pub struct MuCommaLambda {
+    num_children: usize,
+}
+
+impl MuCommaLambda {
+    #[must_use]
+    pub const fn new(num_children: usize) -> Self {
+        Self { num_children }
+    }
+}
+
+impl<P> Survival<P> for MuCommaLambda
+where
+    P: Population + FromIterator<P::Individual>,
+    P::Individual: Ord,
+{
+    fn num_children(&self, _: &P) -> usize {
+        self.num_children
+    }
+
+    fn survivors<R: Rng + ?Sized>(
+        &self,
+        parents: &P,
+        mut children: Vec<P::Individual>,
+        _: &mut R,
+    ) -> Result<P> {
+        let mu = parents.size();
+        ensure!(
+            children.len() >= mu,
+            "(μ,λ) selection needs at least μ = {mu} children, but only got {}",
+            children.len()
+        );
+        children.sort_unstable_by(|a, b| b.cmp(a));
+        children.truncate(mu);
+        Ok(children.into_iter().collect())
+    }
+}
+
+/// Steady-state replacement: each child replaces the loser (i.e., the
+/// worst individual) of a tournament of size `tournament_size` held in
+/// the current population.
+///
+/// All `num_children` children in a generation are made from the
+/// population as it was at the start of that generation, which allows
+/// them to be made in parallel; with `num_children` set to 1 this is
+/// classic steady-state replacement.
+#[derive(Debug, Clone, Copy)]
+pub struct SteadyState {
+    num_children: usize,
+    tournament_size: usize,
+}
+
+impl SteadyState {
+    #[must_use]
+    pub const fn new(num_children: usize, tournament_size: usize) -> Self {
+        Self {
+            num_children,
+            tournament_size,
+        }
+    }
+}
+
+impl<P> Survival<P> for SteadyState
+where
+    P: Population + FromIterator<P::Individual>,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Ord + Clone,
+{
+    fn num_children(&self, _: &P) -> usize {
+        self.num_children
+    }
+
+    fn survivors<R: Rng + ?Sized>(
+        &self,
+        parents: &P,
+        children: Vec<P::Individual>,
+        rng: &mut R,
+    ) -> Result<P> {
+        ensure!(
+            parents.size() >= self.tournament_size,
+            "The population had size {} and we wanted a tournament of size {}",
+            parents.size(),
+            self.tournament_size
+        );
+        let mut population: Vec<P::Individual> = parents.into_iter().cloned().collect();
+        let indices: Vec<usize> = (0..population.len()).collect();
+        for child in children {
+            let loser = *indices
+                .choose_multiple(rng, self.tournament_size)
+                .min_by(|&&x, &&y| population[x].cmp(&population[y]))
+                .context("The replacement tournament was empty")?;
+            population[loser] = child;
+        }
+        Ok(population.into_iter().collect())
+    }
+}
+
+/// NSGA-II's elitist survivor selection.
+///
+/// The parents and the same number of children are combined and sorted
+/// into non-dominated fronts (treating each test result as an objective),
+/// and the next population is filled front by front. If the last front
+/// doesn't fit, its least crowded individuals are kept.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Nsga2;
+
+impl<P, T> Survival<P> for Nsga2
+where
+    P: Population + FromIterator<P::Individual>,
+    for<'pop> &'pop P: IntoIterator<Item = &'pop P::Individual>,
+    P::Individual: Individual<TestResults = TestResults<T>> + Clone,
+    T: Ord + ToPrimitive,
+{
+    fn num_children(&self, population: &P) -> usize {
+        population.size()
+    }
+
+    fn survivors<R: Rng + ?Sized>(
+        &self,
+        parents: &P,
+        children: Vec<P::Individual>,
+        _: &mut R,
+    ) -> Result<P> {
+        let num_survivors = parents.size();
+        let mut combined: Vec<Option<P::Individual>> = parents
+            .into_iter()
+            .cloned()
+            .chain(children)
+            .map(Some)
+            .collect();
+        let results: Vec<_> = combined
+            .iter()
+            .flatten()
+            .map(Individual::test_results)
+            .collect();
+        let ranking = ParetoRanking::new(&results)?;
+
+        let mut survivors = Vec::with_capacity(num_survivors);
+        for front in ranking.fronts() {
+            let remaining = num_survivors - survivors.len();
+            if remaining == 0 {
+                break;
+            }
+            let mut front = front.clone();
+            if front.len() > remaining {
+                front.sort_unstable_by(|&a, &b| ranking.crowded_cmp(b, a));
+                front.truncate(remaining);
+            }
+            survivors.extend(front.into_iter().filter_map(|index| combined[index].take()));
+        }
+        Ok(survivors.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn elitism_keeps_the_best_parents() {
+        let parents = vec![5, 8, 9, 6, 3];
+        let elitism = Elitism::new(2);
+        assert_eq!(3, elitism.num_children(&parents));
+        let next = elitism
+            .survivors(&parents, vec![0, 1, 2], &mut thread_rng())
+            .unwrap();
+        assert_eq!(vec![9, 8, 0, 1, 2], next);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn mu_plus_lambda_keeps_best_of_both() {
+        let parents = vec![5, 8, 1, 6];
+        let next = MuPlusLambda::new(3)
+            .survivors(&parents, vec![7, 0, 9], &mut thread_rng())
+            .unwrap();
+        assert_eq!(vec![9, 8, 7, 6], next);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn mu_comma_lambda_keeps_best_children() {
+        let parents = vec![50, 80, 90];
+        let next = MuCommaLambda::new(5)
+            .survivors(&parents, vec![7, 0, 9, 3, 4], &mut thread_rng())
+            .unwrap();
+        assert_eq!(vec![9, 7, 4], next);
+    }
+
+    #[test]
+    fn mu_comma_lambda_needs_enough_children() {
+        let parents = vec![50, 80, 90];
+        assert!(MuCommaLambda::new(2)
+            .survivors(&parents, vec![7, 0], &mut thread_rng())
+            .is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn steady_state_replaces_the_worst_with_full_tournament() {
+        let parents = vec![5, 8, 1, 6];
+        // With a tournament as large as the population, the loser is always
+        // the worst individual.
+        let next = SteadyState::new(2, 4)
+            .survivors(&parents, vec![10, 20], &mut thread_rng())
+            .unwrap();
+        assert_eq!(vec![20, 8, 10, 6], next);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn nsga2_keeps_the_best_fronts() {
+        use crate::{individual::ec::EcIndividual, test_results::Error};
+
+        let individual = |genome: i32, objectives: [i32; 2]| {
+            EcIndividual::<_, TestResults<Error<i32>>>::new(genome, objectives.to_vec().into())
+        };
+        let parents = vec![
+            individual(0, [5, 5]),
+            individual(1, [0, 4]),
+            individual(2, [3, 3]),
+        ];
+        let children = vec![
+            individual(3, [4, 0]),
+            individual(4, [1, 3]),
+            individual(5, [2, 1]),
+        ];
+        assert_eq!(3, Nsga2.num_children(&parents));
+        let next = Nsga2
+            .survivors(&parents, children, &mut thread_rng())
+            .unwrap();
+        let mut genomes: Vec<_> = next.iter().map(|individual| individual.genome).collect();
+        genomes.sort_unstable();
+        // The first front has four individuals, so the extremes (which are
+        // infinitely far from their neighbors) and the less crowded of the
+        // two interior individuals survive.
+        assert_eq!(vec![1, 3, 5], genomes);
+    }
+}
@@ -1,8 +1,8 @@
 use std::{cmp::Ordering, fmt::Debug, iter::Sum};
 
-// TODO: We can probably use things in the `num` family of traits
-//   (https://github.com/rust-num/num) to genericize `Score` and
-//   `Error` so they're not tied to `i64`s anymore.
+use anyhow::{bail, ensure, Context, Result};
+use num_traits::{NumCast, ToPrimitive};
+
 // TODO: I think that we want `Score` and `Error` to implement
 //   some common trait so that we can mixed vectors of `Score`s
 //   and `Error`s. Or maybe we already have that? Do (Partial)Ord,
@@ -19,12 +19,52 @@ use std::{cmp::Ordering, fmt::Debug, iter::Sum};
 //   anymore. At a minimum we should try to push those requirements
 //   closer to where they're actually needed.
 
+/// Whether `value` is a NaN, i.e., isn't equal to itself.
+#[allow(clippy::eq_op)]
+fn is_nan<T: PartialEq>(value: &T) -> bool {
+    value != value
+}
+
+/// Compare `x` and `y`, where NaNs are less than every other value (and
+/// equal to each other).
+fn cmp_nan_least<T: PartialOrd>(x: &T, y: &T) -> Ordering {
+    x.partial_cmp(y)
+        .unwrap_or_else(|| is_nan(y).cmp(&is_nan(x)))
+}
+
 /// Score implicitly follows a "bigger is better" model.
-#[derive(Eq, PartialEq, Ord, PartialOrd)]
+///
+/// Scores can be any numeric type, including floats. A NaN score is worse
+/// than every other score (and equal to other NaN scores), so `Score<f64>`
+/// is totally ordered and doesn't need to be wrapped in something like
+/// `OrderedFloat`. Two scores are equal exactly when `cmp` says they are;
+/// any other values that can't be compared are treated as equal as well.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Score<T> {
     pub score: T,
 }
 
+impl<T: PartialOrd> PartialEq for Score<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: PartialOrd> Eq for Score<T> {}
+
+impl<T: PartialOrd> Ord for Score<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp_nan_least(&self.score, &other.score)
+    }
+}
+
+impl<T: PartialOrd> PartialOrd for Score<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 impl<T: Debug> Debug for Score<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("{:?}", self.score))
@@ -67,30 +107,60 @@ where
     }
 }
 
+// This allows statistics like the mean and standard deviation to be
+// computed over `Score`s and `Error`s.
+impl<T: ToPrimitive> ToPrimitive for Score<T> {
+    fn to_i64(&self) -> Option<i64> {
+        self.score.to_i64()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.score.to_u64()
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        self.score.to_f64()
+    }
+}
+
 // TODO: Rewrite `Error` using the std::cmp::Reverse type
 //   to convert `Score` to `Error`.
-#[derive(Eq, PartialEq)]
+/// Error follows a "smaller is better" model.
+///
+/// Like [`Score`], a NaN error is worse than every other error, and two
+/// errors are equal exactly when `cmp` says they are.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Error<T> {
     pub error: T,
 }
 
+impl<T: PartialOrd> PartialEq for Error<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: PartialOrd> Eq for Error<T> {}
+
 impl<T: Debug> Debug for Error<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.write_fmt(format_args!("{:?}", self.error))
     }
 }
 
-impl<T: Ord> Ord for Error<T> {
+impl<T: PartialOrd> Ord for Error<T> {
     fn cmp(&self, other: &Self) -> Ordering {
-        self.error.cmp(&other.error).reverse()
+        self.error.partial_cmp(&other.error).map_or_else(
+            || is_nan(&other.error).cmp(&is_nan(&self.error)),
+            Ordering::reverse,
+        )
     }
 }
 
 impl<T: PartialOrd> PartialOrd for Error<T> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.error
-            .partial_cmp(&other.error)
-            .map(std::cmp::Ordering::reverse)
+        Some(self.cmp(other))
     }
 }
 
@@ -130,6 +200,44 @@ where
     }
 }
 
+impl<T: ToPrimitive> ToPrimitive for Error<T> {
+    fn to_i64(&self) -> Option<i64> {
+        self.error.to_i64()
+    }
+
+    fn to_u64(&self) -> Option<u64> {
+        self.error.to_u64()
+    }
+
+    fn to_f64(&self) -> Option<f64> {
+        self.error.to_f64()
+    }
+}
+
+/// A "bigger is better" value for a result, used by fitness-proportional
+/// selection.
+///
+/// `Score`s are used as they are (and may be negative), while `Error`s are
+/// inverted to `1 / (1 + error)`, so an error of zero has a fitness of one.
+/// NaN results have a NaN fitness. This returns `None` if the result can't
+/// be converted to an `f64`, or for negative errors.
+pub trait Fitness {
+    fn fitness(&self) -> Option<f64>;
+}
+
+impl<T: ToPrimitive> Fitness for Score<T> {
+    fn fitness(&self) -> Option<f64> {
+        self.score.to_f64()
+    }
+}
+
+impl<T: ToPrimitive> Fitness for Error<T> {
+    fn fitness(&self) -> Option<f64> {
+        let error = self.error.to_f64()?;
+        (error >= 0.0 || error.is_nan()).then(|| 1.0 / (1.0 + error))
+    }
+}
+
 #[cfg(test)]
 mod score_error_tests {
     use super::*;
@@ -148,6 +256,42 @@ mod score_error_tests {
         assert_eq!(first.partial_cmp(&first), Some(Ordering::Equal));
     }
 
+    #[test]
+    fn errors_are_inverted_for_fitness() {
+        assert_eq!(Some(-3.0), Score::from(-3).fitness());
+        assert_eq!(Some(1.0), Error::from(0).fitness());
+        assert_eq!(Some(0.25), Error::from(3).fitness());
+        assert_eq!(None, Error::from(-1).fitness());
+        assert!(Error::from(f64::NAN).fitness().is_some_and(f64::is_nan));
+    }
+
+    /// Values that can only be compared when they're the same, like sets.
+    #[derive(PartialEq)]
+    struct Incomparable(i32);
+
+    impl PartialOrd for Incomparable {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            (self.0 == other.0).then_some(Ordering::Equal)
+        }
+    }
+
+    #[test]
+    fn equality_agrees_with_cmp() {
+        let nan = Score::from(f64::NAN);
+        assert_eq!(nan, Score::from(f64::NAN));
+        assert_ne!(nan, Score::from(0.0));
+        assert_eq!(Error::from(f64::NAN), Error::from(f64::NAN));
+
+        let first = Score::from(Incomparable(1));
+        let second = Score::from(Incomparable(2));
+        assert_eq!(first.cmp(&second), Ordering::Equal);
+        assert!(first == second);
+        let first = Error::from(Incomparable(1));
+        let second = Error::from(Incomparable(2));
+        assert_eq!(first.cmp(&second), Ordering::Equal);
+        assert!(first == second);
+    }
+
     #[test]
     fn error_smaller_is_better() {
         let first = Error { error: 37 };
@@ -163,12 +307,23 @@ mod score_error_tests {
     }
 }
 
-#[derive(Eq, PartialEq)]
 pub enum TestResult<S, E> {
     Score(Score<S>),
     Error(Error<E>),
 }
 
+impl<S: PartialOrd, E: PartialOrd> PartialEq for TestResult<S, E> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Score(self_score), Self::Score(other_score)) => self_score == other_score,
+            (Self::Error(self_error), Self::Error(other_error)) => self_error == other_error,
+            _ => false,
+        }
+    }
+}
+
+impl<S: PartialOrd, E: PartialOrd> Eq for TestResult<S, E> {}
+
 impl<S: PartialOrd, E: PartialOrd> PartialOrd for TestResult<S, E> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         match (self, other) {
@@ -219,7 +374,8 @@ mod test_result_tests {
     }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TestResults<R> {
     pub results: Vec<R>,
     pub total_result: R,
@@ -237,6 +393,29 @@ impl<R: PartialOrd> PartialOrd for TestResults<R> {
     }
 }
 
+impl<R: Ord> TestResults<R> {
+    /// Whether these results Pareto-dominate `other`, treating each entry
+    /// of `results` as a separate objective: they have to be at least as
+    /// good on every objective, and better on at least one.
+    ///
+    /// Results with different numbers of objectives never dominate each
+    /// other.
+    #[must_use]
+    pub fn dominates(&self, other: &Self) -> bool {
+        self.results.len() == other.results.len()
+            && self
+                .results
+                .iter()
+                .zip(&other.results)
+                .all(|(ours, theirs)| ours >= theirs)
+            && self
+                .results
+                .iter()
+                .zip(&other.results)
+                .any(|(ours, theirs)| ours > theirs)
+    }
+}
+
 /*
  * I can't implement `From` for both a `Vec` and an `Iterator` because there
  * are potentially conflicting implementations then. (The reasons are a bit
@@ -287,6 +466,223 @@ where
     }
 }
 
+/// Test results that wrap a single number, like [`Score`] and [`Error`].
+pub trait NumericResult: From<Self::Value> {
+    type Value;
+
+    fn value(&self) -> &Self::Value;
+}
+
+impl<T> NumericResult for Score<T> {
+    type Value = T;
+
+    fn value(&self) -> &T {
+        &self.score
+    }
+}
+
+impl<T> NumericResult for Error<T> {
+    type Value = T;
+
+    fn value(&self) -> &T {
+        &self.error
+    }
+}
+
+/// What a [`TestResultsBuilder`] does with NaN results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NanPolicy<T> {
+    /// Keep NaNs, which are worse than every other result. Any aggregate of
+    /// results that include a NaN is also NaN.
+    #[default]
+    Worst,
+    /// Return an error, e.g., so the genome can be given a penalty by its
+    /// [`GenomeScorer`](crate::operator::genome_scorer::GenomeScorer).
+    Reject,
+    /// Replace NaNs with this (typically terrible) value.
+    Penalty(T),
+}
+
+/// How the `total_result` of [`TestResults`] is computed from the results.
+///
+/// Everything other than `Sum`, `Min`, and `Max` is computed with `f64`s
+/// and then converted back, so for integer results the mean (for example)
+/// is rounded towards zero.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Aggregate {
+    #[default]
+    Sum,
+    Mean,
+    /// The smallest result, e.g., the worst score.
+    Min,
+    /// The largest result, e.g., the worst error.
+    Max,
+    /// The root of the mean of the squared results, i.e., the RMSE if the
+    /// results are errors.
+    Rmse,
+    /// The sum of the results multiplied by the corresponding weights; there
+    /// has to be one weight per result.
+    WeightedSum(Vec<f64>),
+}
+
+impl Aggregate {
+    /// Aggregate `values`, which may include NaNs.
+    ///
+    /// # Errors
+    /// This returns an error if there are no values (for anything other
+    /// than `Sum`), the wrong number of weights, or the values can't be
+    /// converted to and from `f64`s.
+    pub fn aggregate<T>(&self, values: &[T]) -> Result<T>
+    where
+        T: PartialOrd + Clone + Sum + ToPrimitive + NumCast,
+    {
+        if matches!(self, Self::Sum) {
+            return Ok(values.iter().cloned().sum());
+        }
+        ensure!(
+            !values.is_empty(),
+            "Can't aggregate an empty set of results"
+        );
+        let extreme = |ordering: Ordering| {
+            values
+                .iter()
+                .find(|value| is_nan(*value))
+                .or_else(|| {
+                    values.iter().reduce(|best, value| {
+                        if value.partial_cmp(best) == Some(ordering) {
+                            value
+                        } else {
+                            best
+                        }
+                    })
+                })
+                .cloned()
+                .context("Can't aggregate an empty set of results")
+        };
+        let as_f64s = || {
+            values
+                .iter()
+                .map(|value| {
+                    value
+                        .to_f64()
+                        .context("Couldn't convert a result to an f64")
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+        let len = values.len().to_f64().unwrap_or(f64::NAN);
+        let total = match self {
+            Self::Sum => unreachable!("Sums are handled above"),
+            Self::Min => return extreme(Ordering::Less),
+            Self::Max => return extreme(Ordering::Greater),
+            Self::Mean => as_f64s()?.into_iter().sum::<f64>() / len,
+            Self::Rmse => (as_f64s()?
+                .into_iter()
+                .map(|value| value * value)
+                .sum::<f64>()
+                / len)
+                .sqrt(),
+            Self::WeightedSum(weights) => {
+                ensure!(
+                    weights.len() == values.len(),
+                    "There were {} weights for {} results",
+                    weights.len(),
+                    values.len()
+                );
+                as_f64s()?
+                    .into_iter()
+                    .zip(weights)
+                    .map(|(value, weight)| value * weight)
+                    .sum()
+            }
+        };
+        T::from(total).with_context(|| format!("Couldn't convert the aggregate {total} back"))
+    }
+}
+
+/// Builds [`TestResults`] from numeric results, using the given NaN policy
+/// and aggregate for the `total_result`.
+///
+/// By default NaNs are kept (as the worst results) and the total is the
+/// sum, which matches collecting the results into `TestResults`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResultsBuilder<T> {
+    aggregate: Aggregate,
+    nan_policy: NanPolicy<T>,
+}
+
+impl<T> Default for TestResultsBuilder<T> {
+    fn default() -> Self {
+        Self {
+            aggregate: Aggregate::Sum,
+            nan_policy: NanPolicy::Worst,
+        }
+    }
+}
+
+impl<T> TestResultsBuilder<T> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_aggregate(mut self, aggregate: Aggregate) -> Self {
+        self.aggregate = aggregate;
+        self
+    }
+
+    #[must_use]
+    pub fn with_nan_policy(mut self, nan_policy: NanPolicy<T>) -> Self {
+        self.nan_policy = nan_policy;
+        self
+    }
+}
+
+impl<T> TestResultsBuilder<T>
+where
+    T: PartialOrd + Clone + Sum + ToPrimitive + NumCast,
+{
+    /// Build the `TestResults` for `values`, e.g., the errors of a
+    /// program on each test case.
+    ///
+    /// # Errors
+    /// This returns an error if a value is NaN and the policy is
+    /// [`NanPolicy::Reject`], or if the values can't be aggregated (see
+    /// [`Aggregate::aggregate`]).
+    pub fn build<R, V>(&self, values: impl IntoIterator<Item = V>) -> Result<TestResults<R>>
+    where
+        R: NumericResult<Value = T>,
+        V: Into<R>,
+    {
+        let results = values
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| {
+                let result = value.into();
+                if !is_nan(result.value()) {
+                    return Ok(result);
+                }
+                match &self.nan_policy {
+                    NanPolicy::Worst => Ok(result),
+                    NanPolicy::Reject => bail!("The result of test case {index} was NaN"),
+                    NanPolicy::Penalty(penalty) => Ok(R::from(penalty.clone())),
+                }
+            })
+            .collect::<Result<Vec<R>>>()?;
+        let values = results
+            .iter()
+            .map(|result| result.value().clone())
+            .collect::<Vec<_>>();
+        let total_result = R::from(self.aggregate.aggregate(&values)?);
+        Ok(TestResults {
+            results,
+            total_result,
+        })
+    }
+}
+
 #[cfg(test)]
 mod test_results_from_vec {
     use super::*;
@@ -330,6 +726,18 @@ mod test_results_from_vec {
         assert_eq!(test_results.total_result, errors.into_iter().sum());
     }
 
+    #[test]
+    fn dominance() {
+        let first: TestResults<Error<i32>> = vec![1, 2].into();
+        let second: TestResults<Error<i32>> = vec![1, 3].into();
+        let third: TestResults<Error<i32>> = vec![0, 4].into();
+        assert!(first.dominates(&second));
+        assert!(!second.dominates(&first));
+        assert!(!first.dominates(&first));
+        assert!(!first.dominates(&third));
+        assert!(!third.dominates(&first));
+    }
+
     #[test]
     fn create_test_results_from_iter_scores() {
         let scores = vec![5, 8, 0, 9];
@@ -339,3 +747,88 @@ mod test_results_from_vec {
         assert_eq!(test_results.total_result, scores.into_iter().sum());
     }
 }
+
+#[cfg(test)]
+mod numeric_results {
+    use super::*;
+
+    #[test]
+    fn nan_is_the_worst_score_and_error() {
+        let mut scores = [2.0, f64::NAN, -1.0].map(Score::from);
+        scores.sort();
+        assert!(scores[0].score.is_nan());
+        assert_eq!(scores.iter().max().map(|score| score.score), Some(2.0));
+        assert_eq!(Score::from(f64::NAN), Score::from(f64::NAN));
+
+        let mut errors = [2.0, f64::NAN, -1.0].map(Error::from);
+        errors.sort();
+        assert!(errors[0].error.is_nan());
+        assert_eq!(errors.iter().max().map(|error| error.error), Some(-1.0));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn float_test_results_are_ordered() {
+        let good: TestResults<Error<f64>> = vec![0.5, 0.25].into();
+        let bad: TestResults<Error<f64>> = vec![0.5, f64::NAN].into();
+        assert!(good > bad);
+        assert_eq!([&good, &bad].into_iter().max().unwrap(), &good);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn nan_policies() {
+        let values = [1.0, f64::NAN, 3.0];
+
+        let worst: TestResults<Error<f64>> = TestResultsBuilder::new().build(values).unwrap();
+        assert!(worst.results[1].error.is_nan());
+        assert!(worst.total_result.error.is_nan());
+
+        let rejected: Result<TestResults<Error<f64>>> = TestResultsBuilder::new()
+            .with_nan_policy(NanPolicy::Reject)
+            .build(values);
+        assert!(format!("{:#}", rejected.unwrap_err()).contains("test case 1"));
+
+        let penalized: TestResults<Error<f64>> = TestResultsBuilder::new()
+            .with_nan_policy(NanPolicy::Penalty(100.0))
+            .build(values)
+            .unwrap();
+        assert_eq!(penalized.results[1], Error::from(100.0));
+        assert_eq!(penalized.total_result, Error::from(104.0));
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn aggregates() {
+        let values = [3.0, -4.0, 1.0];
+        let total = |aggregate| {
+            TestResultsBuilder::new()
+                .with_aggregate(aggregate)
+                .build::<Score<f64>, _>(values)
+                .unwrap()
+                .total_result
+                .score
+        };
+        assert!((total(Aggregate::Sum) - 0.0).abs() < f64::EPSILON);
+        assert!((total(Aggregate::Mean) - 0.0).abs() < f64::EPSILON);
+        assert!((total(Aggregate::Min) - -4.0).abs() < f64::EPSILON);
+        assert!((total(Aggregate::Max) - 3.0).abs() < f64::EPSILON);
+        assert!((total(Aggregate::Rmse) - (26.0_f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert!((total(Aggregate::WeightedSum(vec![1.0, 0.5, 2.0])) - 3.0).abs() < f64::EPSILON);
+
+        assert!(TestResultsBuilder::new()
+            .with_aggregate(Aggregate::WeightedSum(vec![1.0]))
+            .build::<Score<f64>, _>(values)
+            .is_err());
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn integer_aggregates_round_towards_zero() {
+        let results: TestResults<Error<i64>> = TestResultsBuilder::new()
+            .with_aggregate(Aggregate::Mean)
+            .build([1, 2, 2])
+            .unwrap();
+        assert_eq!(results.total_result, Error::from(1));
+    }
+}
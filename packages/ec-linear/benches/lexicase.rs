@@ -0,0 +1,115 @@
+//! Compares `Lexicase` and `FastLexicase` on HIFF populations, timing a
+//! generation's worth of selections (including, for `FastLexicase`, the
+//! once-per-generation preparation).
+//!
+//! Run with `cargo bench -p ec-linear --bench lexicase`.
+
+use std::{hint::black_box, iter::once};
+
+use anyhow::Result;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use ec_core::{
+    individual::ec::EcIndividual,
+    operator::{
+        selector::{fast_lexicase::FastLexicase, lexicase::Lexicase, Selector},
+        Prepare,
+    },
+    test_results::{Score, TestResults},
+};
+use ec_linear::genome::bitstring::Bitstring;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const NUM_BITS: usize = 128;
+const NUM_TEST_CASES: usize = 2 * NUM_BITS - 1;
+const POPULATION_SIZE: usize = 1_000;
+
+type HiffIndividual = EcIndividual<Bitstring, TestResults<Score<usize>>>;
+
+fn hiff(bits: &[bool]) -> (bool, TestResults<Score<usize>>) {
+    let len = bits.len();
+    if len < 2 {
+        (true, once(Score::from(len)).collect())
+    } else {
+        let half_len = len / 2;
+        let (left_all_same, left_score) = hiff(&bits[..half_len]);
+        let (right_all_same, right_score) = hiff(&bits[half_len..]);
+        let all_same = left_all_same && right_all_same && bits[0] == bits[half_len];
+
+        (
+            all_same,
+            left_score
+                .results
+                .into_iter()
+                .chain(right_score.results)
+                .chain(once(Score::from(if all_same { len } else { 0 })))
+                .collect(),
+        )
+    }
+}
+
+/// A population made up of copies of `num_distinct` random bitstrings.
+/// Fewer distinct individuals mimics a population that has started to
+/// converge, which is where collapsing duplicates should help the most.
+fn population(num_distinct: usize, rng: &mut impl Rng) -> Vec<HiffIndividual> {
+    let distinct: Vec<_> = (0..num_distinct)
+        .map(|_| {
+            let genome = Bitstring::random(NUM_BITS, rng);
+            let (_, test_results) = hiff(&genome.bits);
+            EcIndividual::new(genome, test_results)
+        })
+        .collect();
+    distinct
+        .iter()
+        .cycle()
+        .take(POPULATION_SIZE)
+        .cloned()
+        .collect()
+}
+
+fn select_parents<'pop>(
+    selector: &impl Selector<Vec<HiffIndividual>>,
+    population: &'pop Vec<HiffIndividual>,
+    rng: &mut impl Rng,
+) -> Result<Vec<&'pop HiffIndividual>> {
+    (0..POPULATION_SIZE)
+        .map(|_| selector.select(population, rng))
+        .collect()
+}
+
+fn bench_lexicase(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut group = c.benchmark_group("hiff_lexicase");
+    for num_distinct in [1_000, 100, 10] {
+        let population = population(num_distinct, &mut rng);
+        group.bench_with_input(
+            BenchmarkId::new("Lexicase", num_distinct),
+            &population,
+            |b, population| {
+                b.iter(|| {
+                    black_box(select_parents(
+                        &Lexicase::new(NUM_TEST_CASES),
+                        population,
+                        &mut rng,
+                    ))
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("FastLexicase", num_distinct),
+            &population,
+            |b, population| {
+                b.iter(|| {
+                    black_box(
+                        FastLexicase::new(NUM_TEST_CASES)
+                            .prepare(population)
+                            .and_then(|selector| select_parents(&selector, population, &mut rng)),
+                    )
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_lexicase);
+criterion_main!(benches);
@@ -0,0 +1,29 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum RunModel {
+    Serial,
+    Parallel,
+}
+
+/// Run a genetic algorithm on bitstrings described by a TOML or JSON config
+/// file
+#[derive(Parser, Debug, Clone)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    /// The run config; it's read as JSON if it ends in `.json`, and as TOML
+    /// otherwise
+    #[clap(value_parser)]
+    pub config: PathBuf,
+
+    /// Should we use parallelism when doing the run?
+    #[clap(short, long, value_enum, default_value_t = RunModel::Parallel)]
+    pub run_model: RunModel,
+
+    /// Seed for the random number generator, which overrides the seed in the
+    /// config; if neither is provided, a random seed is chosen (and printed)
+    #[clap(short, long, value_parser)]
+    pub seed: Option<u64>,
+}
@@ -0,0 +1,141 @@
+pub mod args;
+
+use std::{iter::once, ops::Not};
+
+use anyhow::{ensure, Result};
+use clap::Parser;
+use ec_core::{
+    config::{selectors::selectors, Components, Registry, RunConfig},
+    distributions::collection::ConvertToCollectionGenerator,
+    genome::GenomeDistance,
+    individual::{ec::WithScorer, scorer::FnScorer},
+    operator::Describe,
+    run::Run,
+    statistics::Statistics,
+    test_results::{Score, TestResults},
+};
+use ec_linear::{
+    config::{mutators, recombinators},
+    genome::{bitstring::Bitstring, Linear},
+};
+use rand::{distributions::Standard, prelude::Distribution, rngs::StdRng, SeedableRng};
+
+use crate::args::{Args, RunModel};
+
+/// A bitstring problem that can be named in a config.
+#[derive(Clone, Copy)]
+struct Problem {
+    bit_length: usize,
+    score: fn(&[bool]) -> TestResults<Score<i64>>,
+}
+
+fn count_ones(bits: &[bool]) -> TestResults<Score<i64>> {
+    bits.iter().copied().map(i64::from).collect()
+}
+
+fn hiff(bits: &[bool]) -> TestResults<Score<i64>> {
+    fn block_scores(bits: &[bool]) -> (bool, Vec<i64>) {
+        let len = bits.len();
+        let len_score = i64::try_from(len).unwrap_or(i64::MAX);
+        if len < 2 {
+            return (true, vec![len_score]);
+        }
+        let half_len = len / 2;
+        let (left_all_same, left_scores) = block_scores(&bits[..half_len]);
+        let (right_all_same, right_scores) = block_scores(&bits[half_len..]);
+        let all_same = left_all_same && right_all_same && bits[0] == bits[half_len];
+        let scores = left_scores
+            .into_iter()
+            .chain(right_scores)
+            .chain(once(if all_same { len_score } else { 0 }))
+            .collect();
+        (all_same, scores)
+    }
+
+    block_scores(bits).1.into_iter().collect()
+}
+
+fn problems() -> Registry<Problem> {
+    Registry::new()
+        .with("count_ones", |config, _| {
+            config.check_parameters(&["bit_length"])?;
+            Ok(Problem {
+                bit_length: config.parameter("bit_length")?,
+                score: count_ones,
+            })
+        })
+        .with("hiff", |config, _| {
+            config.check_parameters(&["bit_length"])?;
+            let bit_length: usize = config.parameter("bit_length")?;
+            ensure!(
+                bit_length.is_power_of_two(),
+                "The HIFF bit length must be a power of two, not {bit_length}"
+            );
+            Ok(Problem {
+                bit_length,
+                score: hiff,
+            })
+        })
+}
+
+fn main() -> Result<()> {
+    let Args {
+        config,
+        run_model,
+        seed,
+    } = Args::parse();
+
+    let config = RunConfig::from_path(config)?;
+
+    let seed = seed.or(config.seed).unwrap_or_else(rand::random);
+    println!("Using seed {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let Problem { bit_length, score } = problems().build(&config.problem)?;
+    let scorer = FnScorer(move |bitstring: &Bitstring| score(&bitstring.bits));
+
+    let population = Standard
+        .into_collection_generator(bit_length)
+        .with_scorer(scorer)
+        .into_collection_generator(config.population_size)
+        .sample(&mut rng);
+
+    ensure!(population.is_empty().not());
+
+    // All the components are looked up by name in these registries, so
+    // changing the config doesn't require recompiling.
+    let components = Components {
+        selectors: selectors(),
+        mutators: mutators(),
+        recombinators: recombinators(),
+    };
+    let generation = config.generation(&components, scorer, population)?;
+
+    println!("Child maker:\n{}", generation.child_maker().describe());
+
+    let statistics = Statistics::new()
+        .with_genome_length(|genome: &Bitstring| genome.size())
+        .with_genome_distance(GenomeDistance::distance);
+
+    let mut run = Run::new(generation)
+        .with_max_generations(config.num_generations)
+        .with_observer(|report| {
+            println!("Generation {:2}", report.generation_number);
+            match statistics.compute(report.population) {
+                Ok(statistics) => println!("{statistics}"),
+                Err(error) => eprintln!("Couldn't compute the statistics: {error:#}"),
+            }
+        });
+
+    let summary = match run_model {
+        RunModel::Serial => run.serial_run(&mut rng)?,
+        RunModel::Parallel => run.par_run(&mut rng)?,
+    };
+    println!(
+        "Stopped after {} generations ({:?})",
+        summary.num_generations, summary.stop_reason
+    );
+    println!("Best individual is {}", summary.best);
+
+    Ok(())
+}
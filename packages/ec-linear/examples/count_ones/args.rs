@@ -25,4 +25,9 @@ pub struct Args {
     /// Number of generations to run
     #[clap(short, long, value_parser, default_value_t = 100)]
     pub num_generations: usize,
+
+    /// Seed for the random number generator; a random seed is chosen (and
+    /// printed) if this isn't provided
+    #[clap(short, long, value_parser)]
+    pub seed: Option<u64>,
 }
@@ -7,6 +7,7 @@ use clap::Parser;
 use ec_core::{
     distributions::collection::ConvertToCollectionGenerator,
     generation::Generation,
+    genome::GenomeDistance,
     individual::{ec::WithScorer, scorer::FnScorer},
     operator::{
         genome_extractor::GenomeExtractor,
@@ -15,19 +16,22 @@ use ec_core::{
         recombinator::Recombine,
         selector::{
             best::Best, lexicase::Lexicase, tournament::Tournament, weighted::Weighted, Select,
-            Selector,
         },
         Composable,
     },
+    run::Run,
+    statistics::Statistics,
     test_results::{Score, TestResults},
 };
 use ec_linear::{
-    genome::bitstring::Bitstring, mutator::with_one_over_length::WithOneOverLength,
+    genome::{bitstring::Bitstring, Linear},
+    mutator::with_one_over_length::WithOneOverLength,
     recombinator::two_point_xo::TwoPointXo,
 };
 use rand::{
     distributions::{Distribution, Standard},
-    thread_rng,
+    rngs::StdRng,
+    SeedableRng,
 };
 
 use crate::args::{Args, RunModel};
@@ -43,9 +47,12 @@ fn main() -> Result<()> {
         population_size,
         bit_length,
         num_generations,
+        seed,
     } = Args::parse();
 
-    let mut rng = thread_rng();
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("Using seed {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
 
     let scorer = FnScorer(|bitstring: &Bitstring| count_ones(&bitstring.bits));
 
@@ -82,22 +89,34 @@ fn main() -> Result<()> {
     // generation::new() will take
     //   * a pipeline that gets us from population -> new individual
     //   * an initial population.
-    let mut generation = Generation::new(make_new_individual, population);
-
-    // TODO: It might be useful to insert some kind of logging system so we can
-    //   make this less imperative in nature.
-
-    for generation_number in 0..num_generations {
-        match run_model {
-            RunModel::Serial => generation.serial_next()?,
-            RunModel::Parallel => generation.par_next()?,
-        }
-
-        let best = Best.select(generation.population(), &mut rng)?;
-        // TODO: Change 2 to be the smallest number of digits needed for
-        //  num_generations-1.
-        println!("Generation {generation_number:2} best is {best}");
-    }
+    let generation = Generation::new(make_new_individual, population);
+
+    let statistics = Statistics::new()
+        .with_is_solved(|score: &Score<i64>| score.score == 1)
+        .with_genome_length(|genome: &Bitstring| genome.size())
+        .with_genome_distance(GenomeDistance::distance);
+
+    let mut run = Run::new(generation)
+        .with_max_generations(num_generations)
+        .with_observer(|report| {
+            // TODO: Change 2 to be the smallest number of digits needed for
+            //  num_generations-1.
+            println!("Generation {:2}", report.generation_number);
+            match statistics.compute(report.population) {
+                Ok(statistics) => println!("{statistics}"),
+                Err(error) => eprintln!("Couldn't compute the statistics: {error:#}"),
+            }
+        });
+
+    let summary = match run_model {
+        RunModel::Serial => run.serial_run(&mut rng)?,
+        RunModel::Parallel => run.par_run(&mut rng)?,
+    };
+    println!(
+        "Stopped after {} generations ({:?})",
+        summary.num_generations, summary.stop_reason
+    );
+    println!("Best individual is {}", summary.best);
 
     Ok(())
 }
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::Parser;
 
 #[derive(clap::ValueEnum, Copy, Clone, Debug)]
@@ -7,7 +9,7 @@ pub enum RunModel {
 }
 
 /// Simple genetic algorithm in Rust
-#[derive(Parser, Debug, Copy, Clone)]
+#[derive(Parser, Debug, Clone)]
 #[clap(author, version, about, long_about = None)]
 pub struct Args {
     /// Should we use parallelism when doing the run?
@@ -25,4 +27,27 @@ pub struct Args {
     /// Number of generations to run
     #[clap(short, long, value_parser, default_value_t = 100)]
     pub num_generations: usize,
+
+    /// Relative weight of making a child by crossover (of two parents)
+    #[clap(short = 'x', long, value_parser, default_value_t = 9)]
+    pub crossover_weight: usize,
+
+    /// Relative weight of making a child by mutation (of one parent)
+    #[clap(short, long, value_parser, default_value_t = 1)]
+    pub mutation_weight: usize,
+
+    /// Number of the best distinct individuals seen during the run to keep
+    /// in the hall of fame
+    #[clap(short = 'f', long, value_parser, default_value_t = 10)]
+    pub hall_of_fame_size: usize,
+
+    /// Write the ancestry of the best individual to this file, as a Graphviz
+    /// DOT graph, or in `GraphML` format if it ends in `.graphml`
+    #[clap(short, long, value_parser)]
+    pub lineage: Option<PathBuf>,
+
+    /// Seed for the random number generator; a random seed is chosen (and
+    /// printed) if this isn't provided
+    #[clap(short, long, value_parser)]
+    pub seed: Option<u64>,
 }
@@ -1,31 +1,38 @@
 pub mod args;
 
-use std::{iter::once, ops::Not};
+use std::{fs, iter::once, ops::Not, path::Path};
 
-use anyhow::{ensure, Result};
+use anyhow::{ensure, Context, Result};
 use clap::Parser;
 use ec_core::{
     distributions::collection::ConvertToCollectionGenerator,
     generation::Generation,
-    individual::{ec::WithScorer, scorer::FnScorer},
+    genome::GenomeDistance,
+    hall_of_fame::HallOfFame,
+    individual::{ec::WithScorer, scorer::FnScorer, Identified},
+    lineage::{LineageLog, RecordLineage},
     operator::{
+        composable::Map,
         genome_extractor::GenomeExtractor,
         genome_scorer::GenomeScorer,
         mutator::Mutate,
         recombinator::Recombine,
         selector::{
-            best::Best, lexicase::Lexicase, tournament::Tournament, weighted::Weighted, Select,
-            Selector,
+            fast_lexicase::FastLexicase, tournament::Tournament, weighted::Weighted, Select,
         },
-        Composable,
+        Composable, Describe,
     },
+    run::Run,
+    statistics::Statistics,
+    survival::Elitism,
     test_results::{Score, TestResults},
 };
 use ec_linear::{
-    genome::bitstring::Bitstring, mutator::with_one_over_length::WithOneOverLength,
+    genome::{bitstring::Bitstring, Linear},
+    mutator::with_one_over_length::WithOneOverLength,
     recombinator::two_point_xo::TwoPointXo,
 };
-use rand::{distributions::Standard, prelude::Distribution, thread_rng};
+use rand::{distributions::Standard, prelude::Distribution, rngs::StdRng, SeedableRng};
 
 use crate::args::{Args, RunModel};
 
@@ -54,25 +61,55 @@ fn hiff(bits: &[bool]) -> (bool, TestResults<Score<usize>>) {
     }
 }
 
+/// Write the ancestry of `best` to `path`, as a Graphviz DOT graph, or in
+/// `GraphML` format if `path` ends in `.graphml`.
+fn write_ancestry(lineage_log: &LineageLog, best: &impl Identified, path: &Path) -> Result<()> {
+    let ancestry = lineage_log.ancestry(
+        best.id()
+            .context("The best individual wasn't recorded in the lineage log")?,
+    )?;
+    println!(
+        "Writing the {} individuals in the best individual's ancestry to {}",
+        ancestry.records.len(),
+        path.display()
+    );
+    let graph = if path
+        .extension()
+        .is_some_and(|extension| extension == "graphml")
+    {
+        ancestry.to_graphml()
+    } else {
+        ancestry.to_dot()
+    };
+    fs::write(path, graph)
+        .with_context(|| format!("Couldn't write the ancestry to {}", path.display()))
+}
+
 fn main() -> Result<()> {
     let Args {
         run_model,
         population_size,
         bit_length,
         num_generations,
+        crossover_weight,
+        mutation_weight,
+        hall_of_fame_size,
+        lineage,
+        seed,
     } = Args::parse();
 
-    let mut rng = thread_rng();
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("Using seed {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
 
     let scorer = FnScorer(|bitstring: &Bitstring| hiff(&bitstring.bits).1);
 
     let num_test_cases = 2 * bit_length - 1;
 
-    let selector = Weighted::new(Best, 1)
-        .with_selector(Lexicase::new(num_test_cases), 5)
+    let selector = Weighted::new(FastLexicase::new(num_test_cases), 5)
         .with_selector(Tournament::new(2), population_size - 1);
 
-    let population = Standard
+    let mut population = Standard
         .into_collection_generator(bit_length)
         .with_scorer(scorer)
         .into_collection_generator(population_size)
@@ -82,6 +119,11 @@ fn main() -> Result<()> {
 
     println!("{population:?}");
 
+    // Every child is recorded in `lineage_log` along with its parents, so
+    // we can trace the ancestry of the best individual at the end.
+    let lineage_log = LineageLog::new();
+    lineage_log.record_initial(&mut population);
+
     // Let's assume the process will be generational, i.e., we replace the entire
     // population with newly created/selected individuals every generation.
     // `generation` will be a mutable operator (containing the data structures for
@@ -89,32 +131,83 @@ fn main() -> Result<()> {
     // returning a new population. We'll have different generation operators for
     // serial vs. parallel generation of new individuals.
 
-    let make_new_individual = Select::new(selector)
-        .apply_twice()
-        .then_map(GenomeExtractor)
-        .then(Recombine::new(TwoPointXo))
-        .then(Mutate::new(WithOneOverLength))
-        .wrap::<GenomeScorer<_, _>>(scorer);
+    // Each child is made either by crossover of two parents or by mutation
+    // of one parent, chosen with probability proportional to the weights.
+    let crossover = Select::new(&selector).apply_twice().then(
+        RecordLineage::new(
+            Map::new(GenomeExtractor)
+                .then(Recombine::new(TwoPointXo))
+                .wrap::<GenomeScorer<_, _>>(scorer),
+            &lineage_log,
+        )
+        .with_operators(["two_point_xo"]),
+    );
+    let mutation = Select::new(&selector).then(
+        RecordLineage::new(
+            GenomeExtractor
+                .then(Mutate::new(WithOneOverLength))
+                .wrap::<GenomeScorer<_, _>>(scorer),
+            &lineage_log,
+        )
+        .with_operators(["with_one_over_length"]),
+    );
+    let make_new_individual = crossover.one_of_ops(crossover_weight, mutation, mutation_weight);
+
+    println!("Child maker:\n{}", make_new_individual.describe());
 
     // generation::new() will take
     //   * a pipeline that gets us from population -> new individual
     //   * an initial population.
-    let mut generation = Generation::new(make_new_individual, population);
-
-    // TODO: It might be useful to insert some kind of logging system so we can
-    //   make this less imperative in nature.
-
-    for generation_number in 0..num_generations {
-        match run_model {
-            RunModel::Serial => generation.serial_next()?,
-            RunModel::Parallel => generation.par_next()?,
-        }
-
-        let best = Best.select(generation.population(), &mut rng)?;
-
-        // TODO: Change 2 to be the smallest number of digits needed for
-        //  num_generations-1.
-        println!("Generation {generation_number:2} best is {best}");
+    // Carrying the best individual over unchanged ensures that we never
+    // lose it between generations.
+    let generation =
+        Generation::new(make_new_individual, population).with_survival(Elitism::new(1));
+
+    let statistics = Statistics::new()
+        .with_genome_length(|genome: &Bitstring| genome.size())
+        .with_genome_distance(GenomeDistance::distance);
+
+    // The hall of fame keeps the best distinct individuals seen in any
+    // generation, not just the final one.
+    let hall_of_fame = HallOfFame::new(hall_of_fame_size);
+
+    let mut run = Run::new(generation)
+        .with_max_generations(num_generations)
+        .with_hall_of_fame(&hall_of_fame)
+        .with_observer(|report| {
+            // TODO: Change 2 to be the smallest number of digits needed for
+            //  num_generations-1.
+            println!("Generation {:2}", report.generation_number);
+            match statistics.compute(report.population) {
+                Ok(statistics) => println!("{statistics}"),
+                Err(error) => eprintln!("Couldn't compute the statistics: {error:#}"),
+            }
+            // We only need the ancestries of the current population.
+            lineage_log.prune(report.population.iter().filter_map(Identified::id));
+        });
+
+    let summary = match run_model {
+        RunModel::Serial => run.serial_run(&mut rng)?,
+        RunModel::Parallel => run.par_run(&mut rng)?,
+    };
+    println!(
+        "Stopped after {} generations ({:?})",
+        summary.num_generations, summary.stop_reason
+    );
+    println!("Best individual is {}", summary.best);
+    println!(
+        "Hall of fame scores: {}",
+        hall_of_fame.with_members(|members| {
+            members
+                .iter()
+                .map(|member| member.test_results.total_result.score.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+    );
+
+    if let Some(path) = lineage {
+        write_ancestry(&lineage_log, &summary.best, &path)?;
     }
 
     Ok(())
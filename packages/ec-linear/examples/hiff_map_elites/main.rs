@@ -0,0 +1,132 @@
+pub mod args;
+
+use std::iter::once;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use ec_core::{
+    distributions::collection::ConvertToCollectionGenerator,
+    individual::{
+        ec::{EcIndividual, WithScorer},
+        scorer::FnScorer,
+    },
+    map_elites::{bin, FnFeatures, GridArchive, MapElites},
+    operator::{
+        genome_extractor::GenomeExtractor,
+        genome_scorer::GenomeScorer,
+        mutator::Mutate,
+        selector::{random::Random, Select},
+        Composable,
+    },
+    test_results::{Score, TestResults},
+};
+use ec_linear::{genome::bitstring::Bitstring, mutator::with_one_over_length::WithOneOverLength};
+use num_traits::ToPrimitive;
+use rand::{distributions::Standard, prelude::Distribution, rngs::StdRng, Rng, SeedableRng};
+
+use crate::args::{Args, RunModel};
+
+#[must_use]
+fn hiff(bits: &[bool]) -> (bool, TestResults<Score<usize>>) {
+    let len = bits.len();
+    if len < 2 {
+        (true, once(Score::from(len)).collect())
+    } else {
+        let half_len = len / 2;
+        let (left_all_same, left_score) = hiff(&bits[..half_len]);
+        let (right_all_same, right_score) = hiff(&bits[half_len..]);
+        let all_same = left_all_same && right_all_same && bits[0] == bits[half_len];
+
+        (
+            all_same,
+            left_score
+                .results
+                .into_iter()
+                .chain(right_score.results)
+                .chain(once(Score::from(if all_same { len } else { 0 })))
+                .collect(),
+        )
+    }
+}
+
+fn main() -> Result<()> {
+    let Args {
+        run_model,
+        initial_population_size,
+        batch_size,
+        bit_length,
+        num_bins,
+        num_iterations,
+        seed,
+    } = Args::parse();
+
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("Using seed {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let scorer = FnScorer(|bitstring: &Bitstring| hiff(&bitstring.bits).1);
+
+    // The two features are the number of ones and the number of places
+    // where neighboring bits differ. The best HIFF scores (all zeros or all
+    // ones) are in the two corners where no neighboring bits differ.
+    let max_num_ones = bit_length.to_f64().unwrap_or(f64::NAN);
+    let max_num_changes = bit_length.saturating_sub(1).to_f64().unwrap_or(f64::NAN);
+    let features = FnFeatures(|individual: &EcIndividual<Bitstring, _>| {
+        let bits = &individual.genome.bits;
+        let num_ones = bits.iter().filter(|&&bit| bit).count();
+        let num_changes = bits.windows(2).filter(|pair| pair[0] != pair[1]).count();
+        vec![
+            bin(
+                num_ones.to_f64().unwrap_or(f64::NAN),
+                0.0,
+                max_num_ones,
+                num_bins,
+            ),
+            bin(
+                num_changes.to_f64().unwrap_or(f64::NAN),
+                0.0,
+                max_num_changes,
+                num_bins,
+            ),
+        ]
+    });
+
+    let initial_population = Standard
+        .into_collection_generator(bit_length)
+        .with_scorer(scorer)
+        .into_collection_generator(initial_population_size)
+        .sample(&mut rng);
+
+    let make_new_individual = Select::new(Random)
+        .then(GenomeExtractor)
+        .then(Mutate::new(WithOneOverLength))
+        .wrap::<GenomeScorer<_, _>>(scorer);
+
+    let mut map_elites = MapElites::new(
+        make_new_individual,
+        features,
+        GridArchive::new(vec![num_bins, num_bins]),
+        batch_size,
+    );
+    map_elites.insert_all(initial_population)?;
+
+    for _ in 0..num_iterations {
+        let report = match run_model {
+            RunModel::Serial => map_elites.serial_next(rng.gen())?,
+            RunModel::Parallel => map_elites.par_next(rng.gen())?,
+        };
+        // TODO: Change 3 to be the smallest number of digits needed for
+        //  num_iterations.
+        println!("Iteration {:3}", report.iteration);
+        println!("{report}");
+    }
+
+    let best = map_elites
+        .archive()
+        .iter()
+        .max()
+        .context("The archive was empty")?;
+    println!("Best elite is {best}");
+
+    Ok(())
+}
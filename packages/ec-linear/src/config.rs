@@ -0,0 +1,87 @@
+//! The mutators and recombinators for linear genomes that can be named in
+//! a run config (see [`ec_core::config`]).
+
+use std::ops::Not;
+
+use anyhow::Result;
+use ec_core::config::{ComponentConfig, Registry, SharedMutator, SharedRecombinator};
+
+use crate::{
+    genome::Linear,
+    mutator::{umad::Umad, with_one_over_length::WithOneOverLength, with_rate::WithRate},
+    recombinator::{crossover::Crossover, two_point_xo::TwoPointXo, uniform_xo::UniformXo},
+};
+
+/// A registry of the mutators for linear genomes whose genes can be
+/// negated, like bitstrings.
+///
+/// | Name                  | Parameters      |
+/// |-----------------------|-----------------|
+/// | `with_one_over_length`|                 |
+/// | `with_rate`           | `mutation_rate` |
+///
+/// UMAD needs a way of making new genes, so it isn't included; see
+/// [`umad`] for building one from a config.
+#[must_use]
+pub fn mutators<G>() -> Registry<SharedMutator<G>>
+where
+    G: Linear + FromIterator<G::Gene> + IntoIterator<Item = G::Gene> + 'static,
+    G::Gene: Not<Output = G::Gene>,
+{
+    Registry::new()
+        .with("with_one_over_length", |config, _| {
+            config.check_parameters(&[])?;
+            Ok(SharedMutator::new(WithOneOverLength))
+        })
+        .with("with_rate", |config, _| {
+            config.check_parameters(&["mutation_rate"])?;
+            Ok(SharedMutator::new(WithRate::new(
+                config.parameter("mutation_rate")?,
+            )))
+        })
+}
+
+/// Build a UMAD mutator that makes new genes with `gene_generator`.
+///
+/// The config's parameters are `addition_rate`, `deletion_rate`, and
+/// optionally `empty_addition_rate` (which defaults to the addition rate).
+/// Other parameters are rejected, except for those in `extra_parameters`,
+/// which lets callers read their own parameters (e.g., for configuring the
+/// gene generator) from the same config.
+///
+/// # Errors
+/// This returns an error if any of the parameters are missing, invalid, or
+/// unused.
+pub fn umad<D>(
+    config: &ComponentConfig,
+    extra_parameters: &[&str],
+    gene_generator: D,
+) -> Result<Umad<D>> {
+    let parameters = ["addition_rate", "deletion_rate", "empty_addition_rate"];
+    config.check_parameters(&[&parameters, extra_parameters].concat())?;
+    let addition_rate = config.parameter("addition_rate")?;
+    Ok(Umad::new_with_empty_rate(
+        addition_rate,
+        config.parameter_or("empty_addition_rate", addition_rate)?,
+        config.parameter("deletion_rate")?,
+        gene_generator,
+    ))
+}
+
+/// A registry of the recombinators for linear genomes, which have no
+/// parameters: `two_point_xo` and `uniform_xo`.
+#[must_use]
+pub fn recombinators<G>() -> Registry<SharedRecombinator<G>>
+where
+    G: Crossover + 'static,
+{
+    Registry::new()
+        .with("two_point_xo", |config, _| {
+            config.check_parameters(&[])?;
+            Ok(SharedRecombinator::new(TwoPointXo))
+        })
+        .with("uniform_xo", |config, _| {
+            config.check_parameters(&[])?;
+            Ok(SharedRecombinator::new(UniformXo))
+        })
+}
@@ -3,9 +3,10 @@ use std::fmt::Display;
 use anyhow::bail;
 use ec_core::{
     distributions::collection::{CollectionGenerator, ConvertToCollectionGenerator},
-    genome::Genome,
+    genome::{hamming_distance, Genome, GenomeDistance, GenomeSize},
 };
-use rand::{distributions::Standard, prelude::Distribution, rngs::ThreadRng, Rng};
+use num_traits::ToPrimitive;
+use rand::{distributions::Standard, prelude::Distribution, Rng};
 
 use super::Linear;
 use crate::recombinator::crossover::Crossover;
@@ -30,7 +31,8 @@ impl Distribution<bool> for BoolGenerator {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Bitstring {
     pub bits: Vec<bool>,
 }
@@ -47,11 +49,15 @@ where
 }
 
 impl Bitstring {
-    pub fn random(num_bits: usize, rng: &mut ThreadRng) -> Self {
+    pub fn random<R: Rng + ?Sized>(num_bits: usize, rng: &mut R) -> Self {
         Standard.into_collection_generator(num_bits).sample(rng)
     }
 
-    pub fn random_with_probability(num_bits: usize, probability: f64, rng: &mut ThreadRng) -> Self {
+    pub fn random_with_probability<R: Rng + ?Sized>(
+        num_bits: usize,
+        probability: f64,
+        rng: &mut R,
+    ) -> Self {
         BoolGenerator::new(probability)
             .into_collection_generator(num_bits)
             .sample(rng)
@@ -66,6 +72,15 @@ impl Bitstring {
     }
 }
 
+/// The Hamming distance, i.e., the number of bits that differ.
+impl GenomeDistance for Bitstring {
+    fn distance(&self, other: &Self) -> f64 {
+        hamming_distance(&self.bits, &other.bits)
+            .to_f64()
+            .unwrap_or(f64::NAN)
+    }
+}
+
 impl Display for Bitstring {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         for bit in &self.bits {
@@ -119,11 +134,13 @@ impl Genome for Bitstring {
     type Gene = bool;
 }
 
-impl Linear for Bitstring {
-    fn size(&self) -> usize {
+impl GenomeSize for Bitstring {
+    fn genome_size(&self) -> usize {
         self.bits.len()
     }
+}
 
+impl Linear for Bitstring {
     fn gene_mut(&mut self, index: usize) -> Option<&mut Self::Gene> {
         self.bits.get_mut(index)
     }
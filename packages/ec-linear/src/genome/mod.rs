@@ -1,10 +1,14 @@
-use ec_core::genome::Genome;
+use ec_core::genome::{Genome, GenomeSize};
 
 pub mod bitstring;
 pub mod vector;
 
-pub trait Linear: Genome {
-    fn size(&self) -> usize;
+pub trait Linear: Genome + GenomeSize {
+    /// The number of genes, which is always the genome's
+    /// [`GenomeSize::genome_size`].
+    fn size(&self) -> usize {
+        self.genome_size()
+    }
 
     fn gene_mut(&mut self, index: usize) -> Option<&mut Self::Gene>;
 }
@@ -1,8 +1,9 @@
-use ec_core::genome::Genome;
+use ec_core::genome::{Genome, GenomeDistance, GenomeSize};
 
 use super::Linear;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector<T> {
     pub genes: Vec<T>,
 }
@@ -11,11 +12,31 @@ impl<T> Genome for Vector<T> {
     type Gene = T;
 }
 
-impl<T> Linear for Vector<T> {
-    fn size(&self) -> usize {
+/// The Euclidean distance, treating any genes past the end of the shorter
+/// vector as zeros.
+impl GenomeDistance for Vector<f64> {
+    fn distance(&self, other: &Self) -> f64 {
+        let (longer, shorter) = if self.genes.len() >= other.genes.len() {
+            (&self.genes, &other.genes)
+        } else {
+            (&other.genes, &self.genes)
+        };
+        longer
+            .iter()
+            .enumerate()
+            .map(|(index, x)| (x - shorter.get(index).unwrap_or(&0.0)).powi(2))
+            .sum::<f64>()
+            .sqrt()
+    }
+}
+
+impl<T> GenomeSize for Vector<T> {
+    fn genome_size(&self) -> usize {
         self.genes.len()
     }
+}
 
+impl<T> Linear for Vector<T> {
     fn gene_mut(&mut self, index: usize) -> Option<&mut Self::Gene> {
         self.genes.get_mut(index)
     }
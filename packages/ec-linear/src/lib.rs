@@ -1,3 +1,5 @@
+#[cfg(feature = "config")]
+pub mod config;
 pub mod genome;
 pub mod mutator;
 pub mod recombinator;
@@ -1,9 +1,13 @@
-use ec_core::{genome::Genome, operator::mutator::Mutator};
-use rand::{prelude::Distribution, rngs::ThreadRng, Rng};
+use ec_core::{
+    genome::Genome,
+    operator::{mutator::Mutator, Describe, Description},
+};
+use rand::{prelude::Distribution, Rng};
 
 use crate::genome::Linear;
 
 /// UMAD = Uniform Mutation through random Addition and Deletion
+#[derive(Clone)]
 pub struct Umad<GeneGenerator> {
     addition_rate: f64,
     deletion_rate: f64,
@@ -54,7 +58,7 @@ impl<GeneGenerator> Umad<GeneGenerator> {
         }
     }
 
-    fn new_gene<G>(&self, rng: &mut ThreadRng) -> G::Gene
+    fn new_gene<G, R: Rng + ?Sized>(&self, rng: &mut R) -> G::Gene
     where
         G: Genome,
         GeneGenerator: Distribution<G::Gene>,
@@ -68,12 +72,12 @@ where
     G: Linear + IntoIterator<Item = G::Gene> + FromIterator<G::Gene>,
     GeneGenerator: Distribution<G::Gene>,
 {
-    fn mutate(&self, genome: G, rng: &mut ThreadRng) -> anyhow::Result<G> {
+    fn mutate<R: Rng + ?Sized>(&self, genome: G, rng: &mut R) -> anyhow::Result<G> {
         if genome.size() == 0 {
             if let Some(addition_rate) = self.empty_addition_rate {
                 return Ok(rng
                     .gen_bool(addition_rate)
-                    .then(|| self.new_gene::<G>(rng))
+                    .then(|| self.new_gene::<G, _>(rng))
                     .into_iter()
                     .collect());
             }
@@ -96,7 +100,7 @@ where
                 };
 
                 let new_gene = match (add_gene, delete_new_gene) {
-                    (true, false) => Some(self.new_gene::<G>(rng)),
+                    (true, false) => Some(self.new_gene::<G, _>(rng)),
                     _ => None,
                 };
 
@@ -107,6 +111,22 @@ where
     }
 }
 
+// The gene generator isn't described, since it's typically a distribution
+// over a large set of genes.
+impl<GeneGenerator> Describe for Umad<GeneGenerator> {
+    fn describe(&self) -> Description {
+        let description = Description::new("Umad")
+            .with_parameter("addition_rate", self.addition_rate)
+            .with_parameter("deletion_rate", self.deletion_rate);
+        match self.empty_addition_rate {
+            Some(empty_addition_rate) => {
+                description.with_parameter("empty_addition_rate", empty_addition_rate)
+            }
+            None => description,
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use ec_core::uniform_distribution_of;
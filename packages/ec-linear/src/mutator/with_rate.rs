@@ -1,11 +1,12 @@
 use std::ops::Not;
 
 use anyhow::Result;
-use ec_core::operator::mutator::Mutator;
-use rand::{rngs::ThreadRng, Rng};
+use ec_core::operator::{mutator::Mutator, Describe, Description};
+use rand::Rng;
 
 use crate::genome::Linear;
 
+#[derive(Clone, Copy)]
 pub struct WithRate {
     mutation_rate: f32,
 }
@@ -16,12 +17,16 @@ impl<T> Mutator<Vec<T>> for WithRate
 where
     T: Not<Output = T>,
 {
-    fn mutate(&self, genome: Vec<T>, rng: &mut ThreadRng) -> Result<Vec<T>> {
+    fn mutate<R: Rng + ?Sized>(&self, genome: Vec<T>, rng: &mut R) -> Result<Vec<T>> {
         Ok(genome
             .into_iter()
             .map(|bit| {
                 let r: f32 = rng.gen();
-                if r < self.mutation_rate { !bit } else { bit }
+                if r < self.mutation_rate {
+                    !bit
+                } else {
+                    bit
+                }
             })
             .collect())
     }
@@ -35,12 +40,16 @@ where
     T: Linear + FromIterator<T::Gene> + IntoIterator<Item = T::Gene>,
     T::Gene: Not<Output = T::Gene>,
 {
-    fn mutate(&self, genome: T, rng: &mut ThreadRng) -> Result<T> {
+    fn mutate<R: Rng + ?Sized>(&self, genome: T, rng: &mut R) -> Result<T> {
         Ok(genome
             .into_iter()
             .map(|bit| {
                 let r: f32 = rng.gen();
-                if r < self.mutation_rate { !bit } else { bit }
+                if r < self.mutation_rate {
+                    !bit
+                } else {
+                    bit
+                }
             })
             .collect())
     }
@@ -53,6 +62,12 @@ impl WithRate {
     }
 }
 
+impl Describe for WithRate {
+    fn describe(&self) -> Description {
+        Description::new("WithRate").with_parameter("mutation_rate", self.mutation_rate)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::iter::zip;
@@ -1,9 +1,10 @@
 use anyhow::{ensure, Result};
-use ec_core::operator::recombinator::Recombinator;
-use rand::{rngs::ThreadRng, Rng};
+use ec_core::operator::{recombinator::Recombinator, Describe, Description};
+use rand::Rng;
 
 use super::crossover::Crossover;
 
+#[derive(Clone, Copy)]
 pub struct TwoPointXo;
 
 // TODO: Remove the `Vec<T>` versions when we're done migrating
@@ -11,10 +12,10 @@ pub struct TwoPointXo;
 impl<T> Recombinator<[Vec<T>; 2]> for TwoPointXo {
     type Output = Vec<T>;
 
-    fn recombine(
+    fn recombine<R: Rng + ?Sized>(
         &self,
         [mut first_genome, mut second_genome]: [Vec<T>; 2],
-        rng: &mut ThreadRng,
+        rng: &mut R,
     ) -> Result<Self::Output> {
         ensure!(
             first_genome.len() == second_genome.len(),
@@ -38,7 +39,11 @@ impl<T> Recombinator<[Vec<T>; 2]> for TwoPointXo {
 impl<T> Recombinator<(Vec<T>, Vec<T>)> for TwoPointXo {
     type Output = Vec<T>;
 
-    fn recombine(&self, genomes: (Vec<T>, Vec<T>), rng: &mut ThreadRng) -> Result<Self::Output> {
+    fn recombine<R: Rng + ?Sized>(
+        &self,
+        genomes: (Vec<T>, Vec<T>),
+        rng: &mut R,
+    ) -> Result<Self::Output> {
         self.recombine(<[Vec<T>; 2]>::from(genomes), rng)
     }
 }
@@ -61,10 +66,10 @@ where
 {
     type Output = G;
 
-    fn recombine(
+    fn recombine<R: Rng + ?Sized>(
         &self,
         [mut first_genome, mut second_genome]: [G; 2],
-        rng: &mut ThreadRng,
+        rng: &mut R,
     ) -> Result<Self::Output> {
         ensure!(
             first_genome.size() == second_genome.size(),
@@ -90,7 +95,13 @@ where
 {
     type Output = G;
 
-    fn recombine(&self, genomes: (G, G), rng: &mut ThreadRng) -> Result<Self::Output> {
+    fn recombine<R: Rng + ?Sized>(&self, genomes: (G, G), rng: &mut R) -> Result<Self::Output> {
         self.recombine(<[G; 2]>::from(genomes), rng)
     }
 }
+
+impl Describe for TwoPointXo {
+    fn describe(&self) -> Description {
+        Description::new("TwoPointXo")
+    }
+}
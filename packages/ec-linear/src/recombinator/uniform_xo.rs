@@ -1,9 +1,10 @@
 use anyhow::{ensure, Result};
-use ec_core::operator::recombinator::Recombinator;
-use rand::{rngs::ThreadRng, Rng};
+use ec_core::operator::{recombinator::Recombinator, Describe, Description};
+use rand::Rng;
 
 use super::crossover::Crossover;
 
+#[derive(Clone, Copy)]
 pub struct UniformXo;
 
 // TODO: We should get rid of the `Vec<T>` versions when
@@ -11,10 +12,10 @@ pub struct UniformXo;
 impl<T: Clone> Recombinator<[Vec<T>; 2]> for UniformXo {
     type Output = Vec<T>;
 
-    fn recombine(
+    fn recombine<R: Rng + ?Sized>(
         &self,
         [first_genome, second_genome]: [Vec<T>; 2],
-        rng: &mut ThreadRng,
+        rng: &mut R,
     ) -> Result<Self::Output> {
         ensure!(
             first_genome.len() == second_genome.len(),
@@ -38,7 +39,11 @@ impl<T: Clone> Recombinator<[Vec<T>; 2]> for UniformXo {
 impl<T: Clone> Recombinator<(Vec<T>, Vec<T>)> for UniformXo {
     type Output = Vec<T>;
 
-    fn recombine(&self, genomes: (Vec<T>, Vec<T>), rng: &mut ThreadRng) -> Result<Self::Output> {
+    fn recombine<R: Rng + ?Sized>(
+        &self,
+        genomes: (Vec<T>, Vec<T>),
+        rng: &mut R,
+    ) -> Result<Self::Output> {
         self.recombine(<[Vec<T>; 2]>::from(genomes), rng)
     }
 }
@@ -49,10 +54,10 @@ where
 {
     type Output = G;
 
-    fn recombine(
+    fn recombine<R: Rng + ?Sized>(
         &self,
         [mut first_genome, mut second_genome]: [G; 2],
-        rng: &mut ThreadRng,
+        rng: &mut R,
     ) -> Result<Self::Output> {
         ensure!(
             first_genome.size() == second_genome.size(),
@@ -77,7 +82,13 @@ where
 {
     type Output = G;
 
-    fn recombine(&self, genomes: (G, G), rng: &mut ThreadRng) -> Result<Self::Output> {
+    fn recombine<R: Rng + ?Sized>(&self, genomes: (G, G), rng: &mut R) -> Result<Self::Output> {
         self.recombine(<[G; 2]>::from(genomes), rng)
     }
 }
+
+impl Describe for UniformXo {
+    fn describe(&self) -> Description {
+        Description::new("UniformXo")
+    }
+}
@@ -0,0 +1,42 @@
+use clap::Parser;
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum RunModel {
+    Serial,
+    Parallel,
+}
+
+/// Simple genetic algorithm in Rust
+#[derive(Parser, Debug, Copy, Clone)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    /// Should we use parallelism when doing the run?
+    #[clap(short, long, value_enum, default_value_t = RunModel::Parallel)]
+    pub run_model: RunModel,
+
+    /// Number of individuals in each layer
+    #[clap(short = 'p', long, value_parser, default_value_t = 100)]
+    pub layer_size: usize,
+
+    /// Number of age layers
+    #[clap(short = 'l', long, value_parser, default_value_t = 5)]
+    pub num_layers: usize,
+
+    /// Number of generations between injections of new random individuals,
+    /// which is also the gap between the layers' age limits
+    #[clap(short, long, value_parser, default_value_t = 10)]
+    pub age_gap: usize,
+
+    /// Maximum number of initial instructions
+    #[clap(short = 'i', long, value_parser, default_value_t = 50)]
+    pub max_initial_instructions: usize,
+
+    /// Number of generations to run
+    #[clap(short, long, value_parser, default_value_t = 200)]
+    pub num_generations: usize,
+
+    /// Seed for the random number generator; a random seed is chosen (and
+    /// printed) if this isn't provided
+    #[clap(short, long, value_parser)]
+    pub seed: Option<u64>,
+}
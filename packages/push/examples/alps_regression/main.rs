@@ -0,0 +1,192 @@
+pub mod args;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use ec_core::{
+    alps::{linear_age_limits, Alps, InheritBirth},
+    distributions::collection::ConvertToCollectionGenerator,
+    individual::{ec::WithScorer, scorer::FnScorer},
+    operator::{
+        genome_extractor::GenomeExtractor,
+        genome_scorer::GenomeScorer,
+        mutator::Mutate,
+        selector::{
+            epsilon_lexicase::{EpsilonLexicase, Variant},
+            Select,
+        },
+        Composable,
+    },
+    test_results::{self, TestResults},
+    uniform_distribution_of,
+};
+use ec_linear::mutator::umad::Umad;
+use num_traits::Float;
+use ordered_float::OrderedFloat;
+use push::{
+    evaluation::cases::{Case, WithTarget},
+    genome::plushy::{ConvertToGeneGenerator, Plushy},
+    instruction::{variable_name::VariableName, FloatInstruction, PushInstruction},
+    push_vm::{program::PushProgram, push_state::PushState, HasStack, State},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::args::{Args, RunModel};
+
+/*
+ * This is the "complex regression" problem (see the `complex_regression`
+ * example), but using an age-layered population (ALPS) to avoid premature
+ * convergence.
+ */
+
+// The penalty value to use when an evolved program doesn't have an expected
+// "return" value on the appropriate stack at the end of its execution.
+const PENALTY_VALUE: f64 = 1_000.0;
+
+type Of64 = OrderedFloat<f64>;
+
+/// The target polynomial is (x^3 + 1)^3 + 1
+/// i.e., x^9 + 3x^6 + 3x^3 + 2
+fn target_fn(input: Of64) -> Of64 {
+    (input.powi(3) + 1.0).powi(3) + 1.0
+}
+
+fn build_push_state(
+    program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
+    input: Of64,
+) -> PushState {
+    #[allow(clippy::unwrap_used)]
+    PushState::builder()
+        .with_max_stack_size(1000)
+        .with_program(program)
+        // This will return an error if the program is longer than the allowed
+        // max stack size.
+        // We arguably should check that and return an error here.
+        .unwrap()
+        .with_float_input("x", input)
+        .build()
+}
+
+fn score_program(
+    program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
+    Case { input, output }: Case<Of64>,
+) -> Of64 {
+    let state = build_push_state(program, input);
+    #[allow(clippy::option_if_let_else)]
+    match state.run_to_completion() {
+        Ok(final_state) => final_state.stack::<Of64>().top().map_or_else(
+            |_| Of64::from(PENALTY_VALUE),
+            |answer| (answer - output).abs(),
+        ),
+
+        Err(_) => {
+            // Do some logging, perhaps?
+            Of64::from(PENALTY_VALUE)
+        }
+    }
+}
+
+fn score_genome<'a>(
+    genome: &Plushy,
+    training_cases: impl Iterator<Item = &'a Case<Of64>>,
+) -> TestResults<test_results::Error<Of64>> {
+    let program: Vec<PushProgram> = genome.clone().into();
+
+    training_cases
+        .map(|&case| score_program(program.iter().cloned(), case))
+        .collect()
+}
+
+fn main() -> Result<()> {
+    let Args {
+        run_model,
+        layer_size,
+        num_layers,
+        age_gap,
+        max_initial_instructions,
+        num_generations,
+        seed,
+    } = Args::parse();
+
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("Using seed {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // Inputs from -4 (inclusive) to 4 (exclusive) in increments of 0.25.
+    let training_cases = (-4 * 4..4 * 4)
+        .map(|n| Of64::from(n) / 4.0)
+        .with_target(|&i| target_fn(i));
+
+    let scorer = FnScorer(|genome: &Plushy| score_genome(genome, training_cases.iter()));
+
+    let selector = EpsilonLexicase::new(training_cases.len()).with_variant(Variant::Dynamic);
+
+    let gene_generator = uniform_distribution_of![<PushInstruction>
+        FloatInstruction::Add,
+        FloatInstruction::Subtract,
+        FloatInstruction::Multiply,
+        FloatInstruction::ProtectedDivide,
+        FloatInstruction::Dup,
+        FloatInstruction::Push(OrderedFloat(0.0)),
+        FloatInstruction::Push(OrderedFloat(1.0)),
+        VariableName::from("x")
+    ]
+    .into_gene_generator();
+
+    let umad = Umad::new(0.1, 0.1, &gene_generator);
+
+    // Children inherit the birth generation of their parent, so their age
+    // reflects how long their genetic material has been evolving.
+    let make_new_individual = Select::new(selector).then(InheritBirth::new(
+        GenomeExtractor
+            .then(Mutate::new(umad))
+            .wrap::<GenomeScorer<_, _>>(scorer),
+    ));
+
+    let random_individuals = gene_generator
+        .to_collection_generator(max_initial_instructions)
+        .with_scorer(scorer);
+
+    let mut alps = Alps::new(
+        make_new_individual,
+        random_individuals,
+        layer_size,
+        linear_age_limits(age_gap, num_layers),
+    )
+    .with_injection_interval(age_gap);
+
+    for _ in 0..num_generations {
+        match run_model {
+            RunModel::Serial => alps.serial_next(rng.gen())?,
+            RunModel::Parallel => alps.par_next(rng.gen())?,
+        }
+        // TODO: Change 3 to be the smallest number of digits needed for
+        //  num_generations.
+        println!("Generation {:3}", alps.generation_number());
+        for (index, layer) in alps.layers().iter().enumerate() {
+            let max_age = alps
+                .age_limits()
+                .get(index)
+                .map_or_else(|| "no limit".to_string(), ToString::to_string);
+            let best_error = layer
+                .iter()
+                .max()
+                .map(|best| best.test_results.total_result.error);
+            println!(
+                "  layer {index} (max age {max_age}): {} individuals, best total error \
+                 {best_error:?}",
+                layer.len()
+            );
+        }
+
+        let best = alps.best().context("All the layers were empty")?;
+        if best.test_results.total_result.error == OrderedFloat(0.0) {
+            println!("Best individual is {best:#?}");
+            println!("SUCCESS");
+            return Ok(());
+        }
+    }
+
+    println!("Best individual is {:#?}", alps.best());
+
+    Ok(())
+}
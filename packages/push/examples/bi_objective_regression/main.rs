@@ -0,0 +1,211 @@
+pub mod args;
+
+use std::ops::Not;
+
+use anyhow::{ensure, Result};
+use clap::Parser;
+use ec_core::{
+    distributions::collection::ConvertToCollectionGenerator,
+    generation::Generation,
+    genome::GenomeDistance,
+    individual::{ec::WithScorer, scorer::FnScorer},
+    operator::{
+        genome_extractor::GenomeExtractor,
+        genome_scorer::GenomeScorer,
+        mutator::Mutate,
+        selector::{crowded_tournament::CrowdedTournament, Select},
+        Composable,
+    },
+    pareto::non_dominated_sort,
+    run::{Run, StopReason},
+    statistics::Statistics,
+    survival::Nsga2,
+    test_results::{self, TestResults},
+    uniform_distribution_of,
+};
+use ec_linear::{genome::Linear, mutator::umad::Umad};
+use num_traits::ToPrimitive;
+use ordered_float::OrderedFloat;
+use push::{
+    evaluation::cases::{Case, Cases, WithTarget},
+    genome::plushy::{ConvertToGeneGenerator, Plushy},
+    instruction::{variable_name::VariableName, FloatInstruction, PushInstruction},
+    push_vm::{program::PushProgram, push_state::PushState, HasStack, State},
+};
+use rand::{distributions::Distribution, rngs::StdRng, SeedableRng};
+
+use crate::args::{Args, RunModel};
+
+/*
+ * This is the "simple regression" problem (see the `simple_regression`
+ * example), but with two competing objectives: the total error on the
+ * training cases, and the length of the genome. NSGA-II evolves a set of
+ * trade-offs between the two, from long accurate programs to short
+ * inaccurate ones.
+ */
+
+const PENALTY_VALUE: f64 = 1_000.0;
+
+fn target_fn(input: f64) -> f64 {
+    // x^3 - 2x^2 - x
+    input.powi(3) - 2.0f64.mul_add(input.powi(2), input)
+}
+
+fn build_push_state(
+    program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
+    input: f64,
+) -> PushState {
+    #[allow(clippy::unwrap_used)]
+    PushState::builder()
+        .with_max_stack_size(1000)
+        .with_program(program)
+        // This will return an error if the program is longer than the allowed
+        // max stack size.
+        // We arguably should check that and return an error here.
+        .unwrap()
+        // The Push float stack holds `OrderedFloat`s, so we only convert to
+        // and from those when running the program.
+        .with_float_input("x", OrderedFloat(input))
+        .build()
+}
+
+fn score_program(
+    program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
+    Case { input, output }: Case<f64>,
+) -> f64 {
+    let state = build_push_state(program, input);
+    #[allow(clippy::option_if_let_else)]
+    match state.run_to_completion() {
+        Ok(final_state) => final_state
+            .stack::<OrderedFloat<f64>>()
+            .top()
+            .map_or(PENALTY_VALUE, |answer| (answer.0 - output).abs()),
+
+        Err(_) => {
+            // Do some logging, perhaps?
+            PENALTY_VALUE
+        }
+    }
+}
+
+/// The two objectives (both of which are minimized) are the total error
+/// across the training cases and the length of the genome.
+fn score_genome(
+    genome: &Plushy,
+    training_cases: &Cases<f64>,
+) -> TestResults<test_results::Error<f64>> {
+    let program: Vec<PushProgram> = genome.clone().into();
+
+    let total_error: f64 = training_cases
+        .iter()
+        .map(|&case| score_program(program.iter().cloned(), case))
+        .sum();
+    let length = genome.size().to_f64().unwrap_or(f64::INFINITY);
+
+    vec![total_error, length].into()
+}
+
+fn main() -> Result<()> {
+    let Args {
+        run_model,
+        population_size,
+        max_initial_instructions,
+        num_generations,
+        seed,
+    } = Args::parse();
+
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("Using seed {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // Inputs from -4 (inclusive) to 4 (exclusive) in increments of 0.25.
+    let training_cases = (-4 * 4..4 * 4)
+        .map(|n| f64::from(n) / 4.0)
+        .with_target(|&i| target_fn(i));
+
+    let scorer = FnScorer(|genome: &Plushy| score_genome(genome, &training_cases));
+
+    let gene_generator = uniform_distribution_of![<PushInstruction>
+        FloatInstruction::Add,
+        FloatInstruction::Subtract,
+        FloatInstruction::Multiply,
+        FloatInstruction::ProtectedDivide,
+        VariableName::from("x")
+    ]
+    .into_gene_generator();
+
+    let population = gene_generator
+        .to_collection_generator(max_initial_instructions)
+        .with_scorer(scorer)
+        .into_collection_generator(population_size)
+        .sample(&mut rng);
+
+    ensure!(population.is_empty().not());
+
+    let umad = Umad::new(0.1, 0.1, &gene_generator);
+
+    let make_new_individual = Select::new(CrowdedTournament::new(2))
+        .then(GenomeExtractor)
+        .then(Mutate::new(umad))
+        .wrap::<GenomeScorer<_, _>>(scorer);
+
+    let generation = Generation::new(make_new_individual, population).with_survival(Nsga2);
+
+    let statistics = Statistics::new()
+        .with_genome_length(|genome: &Plushy| genome.size())
+        .with_genome_distance(GenomeDistance::distance);
+
+    // The "best" individual reported by the run is the one with the smallest
+    // sum of the two objectives, which isn't very meaningful here; what we
+    // care about is the final Pareto front, which we print at the end.
+    let mut run = Run::new(generation)
+        .with_max_generations(num_generations)
+        .with_target(|individual| individual.test_results.results[0].error == 0.0)
+        .with_observer(|report| {
+            // TODO: Change 2 to be the smallest number of digits needed for
+            //  num_generations-1.
+            println!("Generation {:2}", report.generation_number);
+            match statistics.compute(report.population) {
+                Ok(statistics) => println!("{statistics}"),
+                Err(error) => eprintln!("Couldn't compute the statistics: {error:#}"),
+            }
+        });
+
+    let summary = match run_model {
+        RunModel::Serial => run.serial_run(&mut rng)?,
+        RunModel::Parallel => run.par_run(&mut rng)?,
+    };
+    println!(
+        "Stopped after {} generations ({:?})",
+        summary.num_generations, summary.stop_reason
+    );
+
+    let population = run.generation().population();
+    let results: Vec<_> = population
+        .iter()
+        .map(|individual| &individual.test_results)
+        .collect();
+    let mut front: Vec<_> = non_dominated_sort(&results)[0]
+        .iter()
+        .map(|&index| &population[index])
+        .collect();
+    front.sort_unstable_by(|x, y| {
+        f64::total_cmp(
+            &x.test_results.results[1].error,
+            &y.test_results.results[1].error,
+        )
+    });
+    front.dedup_by_key(|individual| &individual.test_results.results);
+    println!("The final Pareto front (error, length) is:");
+    for individual in front {
+        let [error, length] =
+            [0, 1].map(|objective| individual.test_results.results[objective].error);
+        println!("  ({error}, {length}): {:?}", individual.genome);
+    }
+
+    if summary.stop_reason == StopReason::TargetReached {
+        println!("SUCCESS");
+    }
+
+    Ok(())
+}
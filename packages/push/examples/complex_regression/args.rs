@@ -26,7 +26,17 @@ pub struct Args {
     #[clap(short, long, value_parser, default_value_t = 100)]
     pub max_genome_length: usize,
 
+    /// Number of training cases to (randomly) sample each generation; all
+    /// the cases are used if this isn't provided
+    #[clap(short = 'c', long, value_parser)]
+    pub num_sampled_cases: Option<usize>,
+
     /// Number of generations to run
     #[clap(short, long, value_parser, default_value_t = 100)]
     pub num_generations: usize,
+
+    /// Seed for the random number generator; a random seed is chosen (and
+    /// printed) if this isn't provided
+    #[clap(short, long, value_parser)]
+    pub seed: Option<u64>,
 }
@@ -6,28 +6,36 @@ use anyhow::{ensure, Result};
 use clap::Parser;
 use ec_core::{
     distributions::collection::ConvertToCollectionGenerator,
+    down_sample::{CaseSample, DownSample, DownSampledScorer, RandomDownSampler},
     generation::Generation,
-    individual::{ec::WithScorer, scorer::FnScorer},
+    genome::GenomeDistance,
+    individual::ec::WithScorer,
     operator::{
         genome_extractor::GenomeExtractor,
         genome_scorer::GenomeScorer,
         mutator::Mutate,
-        selector::{best::Best, lexicase::Lexicase, Select, Selector},
-        Composable,
+        selector::{
+            best::Best,
+            epsilon_lexicase::{EpsilonLexicase, Variant},
+            Select, Selector,
+        },
+        Composable, Describe,
     },
+    run::{Run, StopReason},
+    statistics::Statistics,
+    survival::Generational,
     test_results::{self, TestResults},
     uniform_distribution_of,
 };
-use ec_linear::mutator::umad::Umad;
-use num_traits::Float;
+use ec_linear::{genome::Linear, mutator::umad::Umad};
 use ordered_float::OrderedFloat;
 use push::{
-    evaluation::cases::{Case, Cases, WithTarget},
+    evaluation::cases::{Case, WithTarget},
     genome::plushy::{ConvertToGeneGenerator, Plushy},
     instruction::{variable_name::VariableName, FloatInstruction, PushInstruction},
     push_vm::{program::PushProgram, push_state::PushState, HasStack, State},
 };
-use rand::{prelude::Distribution, thread_rng};
+use rand::{prelude::Distribution, rngs::StdRng, SeedableRng};
 
 use crate::args::{Args, RunModel};
 
@@ -41,17 +49,15 @@ use crate::args::{Args, RunModel};
 // "return" value on the appropriate stack at the end of its execution.
 const PENALTY_VALUE: f64 = 1_000.0;
 
-type Of64 = OrderedFloat<f64>;
-
 /// The target polynomial is (x^3 + 1)^3 + 1
 /// i.e., x^9 + 3x^6 + 3x^3 + 2
-fn target_fn(input: Of64) -> Of64 {
+fn target_fn(input: f64) -> f64 {
     (input.powi(3) + 1.0).powi(3) + 1.0
 }
 
 fn build_push_state(
     program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
-    input: Of64,
+    input: f64,
 ) -> PushState {
     #[allow(clippy::unwrap_used)]
     PushState::builder()
@@ -61,37 +67,38 @@ fn build_push_state(
         // max stack size.
         // We arguably should check that and return an error here.
         .unwrap()
-        .with_float_input("x", input)
+        // The Push float stack holds `OrderedFloat`s, so we only convert to
+        // and from those when running the program.
+        .with_float_input("x", OrderedFloat(input))
         .build()
 }
 
 fn score_program(
     program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
-    Case { input, output }: Case<Of64>,
-) -> Of64 {
+    Case { input, output }: Case<f64>,
+) -> f64 {
     let state = build_push_state(program, input);
     #[allow(clippy::option_if_let_else)]
     match state.run_to_completion() {
         Ok(final_state) => final_state
-            .stack::<Of64>()
+            .stack::<OrderedFloat<f64>>()
             .top()
-            .map_or(Of64::from(PENALTY_VALUE), |answer| (answer - output).abs()),
+            .map_or(PENALTY_VALUE, |answer| (answer.0 - output).abs()),
 
         Err(_) => {
             // Do some logging, perhaps?
-            Of64::from(PENALTY_VALUE)
+            PENALTY_VALUE
         }
     }
 }
 
-fn score_genome(
+fn score_genome<'a>(
     genome: &Plushy,
-    training_cases: &Cases<Of64>,
-) -> TestResults<test_results::Error<Of64>> {
+    training_cases: impl Iterator<Item = &'a Case<f64>>,
+) -> TestResults<test_results::Error<f64>> {
     let program: Vec<PushProgram> = genome.clone().into();
 
     training_cases
-        .iter()
         .map(|&case| score_program(program.iter().cloned(), case))
         .collect()
 }
@@ -103,14 +110,18 @@ fn main() -> Result<()> {
         max_initial_instructions,
         // FIXME: Actually use this
         max_genome_length: _,
+        num_sampled_cases,
         num_generations,
+        seed,
     } = Args::parse();
 
-    let mut rng = thread_rng();
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("Using seed {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
 
     // Inputs from -4 (inclusive) to 4 (exclusive) in increments of 0.25.
     let training_cases = (-4 * 4..4 * 4)
-        .map(|n| Of64::from(n) / 4.0)
+        .map(|n| f64::from(n) / 4.0)
         .with_target(|&i| target_fn(i));
 
     // The range want is -4 1/8, -3 7/8, -3 5/8, ..., 3 7/8, 4 1/8.
@@ -118,19 +129,32 @@ fn main() -> Result<()> {
     // -33, -31, -29, ..., 31, 33.
     let _testing_cases = (-33..=33)
         .step_by(2)
-        .map(|n| Of64::from(n) / 8.0)
+        .map(|n| f64::from(n) / 8.0)
         .with_target(|&i| target_fn(i));
 
+    // Each generation's children are only evaluated on a sample of the
+    // training cases, and a new sample is drawn at the end of each generation.
+    let case_sample = CaseSample::random(
+        training_cases.len(),
+        num_sampled_cases.unwrap_or(training_cases.len()),
+        &mut rng,
+    )?;
+
     /*
      * The `scorer` will need to take an evolved program (sequence of
-     * instructions) and run it on all the inputs from -4 (inclusive) to 4
+     * instructions) and run it on the sampled inputs from -4 (inclusive) to 4
      * (exclusive) in increments of 0.25, collecting together the errors,
      * i.e., the absolute difference between the returned value and the
      * expected value.
      */
-    let scorer = FnScorer(|genome: &Plushy| score_genome(genome, &training_cases));
+    let scorer = DownSampledScorer::new(&case_sample, |genome: &Plushy, indices: &[usize]| {
+        score_genome(genome, training_cases.sampled(indices))
+    });
 
-    let selector = Lexicase::new(training_cases.len());
+    // Exact ties on real-valued errors are rare, so we use dynamic
+    // epsilon-lexicase, which computes its epsilons from the remaining
+    // candidates during each selection.
+    let selector = EpsilonLexicase::new(case_sample.sample_size()).with_variant(Variant::Dynamic);
 
     let gene_generator = uniform_distribution_of![<PushInstruction>
         FloatInstruction::Add,
@@ -162,26 +186,53 @@ fn main() -> Result<()> {
         .then(Mutate::new(umad))
         .wrap::<GenomeScorer<_, _>>(scorer);
 
-    let mut generation = Generation::new(make_new_individual, population);
-
-    // TODO: It might be useful to insert some kind of logging system so we can
-    // make this less imperative in nature.
-
-    for generation_number in 0..num_generations {
-        match run_model {
-            RunModel::Serial => generation.serial_next()?,
-            RunModel::Parallel => generation.par_next()?,
-        }
-
-        let best = Best.select(generation.population(), &mut rng)?;
-        // TODO: Change 2 to be the smallest number of digits needed for
-        // num_generations-1.
-        println!("Generation {generation_number:2} best is {best:#?}");
-
-        if best.test_results.total_result.error == OrderedFloat(0.0) {
-            println!("SUCCESS");
-            break;
-        }
+    println!("Child maker:\n{}", make_new_individual.describe());
+
+    // A new sample is drawn after each generation's survivors are chosen, so
+    // every child is scored once, on the sample in use when it's made.
+    // `Generational` doesn't carry any parents over, so nothing needs to be
+    // re-scored.
+    let generation = Generation::new(make_new_individual, population).with_survival(
+        DownSample::new(Generational, &case_sample, RandomDownSampler, scorer),
+    );
+
+    let statistics = Statistics::new()
+        .with_is_solved(|error: &test_results::Error<f64>| error.error == 0.0)
+        .with_genome_length(|genome: &Plushy| genome.size())
+        .with_genome_distance(GenomeDistance::distance);
+
+    let mut run = Run::new(generation)
+        .with_max_generations(num_generations)
+        // The best individual might only have zero error on the sampled cases,
+        // so we check it against all the training cases.
+        .with_target(|best| {
+            score_genome(&best.genome, training_cases.iter())
+                .total_result
+                .error
+                == 0.0
+        })
+        .with_observer(|report| {
+            // TODO: Change 2 to be the smallest number of digits needed for
+            //  num_generations-1.
+            println!("Generation {:2}", report.generation_number);
+            match statistics.compute(report.population) {
+                Ok(statistics) => println!("{statistics}"),
+                Err(error) => eprintln!("Couldn't compute the statistics: {error:#}"),
+            }
+        });
+
+    let summary = match run_model {
+        RunModel::Serial => run.serial_run(&mut rng)?,
+        RunModel::Parallel => run.par_run(&mut rng)?,
+    };
+    println!(
+        "Stopped after {} generations ({:?})",
+        summary.num_generations, summary.stop_reason
+    );
+    println!("Best individual is {:#?}", summary.best);
+
+    if summary.stop_reason == StopReason::TargetReached {
+        println!("SUCCESS");
     }
 
     Ok(())
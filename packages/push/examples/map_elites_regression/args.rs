@@ -0,0 +1,46 @@
+use clap::Parser;
+
+#[derive(clap::ValueEnum, Copy, Clone, Debug)]
+pub enum RunModel {
+    Serial,
+    Parallel,
+}
+
+/// Simple genetic algorithm in Rust
+#[derive(Parser, Debug, Copy, Clone)]
+#[clap(author, version, about, long_about = None)]
+pub struct Args {
+    /// Should we use parallelism when doing the run?
+    #[clap(short, long, value_enum, default_value_t = RunModel::Parallel)]
+    pub run_model: RunModel,
+
+    /// Number of random individuals used to seed the archive
+    #[clap(short = 'p', long, value_parser, default_value_t = 100)]
+    pub initial_population_size: usize,
+
+    /// Number of children made from the archive each iteration
+    #[clap(short = 'c', long, value_parser, default_value_t = 100)]
+    pub batch_size: usize,
+
+    /// Maximum number of initial instructions
+    #[clap(short = 'i', long, value_parser, default_value_t = 50)]
+    pub max_initial_instructions: usize,
+
+    /// Largest program size that gets its own bins; longer programs share
+    /// the last bin for program size
+    #[clap(short = 'l', long, value_parser, default_value_t = 100)]
+    pub max_program_size: usize,
+
+    /// Number of bins for each of the two features
+    #[clap(short = 'g', long, value_parser, default_value_t = 16)]
+    pub num_bins: usize,
+
+    /// Number of iterations to run
+    #[clap(short, long, value_parser, default_value_t = 100)]
+    pub num_iterations: usize,
+
+    /// Seed for the random number generator; a random seed is chosen (and
+    /// printed) if this isn't provided
+    #[clap(short, long, value_parser)]
+    pub seed: Option<u64>,
+}
@@ -0,0 +1,204 @@
+pub mod args;
+
+use anyhow::{Context, Result};
+use clap::Parser;
+use ec_core::{
+    distributions::collection::ConvertToCollectionGenerator,
+    individual::{
+        ec::{EcIndividual, WithScorer},
+        scorer::FnScorer,
+    },
+    map_elites::{bin, FnFeatures, GridArchive, MapElites},
+    operator::{
+        genome_extractor::GenomeExtractor,
+        genome_scorer::GenomeScorer,
+        mutator::Mutate,
+        selector::{random::Random, Select},
+        Composable,
+    },
+    test_results::{self, TestResults},
+    uniform_distribution_of,
+};
+use ec_linear::{genome::Linear, mutator::umad::Umad};
+use num_traits::{Float, ToPrimitive};
+use ordered_float::OrderedFloat;
+use push::{
+    evaluation::cases::{Case, Cases, WithTarget},
+    genome::plushy::{ConvertToGeneGenerator, Plushy, PushGene},
+    instruction::{variable_name::VariableName, FloatInstruction, PushInstruction},
+    push_vm::{program::PushProgram, push_state::PushState, HasStack, State},
+};
+use rand::{distributions::Distribution, rngs::StdRng, Rng, SeedableRng};
+
+use crate::args::{Args, RunModel};
+
+/*
+ * This is the "simple regression" problem (see the `simple_regression`
+ * example), using MAP-Elites to find the best program for each
+ * combination of program size and instruction mix (the fraction of the
+ * genes that are float instructions, as opposed to reading the input or
+ * closing blocks).
+ */
+
+const PENALTY_VALUE: f64 = 1_000.0;
+
+type Of64 = OrderedFloat<f64>;
+
+fn target_fn(input: Of64) -> Of64 {
+    input.powi(3) - Of64::from(2) * input.powi(2) - input
+}
+
+fn build_push_state(
+    program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
+    input: Of64,
+) -> PushState {
+    #[allow(clippy::unwrap_used)]
+    PushState::builder()
+        .with_max_stack_size(1000)
+        .with_program(program)
+        // This will return an error if the program is longer than the allowed
+        // max stack size.
+        // We arguably should check that and return an error here.
+        .unwrap()
+        .with_float_input("x", input)
+        .build()
+}
+
+fn score_program(
+    program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
+    Case { input, output }: Case<Of64>,
+) -> Of64 {
+    let state = build_push_state(program, input);
+    #[allow(clippy::option_if_let_else)]
+    match state.run_to_completion() {
+        Ok(final_state) => final_state.stack::<Of64>().top().map_or_else(
+            |_| Of64::from(PENALTY_VALUE),
+            |answer| (answer - output).abs(),
+        ),
+
+        Err(_) => {
+            // Do some logging, perhaps?
+            Of64::from(PENALTY_VALUE)
+        }
+    }
+}
+
+fn score_genome(
+    genome: &Plushy,
+    training_cases: &Cases<Of64>,
+) -> TestResults<test_results::Error<Of64>> {
+    let program: Vec<PushProgram> = genome.clone().into();
+
+    training_cases
+        .iter()
+        .map(|&case| score_program(program.iter().cloned(), case))
+        .collect()
+}
+
+/// The fraction of the genes that are float instructions.
+fn float_instruction_fraction(genome: &Plushy) -> f64 {
+    let genes = genome.get_genes();
+    let num_float_instructions = genes
+        .iter()
+        .filter(|gene| {
+            matches!(
+                gene,
+                PushGene::Instruction(PushInstruction::FloatInstruction(_))
+            )
+        })
+        .count();
+    num_float_instructions.to_f64().unwrap_or(f64::NAN) / genes.len().to_f64().unwrap_or(f64::NAN)
+}
+
+fn main() -> Result<()> {
+    let Args {
+        run_model,
+        initial_population_size,
+        batch_size,
+        max_initial_instructions,
+        max_program_size,
+        num_bins,
+        num_iterations,
+        seed,
+    } = Args::parse();
+
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("Using seed {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // Inputs from -4 (inclusive) to 4 (exclusive) in increments of 0.25.
+    let training_cases = (-4 * 4..4 * 4)
+        .map(|n| Of64::from(n) / 4.0)
+        .with_target(|&i| target_fn(i));
+
+    let scorer = FnScorer(|genome: &Plushy| score_genome(genome, &training_cases));
+
+    let features = FnFeatures(|individual: &EcIndividual<Plushy, _>| {
+        let size = individual.genome.size().to_f64().unwrap_or(f64::NAN);
+        vec![
+            bin(
+                size,
+                0.0,
+                max_program_size.to_f64().unwrap_or(f64::NAN),
+                num_bins,
+            ),
+            // Empty programs have a fraction of NaN, and so go in the first bin.
+            bin(
+                float_instruction_fraction(&individual.genome),
+                0.0,
+                1.0,
+                num_bins,
+            ),
+        ]
+    });
+
+    let gene_generator = uniform_distribution_of![<PushInstruction>
+        FloatInstruction::Add,
+        FloatInstruction::Subtract,
+        FloatInstruction::Multiply,
+        FloatInstruction::ProtectedDivide,
+        VariableName::from("x")
+    ]
+    .into_gene_generator();
+
+    let initial_population = gene_generator
+        .to_collection_generator(max_initial_instructions)
+        .with_scorer(scorer)
+        .into_collection_generator(initial_population_size)
+        .sample(&mut rng);
+
+    let umad = Umad::new(0.1, 0.1, &gene_generator);
+
+    let make_new_individual = Select::new(Random)
+        .then(GenomeExtractor)
+        .then(Mutate::new(umad))
+        .wrap::<GenomeScorer<_, _>>(scorer);
+
+    let mut map_elites = MapElites::new(
+        make_new_individual,
+        features,
+        GridArchive::new(vec![num_bins, num_bins]),
+        batch_size,
+    );
+    map_elites.insert_all(initial_population)?;
+
+    for _ in 0..num_iterations {
+        let report = match run_model {
+            RunModel::Serial => map_elites.serial_next(rng.gen())?,
+            RunModel::Parallel => map_elites.par_next(rng.gen())?,
+        };
+        // TODO: Change 3 to be the smallest number of digits needed for
+        //  num_iterations.
+        println!("Iteration {:3}", report.iteration);
+        println!("{report}");
+    }
+
+    let best = map_elites
+        .archive()
+        .iter()
+        .max()
+        .context("The archive was empty")?;
+    println!("Best elite is {best:#?}");
+
+    Ok(())
+}
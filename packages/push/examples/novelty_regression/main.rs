@@ -0,0 +1,201 @@
+pub mod args;
+
+use std::ops::Not;
+
+use anyhow::{ensure, Result};
+use clap::Parser;
+use ec_core::{
+    distributions::collection::ConvertToCollectionGenerator,
+    generation::Generation,
+    genome::GenomeDistance,
+    individual::{
+        ec::{EcIndividual, WithScorer},
+        scorer::FnScorer,
+    },
+    novelty::{ArchivePolicy, FnDescriptor, NoveltyArchive},
+    operator::{
+        genome_extractor::GenomeExtractor,
+        genome_scorer::GenomeScorer,
+        mutator::Mutate,
+        selector::{novelty::Novelty, Select},
+        Composable,
+    },
+    run::{Run, StopReason},
+    statistics::Statistics,
+    test_results::{self, TestResults},
+    uniform_distribution_of,
+};
+use ec_linear::{genome::Linear, mutator::umad::Umad};
+use num_traits::Float;
+use ordered_float::OrderedFloat;
+use push::{
+    evaluation::cases::{Case, Cases, WithTarget},
+    genome::plushy::{ConvertToGeneGenerator, Plushy},
+    instruction::{variable_name::VariableName, FloatInstruction, PushInstruction},
+    push_vm::{program::PushProgram, push_state::PushState, HasStack, State},
+};
+use rand::{distributions::Distribution, rngs::StdRng, SeedableRng};
+
+use crate::args::{Args, RunModel};
+
+/*
+ * This is the "simple regression" problem (see the `simple_regression`
+ * example), but selecting on a blend of novelty and fitness. The behavior
+ * of a program is the vector of its outputs on the training cases, so
+ * programs are novel if they compute functions unlike the ones seen
+ * before.
+ */
+
+const PENALTY_VALUE: f64 = 1_000.0;
+
+type Of64 = OrderedFloat<f64>;
+
+fn target_fn(input: Of64) -> Of64 {
+    input.powi(3) - Of64::from(2) * input.powi(2) - input
+}
+
+fn build_push_state(
+    program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
+    input: Of64,
+) -> PushState {
+    #[allow(clippy::unwrap_used)]
+    PushState::builder()
+        .with_max_stack_size(1000)
+        .with_program(program)
+        // This will return an error if the program is longer than the allowed
+        // max stack size.
+        // We arguably should check that and return an error here.
+        .unwrap()
+        .with_float_input("x", input)
+        .build()
+}
+
+/// The output of the program on the given input, or `PENALTY_VALUE` if
+/// it fails or doesn't leave anything on the float stack.
+fn run_program(
+    program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
+    input: Of64,
+) -> Of64 {
+    let state = build_push_state(program, input);
+    state.run_to_completion().map_or_else(
+        |_| Of64::from(PENALTY_VALUE),
+        |final_state| {
+            final_state
+                .stack::<Of64>()
+                .top()
+                .map_or_else(|_| Of64::from(PENALTY_VALUE), |&answer| answer)
+        },
+    )
+}
+
+fn outputs(genome: &Plushy, training_cases: &Cases<Of64>) -> Vec<Of64> {
+    let program: Vec<PushProgram> = genome.clone().into();
+
+    training_cases
+        .iter()
+        .map(|&Case { input, .. }| run_program(program.iter().cloned(), input))
+        .collect()
+}
+
+fn score_genome(
+    genome: &Plushy,
+    training_cases: &Cases<Of64>,
+) -> TestResults<test_results::Error<Of64>> {
+    outputs(genome, training_cases)
+        .into_iter()
+        .zip(training_cases.iter())
+        .map(|(answer, &Case { output, .. })| (answer - output).abs())
+        .collect()
+}
+
+fn main() -> Result<()> {
+    let Args {
+        run_model,
+        population_size,
+        max_initial_instructions,
+        num_generations,
+        fitness_weight,
+        seed,
+    } = Args::parse();
+
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("Using seed {seed}");
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // Inputs from -4 (inclusive) to 4 (exclusive) in increments of 0.25.
+    let training_cases = (-4 * 4..4 * 4)
+        .map(|n| Of64::from(n) / 4.0)
+        .with_target(|&i| target_fn(i));
+
+    let scorer = FnScorer(|genome: &Plushy| score_genome(genome, &training_cases));
+
+    // This re-runs each program on the training cases, since the individuals
+    // only keep the errors and not the outputs themselves.
+    let descriptor = FnDescriptor(|individual: &EcIndividual<Plushy, _>| {
+        outputs(&individual.genome, &training_cases)
+    });
+    let archive = NoveltyArchive::new(15, ArchivePolicy::MostNovel(5)).with_max_size(1_000);
+
+    let gene_generator = uniform_distribution_of![<PushInstruction>
+        FloatInstruction::Add,
+        FloatInstruction::Subtract,
+        FloatInstruction::Multiply,
+        FloatInstruction::ProtectedDivide,
+        VariableName::from("x")
+    ]
+    .into_gene_generator();
+
+    let population = gene_generator
+        .to_collection_generator(max_initial_instructions)
+        .with_scorer(scorer)
+        .into_collection_generator(population_size)
+        .sample(&mut rng);
+
+    ensure!(population.is_empty().not());
+
+    let umad = Umad::new(0.1, 0.1, &gene_generator);
+
+    let selector = Novelty::new(2, descriptor, &archive).with_fitness_weight(fitness_weight);
+
+    let make_new_individual = Select::new(selector)
+        .then(GenomeExtractor)
+        .then(Mutate::new(umad))
+        .wrap::<GenomeScorer<_, _>>(scorer);
+
+    let generation = Generation::new(make_new_individual, population);
+
+    let statistics = Statistics::new()
+        .with_is_solved(|error: &test_results::Error<Of64>| error.error == OrderedFloat(0.0))
+        .with_genome_length(|genome: &Plushy| genome.size())
+        .with_genome_distance(GenomeDistance::distance);
+
+    let mut run = Run::new(generation)
+        .with_max_generations(num_generations)
+        .with_target(|best| best.test_results.total_result.error == OrderedFloat(0.0))
+        .with_observer(|report| {
+            // TODO: Change 2 to be the smallest number of digits needed for
+            //  num_generations-1.
+            println!("Generation {:2}", report.generation_number);
+            match statistics.compute(report.population) {
+                Ok(statistics) => println!("{statistics}"),
+                Err(error) => eprintln!("Couldn't compute the statistics: {error:#}"),
+            }
+            println!("  archived behaviors: {}", archive.len());
+        });
+
+    let summary = match run_model {
+        RunModel::Serial => run.serial_run(&mut rng)?,
+        RunModel::Parallel => run.par_run(&mut rng)?,
+    };
+    println!(
+        "Stopped after {} generations ({:?})",
+        summary.num_generations, summary.stop_reason
+    );
+    println!("Best individual is {:#?}", summary.best);
+
+    if summary.stop_reason == StopReason::TargetReached {
+        println!("SUCCESS");
+    }
+
+    Ok(())
+}
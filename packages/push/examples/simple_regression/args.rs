@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::Parser;
 
 #[derive(clap::ValueEnum, Copy, Clone, Debug)]
@@ -7,7 +9,7 @@ pub enum RunModel {
 }
 
 /// Simple genetic algorithm in Rust
-#[derive(Parser, Debug, Copy, Clone)]
+#[derive(Parser, Debug, Clone)]
 #[clap(author, version, about, long_about = None)]
 pub struct Args {
     /// Should we use parallelism when doing the run?
@@ -26,7 +28,29 @@ pub struct Args {
     #[clap(short, long, value_parser, default_value_t = 100)]
     pub max_genome_length: usize,
 
+    /// Maximum number of genomes whose scores are cached, so children that
+    /// are copies of an earlier genome don't have to be run again
+    #[clap(short, long, value_parser, default_value_t = 10_000)]
+    pub cache_size: usize,
+
     /// Number of generations to run
     #[clap(short, long, value_parser, default_value_t = 100)]
     pub num_generations: usize,
+
+    /// Seed for the random number generator; a random seed is chosen (and
+    /// printed) if this isn't provided
+    #[clap(short, long, value_parser)]
+    pub seed: Option<u64>,
+
+    /// File to save a checkpoint to after every generation (this needs the
+    /// `serde` feature)
+    #[clap(long, value_parser)]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Checkpoint file to resume an interrupted run from; the population,
+    /// random number generator, and run settings (like the number of
+    /// generations) all come from the checkpoint (this needs the `serde`
+    /// feature)
+    #[clap(long, value_parser)]
+    pub resume: Option<PathBuf>,
 }
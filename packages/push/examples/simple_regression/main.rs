@@ -2,27 +2,34 @@ pub mod args;
 
 use std::ops::Not;
 
-use anyhow::{ensure, Result};
+use anyhow::{anyhow, ensure, Result};
 use clap::Parser;
+#[cfg(feature = "serde")]
+use ec_core::checkpoint::Checkpoint;
 use ec_core::{
     distributions::collection::ConvertToCollectionGenerator,
     generation::Generation,
-    individual::{ec::WithScorer, scorer::FnScorer},
+    genome::GenomeDistance,
+    individual::{
+        ec::WithScorer,
+        scorer::{CachingScorer, FnScorer, FnTryScorer, TryScorer},
+    },
     operator::{
         genome_extractor::GenomeExtractor,
-        genome_scorer::GenomeScorer,
+        genome_scorer::{GenomeScorer, Penalty},
         mutator::Mutate,
         selector::{
             best::Best, lexicase::Lexicase, tournament::Tournament, weighted::Weighted, Select,
             Selector,
         },
-        Composable,
+        Composable, Describe,
     },
-    test_results::{self, TestResults},
+    run::{Run, StopReason},
+    statistics::Statistics,
+    test_results::{self, NanPolicy, TestResults, TestResultsBuilder},
     uniform_distribution_of,
 };
-use ec_linear::mutator::umad::Umad;
-use num_traits::Float;
+use ec_linear::{genome::Linear, mutator::umad::Umad};
 use ordered_float::OrderedFloat;
 use push::{
     evaluation::cases::{Case, Cases, WithTarget},
@@ -30,7 +37,8 @@ use push::{
     instruction::{variable_name::VariableName, FloatInstruction, PushInstruction},
     push_vm::{program::PushProgram, push_state::PushState, HasStack, State},
 };
-use rand::{distributions::Distribution, thread_rng};
+use rand::{distributions::Distribution, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 
 use crate::args::{Args, RunModel};
 
@@ -43,59 +51,58 @@ use crate::args::{Args, RunModel};
 
 const PENALTY_VALUE: f64 = 1_000.0;
 
-type Of64 = OrderedFloat<f64>;
-
-fn target_fn(input: Of64) -> Of64 {
-    input.powi(3) - Of64::from(2) * input.powi(2) - input
+fn target_fn(input: f64) -> f64 {
+    // x^3 - 2x^2 - x
+    input.powi(3) - 2.0f64.mul_add(input.powi(2), input)
 }
 
 fn build_push_state(
     program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
-    input: Of64,
-) -> PushState {
-    #[allow(clippy::unwrap_used)]
-    PushState::builder()
+    input: f64,
+) -> Result<PushState> {
+    Ok(PushState::builder()
         .with_max_stack_size(1000)
-        .with_program(program)
         // This will return an error if the program is longer than the allowed
         // max stack size.
-        // We arguably should check that and return an error here.
-        .unwrap()
-        .with_float_input("x", input)
-        .build()
+        .with_program(program)?
+        // The Push float stack holds `OrderedFloat`s, so we only convert to
+        // and from those when running the program.
+        .with_float_input("x", OrderedFloat(input))
+        .build())
 }
 
 fn score_program(
     program: impl DoubleEndedIterator<Item = PushProgram> + ExactSizeIterator,
-    Case { input, output }: Case<Of64>,
-) -> Of64 {
-    let state = build_push_state(program, input);
-    #[allow(clippy::option_if_let_else)]
-    match state.run_to_completion() {
-        Ok(final_state) => final_state
-            .stack::<Of64>()
-            .top()
-            .map_or(Of64::from(PENALTY_VALUE), |answer| (answer - output).abs()),
-
-        Err(_) => {
-            // Do some logging, perhaps?
-            Of64::from(PENALTY_VALUE)
-        }
-    }
+    Case { input, output }: Case<f64>,
+) -> Result<f64> {
+    let final_state = build_push_state(program, input)?
+        .run_to_completion()
+        .map_err(|error| anyhow!("The program failed: {}", error.error()))?;
+    // A program that doesn't leave an answer on the float stack ran fine, but
+    // gets the penalty value as its error on this case.
+    Ok(final_state
+        .stack::<OrderedFloat<f64>>()
+        .top()
+        .map_or(PENALTY_VALUE, |answer| (answer.0 - output).abs()))
 }
 
+/// Score `genome` on all the training cases, failing if the program fails on
+/// any of them.
 fn score_genome(
     genome: &Plushy,
-    training_cases: &Cases<Of64>,
-) -> TestResults<test_results::Error<Of64>> {
+    training_cases: &Cases<f64>,
+    results_builder: &TestResultsBuilder<f64>,
+) -> Result<TestResults<test_results::Error<f64>>> {
     let program: Vec<PushProgram> = genome.clone().into();
 
-    training_cases
+    let errors = training_cases
         .iter()
         .map(|&case| score_program(program.iter().cloned(), case))
-        .collect()
+        .collect::<Result<Vec<_>>>()?;
+    results_builder.build(errors)
 }
 
+#[allow(clippy::too_many_lines)]
 fn main() -> Result<()> {
     let Args {
         run_model,
@@ -103,16 +110,34 @@ fn main() -> Result<()> {
         max_initial_instructions,
         // FIXME: Actually use this
         max_genome_length: _,
+        cache_size,
         num_generations,
+        seed,
+        checkpoint,
+        resume,
     } = Args::parse();
 
-    let mut rng = thread_rng();
+    #[cfg(not(feature = "serde"))]
+    ensure!(
+        checkpoint.is_none() && resume.is_none(),
+        "Saving or resuming checkpoints needs the `serde` feature"
+    );
+
+    let seed = seed.unwrap_or_else(rand::random);
+    println!("Using seed {seed}");
+    // Unlike `StdRng`, this generator can be saved in a checkpoint.
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
 
     // Inputs from -4 (inclusive) to 4 (exclusive) in increments of 0.25.
     let training_cases = (-4 * 4..4 * 4)
-        .map(|n| Of64::from(n) / 4.0)
+        .map(|n| f64::from(n) / 4.0)
         .with_target(|&i| target_fn(i));
 
+    // Errors are plain `f64`s, so a program whose answer overflows can have
+    // a NaN error on some cases; those get the penalty value instead.
+    let results_builder =
+        TestResultsBuilder::new().with_nan_policy(NanPolicy::Penalty(PENALTY_VALUE));
+
     /*
      * The `scorer` will need to take an evolved program (sequence of
      * instructions) and run it on all the inputs from -4 (inclusive) to 4
@@ -121,8 +146,16 @@ fn main() -> Result<()> {
      * expected value.
      *
      * The target polynomial is x^3 - 2x^2 - x
+     *
+     * Programs that fail (e.g., by overflowing a stack) can't be scored, and
+     * are given the penalty value on every case instead.
      */
-    let scorer = FnScorer(|genome: &Plushy| score_genome(genome, &training_cases));
+    let scorer = CachingScorer::new(
+        FnTryScorer(|genome: &Plushy| score_genome(genome, &training_cases, &results_builder)),
+        cache_size,
+    );
+    let penalty: TestResults<test_results::Error<f64>> =
+        training_cases.iter().map(|_| PENALTY_VALUE).collect();
 
     let num_test_cases = 10;
 
@@ -139,44 +172,102 @@ fn main() -> Result<()> {
     ]
     .into_gene_generator();
 
-    let population = gene_generator
-        .to_collection_generator(max_initial_instructions)
-        .with_scorer(scorer)
-        .into_collection_generator(population_size)
-        .sample(&mut rng);
+    let initial_population = |rng: &mut ChaCha8Rng| -> Result<_> {
+        let population = gene_generator
+            .to_collection_generator(max_initial_instructions)
+            // Generating individuals can't fail, so we apply the penalty by
+            // hand for the initial population.
+            .with_scorer(FnScorer(|genome: &Plushy| {
+                scorer.try_score(genome).unwrap_or_else(|_| penalty.clone())
+            }))
+            .into_collection_generator(population_size)
+            .sample(rng);
 
-    ensure!(population.is_empty().not());
+        ensure!(population.is_empty().not());
 
-    let best = Best.select(&population, &mut rng)?;
-    println!("Best initial individual is {best:?}");
+        let best = Best.select(&population, rng)?;
+        println!("Best initial individual is {best:?}");
+
+        Ok(population)
+    };
 
     let umad = Umad::new(0.1, 0.1, &gene_generator);
 
-    let make_new_individual = Select::new(selector)
-        .then(GenomeExtractor)
-        .then(Mutate::new(umad))
-        .wrap::<GenomeScorer<_, _>>(scorer);
+    let make_new_individual = GenomeScorer::fallible(
+        Select::new(selector)
+            .then(GenomeExtractor)
+            .then(Mutate::new(umad)),
+        &scorer,
+    )
+    .with_on_error(Penalty(penalty.clone()));
+    let score_errors = make_new_individual.error_count().clone();
+
+    println!("Child maker:\n{}", make_new_individual.describe());
 
-    let mut generation = Generation::new(make_new_individual, population);
+    let statistics = Statistics::new()
+        .with_is_solved(|error: &test_results::Error<f64>| *error == 0.0.into())
+        .with_genome_length(|genome: &Plushy| genome.size())
+        .with_genome_distance(GenomeDistance::distance);
 
-    // TODO: It might be useful to insert some kind of logging system so we can
-    // make this less imperative in nature.
+    // Resuming from a checkpoint replaces the initial population, the random
+    // number generator, and the run settings with the saved ones.
+    #[cfg(feature = "serde")]
+    let (run, mut rng) = if let Some(path) = &resume {
+        let (run, rng) = Run::from_checkpoint(Checkpoint::load(path)?, |population| {
+            Generation::new(make_new_individual, population)
+        });
+        println!("Resuming from {}", path.display());
+        (run, rng)
+    } else {
+        let population = initial_population(&mut rng)?;
+        let run = Run::new(Generation::new(make_new_individual, population))
+            .with_max_generations(num_generations);
+        (run, rng)
+    };
+    #[cfg(not(feature = "serde"))]
+    let run = Run::new(Generation::new(
+        make_new_individual,
+        initial_population(&mut rng)?,
+    ))
+    .with_max_generations(num_generations);
 
-    for generation_number in 0..num_generations {
-        match run_model {
-            RunModel::Serial => generation.serial_next()?,
-            RunModel::Parallel => generation.par_next()?,
-        }
+    let mut run = run
+        .with_target(|best| best.test_results.total_result == 0.0.into())
+        .with_score_error_count(score_errors)
+        .with_observer(|report| {
+            // TODO: Change 2 to be the smallest number of digits needed for
+            //  num_generations-1.
+            println!(
+                "Generation {:2} ({} programs failed so far)",
+                report.generation_number, report.num_score_errors
+            );
+            match statistics.compute(report.population) {
+                Ok(statistics) => println!("{statistics}"),
+                Err(error) => eprintln!("Couldn't compute the statistics: {error:#}"),
+            }
+        });
 
-        let best = Best.select(generation.population(), &mut rng)?;
-        // TODO: Change 2 to be the smallest number of digits needed for
-        // num_generations-1.
-        println!("Generation {generation_number:2} best is {best:#?}");
+    #[cfg(feature = "serde")]
+    let summary = match (run_model, &checkpoint) {
+        (RunModel::Serial, Some(path)) => run.serial_run_with_checkpoints(&mut rng, path, 1)?,
+        (RunModel::Parallel, Some(path)) => run.par_run_with_checkpoints(&mut rng, path, 1)?,
+        (RunModel::Serial, None) => run.serial_run(&mut rng)?,
+        (RunModel::Parallel, None) => run.par_run(&mut rng)?,
+    };
+    #[cfg(not(feature = "serde"))]
+    let summary = match run_model {
+        RunModel::Serial => run.serial_run(&mut rng)?,
+        RunModel::Parallel => run.par_run(&mut rng)?,
+    };
+    println!(
+        "Stopped after {} generations ({:?}); {} programs failed",
+        summary.num_generations, summary.stop_reason, summary.num_score_errors
+    );
+    println!("Best individual is {:#?}", summary.best);
+    println!("Score cache: {}", scorer.stats());
 
-        if best.test_results.total_result.error == OrderedFloat(0.0) {
-            println!("SUCCESS");
-            break;
-        }
+    if summary.stop_reason == StopReason::TargetReached {
+        println!("SUCCESS");
     }
 
     Ok(())
@@ -0,0 +1,112 @@
+//! The mutators for [`Plushy`] genomes that can be named in a run config
+//! (see [`ec_core::config`]).
+
+use anyhow::{ensure, Result};
+use ec_core::{
+    config::{ComponentConfig, Registry, SharedMutator},
+    distributions::conversion::IntoDistribution,
+};
+
+use crate::{
+    genome::plushy::{ConvertToGeneGenerator, Plushy},
+    instruction::PushInstruction,
+};
+
+/// A registry of the mutators for [`Plushy`] genomes:
+///
+/// | Name   | Parameters                                                                         |
+/// |--------|------------------------------------------------------------------------------------|
+/// | `umad` | `addition_rate`, `deletion_rate`, `instructions`, optional `empty_addition_rate` and `close_probability` |
+///
+/// `instructions` is the list of instructions that new genes are chosen
+/// from (uniformly), in the same format they're serialized in, e.g.,
+/// `[{ FloatInstruction = "Add" }, { FloatInstruction = "Multiply" }]` in
+/// TOML. If the `close_probability` is omitted, a close is as likely as any
+/// single instruction.
+#[must_use]
+pub fn mutators() -> Registry<SharedMutator<Plushy>> {
+    Registry::new().with("umad", |config, _| umad(config))
+}
+
+fn umad(config: &ComponentConfig) -> Result<SharedMutator<Plushy>> {
+    let instructions: Vec<PushInstruction> = config.parameter("instructions")?;
+    ensure!(
+        !instructions.is_empty(),
+        "UMAD needs at least one instruction to add"
+    );
+    let instructions = instructions.into_distribution()?;
+    let close_probability: Option<f32> = config.optional_parameter("close_probability")?;
+    let gene_generator = match close_probability {
+        Some(close_probability) => {
+            instructions.into_gene_generator_with_close_probability(close_probability)
+        }
+        None => instructions.into_gene_generator(),
+    };
+    Ok(SharedMutator::new(ec_linear::config::umad(
+        config,
+        &["instructions", "close_probability"],
+        gene_generator,
+    )?))
+}
+
+#[cfg(test)]
+mod tests {
+    use ec_core::operator::{mutator::Mutator, Describe};
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+    use crate::{genome::plushy::PushGene, instruction::FloatInstruction};
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn umad_adds_the_configured_instructions() {
+        let config: ComponentConfig = serde_json::from_str(
+            r#"{
+                "name": "umad",
+                "addition_rate": 1.0,
+                "deletion_rate": 0.0,
+                "close_probability": 0.0,
+                "instructions": [{ "FloatInstruction": "Add" }]
+            }"#,
+        )
+        .unwrap();
+        let umad = mutators().build(&config).unwrap();
+        assert_eq!(
+            umad.describe().to_string(),
+            "Umad(addition_rate = 1, deletion_rate = 0, empty_addition_rate = 1)"
+        );
+        let child = umad
+            .mutate(Plushy::new([]), &mut StdRng::seed_from_u64(0))
+            .unwrap();
+        assert_eq!(
+            child.get_genes(),
+            [PushGene::Instruction(FloatInstruction::Add.into())]
+        );
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn umad_needs_instructions() {
+        let config = ComponentConfig::new("umad")
+            .with_parameter("addition_rate", 0.1)
+            .with_parameter("deletion_rate", 0.1)
+            .with_parameter("instructions", Vec::<serde_json::Value>::new());
+        assert!(mutators().build(&config).is_err());
+    }
+
+    #[test]
+    fn umad_rejects_unused_parameters() {
+        let config = ComponentConfig::new("umad")
+            .with_parameter("addition_rate", 0.1)
+            .with_parameter("deletion_rate", 0.1)
+            .with_parameter(
+                "instructions",
+                vec![serde_json::json!({ "FloatInstruction": "Add" })],
+            )
+            .with_parameter("close_probabilty", 0.1);
+        let Err(error) = mutators().build(&config) else {
+            panic!("Built UMAD with a misspelled parameter");
+        };
+        assert!(format!("{error:#}").contains("`close_probabilty`"));
+    }
+}
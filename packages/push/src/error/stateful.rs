@@ -47,6 +47,10 @@ impl<S, E, Severity: ErrorSeverity> StatefulError<S, E, Severity> {
             _p: PhantomData,
         }
     }
+
+    pub const fn error(&self) -> &E {
+        &self.error
+    }
 }
 
 impl<S, E, Severity: ErrorSeverity> IntoState<S> for StatefulError<S, E, Severity> {
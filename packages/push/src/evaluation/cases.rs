@@ -67,6 +67,23 @@ impl<Input, Output> Cases<Input, Output> {
         self.cases.iter_mut()
     }
 
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&Case<Input, Output>> {
+        self.cases.get(index)
+    }
+
+    /// Iterate over just the cases with the given `indices`, e.g., the
+    /// cases in a down-sample, in the order the indices are given.
+    ///
+    /// # Panics
+    /// The iterator will panic if any index is out of bounds.
+    pub fn sampled<'a>(
+        &'a self,
+        indices: &'a [usize],
+    ) -> impl Iterator<Item = &'a Case<Input, Output>> + 'a {
+        indices.iter().map(|&index| &self.cases[index])
+    }
+
     #[must_use]
     pub fn is_empty(&self) -> bool {
         self.cases.is_empty()
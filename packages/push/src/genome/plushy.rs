@@ -1,14 +1,15 @@
 use easy_cast::ConvApprox;
 use ec_core::{
     distributions::{choices::ChoicesDistribution, collection::CollectionGenerator},
-    genome::Genome,
+    genome::{levenshtein_distance, Genome, GenomeDistance, GenomeSize},
 };
 use ec_linear::genome::Linear;
 use rand::{prelude::Distribution, Rng};
 
 use crate::instruction::PushInstruction;
 
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PushGene {
     Close,
     Instruction(PushInstruction),
@@ -147,7 +148,8 @@ where
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Plushy {
     genes: Vec<PushGene>,
 }
@@ -172,11 +174,21 @@ impl Genome for Plushy {
     type Gene = PushGene;
 }
 
-impl Linear for Plushy {
-    fn size(&self) -> usize {
+/// The Levenshtein (edit) distance, i.e., the number of genes that have to
+/// be inserted, deleted, or replaced to turn one genome into the other.
+impl GenomeDistance for Plushy {
+    fn distance(&self, other: &Self) -> f64 {
+        f64::conv_approx(levenshtein_distance(&self.genes, &other.genes))
+    }
+}
+
+impl GenomeSize for Plushy {
+    fn genome_size(&self) -> usize {
         self.genes.len()
     }
+}
 
+impl Linear for Plushy {
     fn gene_mut(&mut self, index: usize) -> Option<&mut Self::Gene> {
         self.genes.get_mut(index)
     }
@@ -215,7 +227,12 @@ impl FromIterator<PushGene> for Plushy {
 #[cfg(test)]
 mod test {
     use ec_core::{
-        distributions::collection::ConvertToCollectionGenerator, operator::mutator::Mutator,
+        distributions::collection::ConvertToCollectionGenerator,
+        individual::{comparator::LexicographicParsimony, ec::EcIndividual},
+        operator::{
+            mutator::Mutator,
+            selector::{tournament::Tournament, Selector},
+        },
         uniform_distribution_of,
     };
     use ec_linear::mutator::umad::Umad;
@@ -295,4 +312,40 @@ mod test {
     }
 
     // TODO: Test that `Umad` works here on Plushy genomes.
+
+    #[test]
+    fn distance_is_the_edit_distance() {
+        let parent = Plushy::new(vec_into![
+            IntInstruction::Add,
+            BoolInstruction::And,
+            IntInstruction::Multiply,
+        ]);
+        let child = Plushy::new(vec_into![
+            BoolInstruction::And,
+            IntInstruction::Multiply,
+            PushGene::Close,
+        ]);
+
+        assert!(parent.distance(&parent).abs() < f64::EPSILON);
+        // Delete the `Add` and add the `Close`.
+        assert!((parent.distance(&child) - 2.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn parsimony_prefers_smaller_programs_with_the_same_results() {
+        let bloated = Plushy::new(vec_into![
+            IntInstruction::Add,
+            PushGene::Close,
+            PushGene::Close,
+        ]);
+        let lean = Plushy::new(vec_into![IntInstruction::Add]);
+        assert_eq!(lean.genome_size(), 1);
+        let population = vec![EcIndividual::new(bloated, 7), EcIndividual::new(lean, 7)];
+        let winner = Tournament::new(2)
+            .with_comparator(LexicographicParsimony)
+            .select(&population, &mut thread_rng())
+            .unwrap();
+        assert_eq!(winner.genome.size(), 1);
+    }
 }
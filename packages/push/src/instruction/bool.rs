@@ -8,7 +8,8 @@ use crate::{
     push_vm::stack::{HasStack, StackPush},
 };
 
-#[derive(Debug, strum_macros::Display, Clone, PartialEq, Eq, EnumIter)]
+#[derive(Debug, strum_macros::Display, Clone, PartialEq, Eq, Hash, EnumIter)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum BoolInstruction {
     Push(bool),
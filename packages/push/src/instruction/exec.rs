@@ -13,7 +13,8 @@ use crate::instruction::PushInstruction;
  * followed by another copy of exec_while.
  */
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ExecInstruction {
     Dup,
     IfElse,
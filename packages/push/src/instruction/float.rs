@@ -10,7 +10,8 @@ use crate::{
     },
 };
 
-#[derive(Debug, strum_macros::Display, Copy, Clone, EnumIter, Eq, PartialEq)]
+#[derive(Debug, strum_macros::Display, Copy, Clone, EnumIter, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum FloatInstruction {
     Push(OrderedFloat<f64>),
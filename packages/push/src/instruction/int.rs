@@ -8,7 +8,8 @@ use crate::{
     push_vm::stack::{HasStack, Stack, StackDiscard, StackError, StackPush},
 };
 
-#[derive(Debug, strum_macros::Display, Copy, Clone, PartialEq, Eq, EnumIter)]
+#[derive(Debug, strum_macros::Display, Copy, Clone, PartialEq, Eq, Hash, EnumIter)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum IntInstruction {
     Push(i64),
@@ -55,7 +55,8 @@ impl<S, E> Instruction<S> for Box<dyn Instruction<S, Error = E>> {
     }
 }
 
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum PushInstruction {
     InputVar(VariableName),
@@ -3,6 +3,7 @@ use std::{fmt::Display, sync::Arc};
 use super::PushInstruction;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VariableName(Arc<str>);
 
 impl From<&str> for VariableName {
@@ -1,3 +1,5 @@
+#[cfg(feature = "config")]
+pub mod config;
 pub mod error;
 pub mod evaluation;
 pub mod genome;